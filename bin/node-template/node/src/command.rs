@@ -33,7 +33,7 @@ pub fn run(version: VersionInfo) -> error::Result<()> {
 			chain_spec::load_spec,
 			|config: _| Ok(new_full_start!(config).0),
 			&version,
-		),
+		).map(print_chain_list),
 		None => sc_cli::run(
 			config,
 			opt.run,
@@ -41,6 +41,13 @@ pub fn run(version: VersionInfo) -> error::Result<()> {
 			service::new_full,
 			chain_spec::load_spec,
 			&version,
-		)
+		).map(print_chain_list),
+	}
+}
+
+/// Print the chains `sc_cli` listed instead of running a command, if it did.
+fn print_chain_list(initialized: sc_cli::Initialized) {
+	if let sc_cli::Initialized::ListChains(chains) = initialized {
+		print!("{}", chains);
 	}
 }
@@ -19,6 +19,8 @@ fn main() -> Result<(), error::Error> {
 		description: "Template Node",
 		support_url: "support.anonymous.an",
 		copyright_start_year: 2017,
+		chains: &["dev", "local"],
+		..Default::default()
 	};
 
 	command::run(version)
@@ -30,6 +30,8 @@ fn main() -> Result<(), sc_cli::error::Error> {
 		description: "Generic substrate node",
 		support_url: "https://github.com/paritytech/substrate/issues/new",
 		copyright_start_year: 2017,
+		chains: &["dev", "local", "fir", "flaming-fir", "staging"],
+		..Default::default()
 	};
 
 	node_cli::run(std::env::args(), version)
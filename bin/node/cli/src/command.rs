@@ -38,9 +38,14 @@ where
 			service::new_full,
 			load_spec,
 			&version,
-		),
+		).map(print_chain_list),
 		Some(Subcommand::Inspect(cmd)) => {
-			cmd.init(&mut config, load_spec, &version)?;
+			if let sc_cli::Initialized::ListChains(chains) =
+				cmd.init(&mut config, load_spec, &version)?
+			{
+				print!("{}", chains);
+				return Ok(());
+			}
 
 			let client = sc_service::new_full_client::<
 				node_runtime::Block, node_runtime::RuntimeApi, node_executor::Executor, _, _,
@@ -50,13 +55,24 @@ where
 			cmd.run(inspect)
 		},
 		Some(Subcommand::Benchmark(cmd)) => {
-			cmd.init(&mut config, load_spec, &version)?;
+			if let sc_cli::Initialized::ListChains(chains) =
+				cmd.init(&mut config, load_spec, &version)?
+			{
+				print!("{}", chains);
+				return Ok(());
+			}
 
 			cmd.run::<_, _, node_runtime::Block, node_executor::Executor>(config)
 		},
 		Some(Subcommand::Factory(cli_args)) => {
 			sc_cli::init(&cli_args.shared_params, &version)?;
-			sc_cli::init_config(&mut config, &cli_args.shared_params, &version, load_spec)?;
+			let shared_params = &cli_args.shared_params;
+			if let sc_cli::Initialized::ListChains(chains) =
+				sc_cli::init_config(&mut config, shared_params, &version, load_spec)?
+			{
+				print!("{}", chains);
+				return Ok(());
+			}
 			sc_cli::fill_import_params(
 				&mut config,
 				&cli_args.import_params,
@@ -102,6 +118,13 @@ where
 			load_spec,
 			|config: service::NodeConfiguration| Ok(new_full_start!(config).0),
 			&version,
-		),
+		).map(print_chain_list),
+	}
+}
+
+/// Print the chains `sc_cli` listed instead of running a command, if it did.
+fn print_chain_list(initialized: sc_cli::Initialized) {
+	if let sc_cli::Initialized::ListChains(chains) = initialized {
+		print!("{}", chains);
 	}
 }
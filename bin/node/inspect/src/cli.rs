@@ -72,11 +72,14 @@ impl InspectCmd {
 		config: &mut sc_service::config::Configuration<G, E>,
 		spec_factory: impl FnOnce(&str) -> Result<Option<sc_service::ChainSpec<G, E>>, String>,
 		version: &sc_cli::VersionInfo,
-	) -> error::Result<()> where
+	) -> error::Result<sc_cli::Initialized> where
 		G: sc_service::RuntimeGenesis,
 		E: sc_service::ChainSpecExtension,
 	{
-		sc_cli::init_config(config, &self.shared_params, version, spec_factory)?;
+		let initialized = sc_cli::init_config(config, &self.shared_params, version, spec_factory)?;
+		if initialized != sc_cli::Initialized::Run {
+			return Ok(initialized);
+		}
 		// make sure to configure keystore
 		sc_cli::fill_config_keystore_in_memory(config)?;
 		// and all import params (especially pruning that has to match db meta)
@@ -86,7 +89,7 @@ impl InspectCmd {
 			sc_service::Roles::FULL,
 			self.shared_params.dev,
 		)?;
-		Ok(())
+		Ok(sc_cli::Initialized::Run)
 	}
 
 	/// Run the inspect command, passing the inspector.
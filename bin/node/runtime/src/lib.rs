@@ -818,22 +818,45 @@ impl_runtime_apis! {
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
 			module: Vec<u8>,
-			extrinsic: Vec<u8>,
-			steps: u32,
-			repeat: u32,
-		) -> Option<Vec<frame_benchmarking::BenchmarkResults>> {
+			request: frame_benchmarking::BenchmarkRequest,
+		) -> Option<frame_benchmarking::BenchmarkBatch> {
 			use frame_benchmarking::Benchmarking;
 
-			match module.as_slice() {
-				b"pallet-balances" | b"balances" => Balances::run_benchmark(extrinsic, steps, repeat).ok(),
-				b"pallet-identity" | b"identity" => Identity::run_benchmark(extrinsic, steps, repeat).ok(),
-				b"pallet-timestamp" | b"timestamp" => Timestamp::run_benchmark(extrinsic, steps, repeat).ok(),
-				_ => None,
-			}
+			let results = match module.as_slice() {
+				b"pallet-balances" | b"balances" => Balances::run_benchmark(&request),
+				b"pallet-identity" | b"identity" => Identity::run_benchmark(&request),
+				b"pallet-timestamp" | b"timestamp" => Timestamp::run_benchmark(&request),
+				_ => return None,
+			};
+
+			results.ok().map(|results| frame_benchmarking::BenchmarkBatch {
+				pallet: module,
+				benchmark: request.name,
+				results,
+			})
+		}
+
+		fn list_benchmarks() -> Vec<(Vec<u8>, Vec<frame_benchmarking::BenchmarkMetadata>)> {
+			benchmarked_pallets()
+		}
+
+		fn benchmark_manifest() -> Vec<frame_benchmarking::BenchmarkMeta> {
+			frame_benchmarking::benchmark_manifest(benchmarked_pallets())
 		}
 	}
 }
 
+/// The benchmarks of every pallet of the runtime, along with the name of the pallet.
+fn benchmarked_pallets() -> Vec<(Vec<u8>, Vec<frame_benchmarking::BenchmarkMetadata>)> {
+	use frame_benchmarking::Benchmarking;
+
+	vec![
+		(b"balances".to_vec(), Balances::benchmarks()),
+		(b"identity".to_vec(), Identity::benchmarks()),
+		(b"timestamp".to_vec(), Timestamp::benchmarks()),
+	]
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
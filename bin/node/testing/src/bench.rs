@@ -25,7 +25,7 @@ use std::{sync::Arc, path::Path, collections::BTreeMap};
 use node_primitives::Block;
 use crate::client::{Client, Backend};
 use crate::keyring::*;
-use sc_client_db::PruningMode;
+use sc_client_db::{KeepBlocks, PruningMode};
 use sc_executor::{NativeExecutor, WasmExecutionMethod};
 use sp_consensus::{
 	BlockOrigin, BlockImport, BlockImportParams,
@@ -142,6 +142,8 @@ impl BenchDb {
 			state_cache_size: 16*1024*1024,
 			state_cache_child_ratio: Some((0, 100)),
 			pruning: PruningMode::ArchiveAll,
+			unsafe_pruning: false,
+			keep_blocks: KeepBlocks::All,
 			source: sc_client_db::DatabaseSettingsSrc::Path {
 				path: dir.into(),
 				cache_size: None,
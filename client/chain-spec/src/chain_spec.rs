@@ -18,8 +18,9 @@
 
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::fs::File;
-use std::path::PathBuf;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use serde::{Serialize, Deserialize};
 use sp_core::storage::{StorageKey, StorageData, ChildInfo, Storage, StorageChild};
@@ -45,6 +46,32 @@ impl<G> Clone for GenesisSource<G> {
 	}
 }
 
+/// The first bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Read the spec file at `path`, decompressing it if needed.
+fn read_spec_file(path: &Path) -> Result<Vec<u8>, String> {
+	let bytes = fs::read(path)
+		.map_err(|e| format!("Error opening spec file: {}", e))?;
+	decompress_spec(path, bytes)
+}
+
+/// Returns the JSON of the spec file at `path` with the contents `bytes`, decompressing them
+/// if the file is gzip-compressed, i.e. has a `.gz` extension or starts like a gzip stream.
+pub fn decompress_spec(path: &Path, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+	let compressed = path.extension().map_or(false, |ext| ext == "gz")
+		|| bytes.starts_with(&GZIP_MAGIC);
+	if !compressed {
+		return Ok(bytes);
+	}
+
+	let mut json = Vec::new();
+	flate2::read::GzDecoder::new(&bytes[..])
+		.read_to_end(&mut json)
+		.map_err(|e| format!("Error decompressing spec file: {}", e))?;
+	Ok(json)
+}
+
 impl<G: RuntimeGenesis> GenesisSource<G> {
 	fn resolve(&self) -> Result<Genesis<G>, String> {
 		#[derive(Serialize, Deserialize)]
@@ -54,9 +81,8 @@ impl<G: RuntimeGenesis> GenesisSource<G> {
 
 		match self {
 			GenesisSource::File(path) => {
-				let file = File::open(path)
-					.map_err(|e| format!("Error opening spec file: {}", e))?;
-				let genesis: GenesisContainer<G> = json::from_reader(file)
+				let bytes = read_spec_file(path)?;
+				let genesis: GenesisContainer<G> = json::from_slice(&bytes)
 					.map_err(|e| format!("Error parsing spec file: {}", e))?;
 				Ok(genesis.genesis)
 			},
@@ -214,6 +240,11 @@ impl<G, E> ChainSpec<G, E> {
 		&self.client_spec.extensions
 	}
 
+	/// Returns a mutable reference to defined chain spec extensions.
+	pub fn extensions_mut(&mut self) -> &mut E {
+		&mut self.client_spec.extensions
+	}
+
 	/// Create hardcoded spec.
 	pub fn from_genesis<F: Fn() -> G + 'static + Send + Sync>(
 		name: &str,
@@ -257,10 +288,11 @@ impl<G, E: serde::de::DeserializeOwned> ChainSpec<G, E> {
 	}
 
 	/// Parse json file into a `ChainSpec`
+	///
+	/// The file may be gzip-compressed.
 	pub fn from_json_file(path: PathBuf) -> Result<Self, String> {
-		let file = File::open(&path)
-			.map_err(|e| format!("Error opening spec file: {}", e))?;
-		let client_spec = json::from_reader(file)
+		let bytes = read_spec_file(&path)?;
+		let client_spec = json::from_slice(&bytes)
 			.map_err(|e| format!("Error parsing spec file: {}", e))?;
 		Ok(ChainSpec {
 			client_spec,
@@ -347,6 +379,47 @@ mod tests {
 		assert_eq!(spec1.to_json(false), spec2.to_json(false));
 	}
 
+	fn write_gzipped(path: &Path, json: &[u8]) -> Vec<u8> {
+		use std::io::Write;
+
+		let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+		encoder.write_all(json).unwrap();
+		let compressed = encoder.finish().unwrap();
+		fs::write(path, &compressed).unwrap();
+		compressed
+	}
+
+	#[test]
+	fn should_deserialize_gzipped_chain_spec() {
+		let dir = tempfile::tempdir().unwrap();
+		let json = include_bytes!("../res/chain_spec.json");
+		let plain = TestSpec::from_json_bytes(Cow::Owned(json.to_vec())).unwrap();
+
+		let path = dir.path().join("chain_spec.json.gz");
+		write_gzipped(&path, json);
+		let spec = TestSpec::from_json_file(path).unwrap();
+		assert_eq!(plain.to_json(false), spec.to_json(false));
+
+		// Compressed specs are recognised without the extension, too.
+		let path = dir.path().join("chain_spec.json");
+		write_gzipped(&path, json);
+		let spec = TestSpec::from_json_file(path).unwrap();
+		assert_eq!(spec.name(), "Flaming Fir");
+	}
+
+	#[test]
+	fn should_reject_truncated_gzipped_chain_spec() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("chain_spec.json.gz");
+		let compressed = write_gzipped(&path, include_bytes!("../res/chain_spec.json"));
+		fs::write(&path, &compressed[..compressed.len() / 2]).unwrap();
+
+		match TestSpec::from_json_file(path) {
+			Err(e) => assert!(e.starts_with("Error decompressing spec file"), "{}", e),
+			Ok(_) => panic!("a truncated spec must not be loaded"),
+		}
+	}
+
 	#[derive(Debug, Serialize, Deserialize)]
 	#[serde(rename_all = "camelCase")]
 	struct Extension1 {
@@ -0,0 +1,152 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Defaults for the shared parameters read from the file given by `--config`.
+//!
+//! The file is a TOML table if its extension is `toml` and a JSON object otherwise. Its keys
+//! are the long names of the flags of [`SharedParams`](crate::SharedParams), in kebab or snake
+//! case. Every key whose flag isn't on the command line is turned into that flag, so the command
+//! line takes precedence over the file.
+
+use std::{ffi::OsString, fs, path::{Path, PathBuf}};
+use serde_json::Value;
+
+/// The long name of the flag giving the config file.
+const CONFIG_FLAG: &str = "--config";
+
+/// The long names of the flags which can be set in the config file, along with their short
+/// names, if any.
+const SHARED_PARAMS_KEYS: &[(&str, Option<&str>)] = &[
+	("chain", None),
+	("dev", None),
+	("base-path", Some("-d")),
+	("database-path", None),
+	("log", Some("-l")),
+	("detailed-log-output", None),
+	("chain-spec-extension", None),
+];
+
+/// Returns `args` with the flags set by the config file given by `--config` appended to them.
+///
+/// Flags already in `args` are left out, as are keys which don't name a shared parameter, see
+/// [`unknown_keys`].
+pub fn with_config_file<I>(args: I) -> Result<Vec<OsString>, String> where
+	I: IntoIterator,
+	I::Item: Into<OsString>,
+{
+	let mut args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+	let path = match config_path(&args) {
+		Some(path) => path,
+		None => return Ok(args),
+	};
+
+	let mut added = Vec::new();
+	for (key, value) in read_config_file(&path)? {
+		let short = match SHARED_PARAMS_KEYS.iter().find(|(long, _)| *long == key) {
+			Some((_, short)) => *short,
+			None => continue,
+		};
+		if !is_given(&args, &key, short) {
+			added.extend(value_args(&key, &value)?);
+		}
+	}
+	args.extend(added.into_iter().map(OsString::from));
+	Ok(args)
+}
+
+/// Returns the keys of the config file at `path` which don't name a shared parameter.
+pub fn unknown_keys(path: &Path) -> Result<Vec<String>, String> {
+	Ok(read_config_file(path)?
+		.into_iter()
+		.map(|(key, _)| key)
+		.filter(|key| SHARED_PARAMS_KEYS.iter().all(|(long, _)| long != key))
+		.collect())
+}
+
+/// Returns the value of the `--config` flag in `args`, if given.
+fn config_path(args: &[OsString]) -> Option<PathBuf> {
+	let prefix = format!("{}=", CONFIG_FLAG);
+	args.iter().enumerate().find_map(|(i, arg)| {
+		let arg = arg.to_str()?;
+		if arg == CONFIG_FLAG {
+			args.get(i + 1).map(PathBuf::from)
+		} else if arg.starts_with(&prefix) {
+			Some(PathBuf::from(&arg[prefix.len()..]))
+		} else {
+			None
+		}
+	})
+}
+
+/// Returns whether the flag `--key`, or its `short` name, is given in `args`.
+fn is_given(args: &[OsString], key: &str, short: Option<&str>) -> bool {
+	let long = format!("--{}", key);
+	let long_with_value = format!("{}=", long);
+	args.iter().filter_map(|arg| arg.to_str()).any(|arg| {
+		arg == long || arg.starts_with(&long_with_value)
+			|| short.map_or(false, |short| arg.starts_with(short))
+	})
+}
+
+/// Returns the flags setting `key` to `value`.
+///
+/// `true` gives the bare flag and `false` no flag, an array gives the flag once per item, and
+/// every entry of an object is given as `NAME=JSON`, e.g. for `--chain-spec-extension`.
+fn value_args(key: &str, value: &Value) -> Result<Vec<String>, String> {
+	let flag = format!("--{}", key);
+	Ok(match value {
+		Value::Null | Value::Bool(false) => Vec::new(),
+		Value::Bool(true) => vec![flag],
+		Value::String(s) => vec![flag, s.clone()],
+		Value::Number(n) => vec![flag, n.to_string()],
+		Value::Array(items) => {
+			let mut args = Vec::new();
+			for item in items {
+				match item {
+					Value::Array(_) | Value::Object(_) => return Err(format!(
+						"Invalid value for '{}' in the config file: arrays can't be nested",
+						key,
+					)),
+					item => args.extend(value_args(key, item)?),
+				}
+			}
+			args
+		},
+		Value::Object(entries) => entries.iter()
+			.flat_map(|(name, value)| vec![flag.clone(), format!("{}={}", name, value)])
+			.collect(),
+	})
+}
+
+/// Read the entries of the config file at `path`, with their keys in kebab case.
+fn read_config_file(path: &Path) -> Result<Vec<(String, Value)>, String> {
+	let contents = fs::read_to_string(path)
+		.map_err(|e| format!("Error reading config file {}: {}", path.display(), e))?;
+	let value = if path.extension().map_or(false, |ext| ext == "toml") {
+		toml::from_str::<toml::Value>(&contents)
+			.map_err(|e| e.to_string())
+			.and_then(|v| serde_json::to_value(v).map_err(|e| e.to_string()))
+	} else {
+		serde_json::from_str::<Value>(&contents).map_err(|e| e.to_string())
+	}.map_err(|e| format!("Error parsing config file {}: {}", path.display(), e))?;
+
+	match value {
+		Value::Object(entries) => Ok(entries.into_iter()
+			.map(|(key, value)| (key.replace('_', "-"), value))
+			.collect()),
+		_ => Err(format!("Config file {} doesn't hold a table of flags", path.display())),
+	}
+}
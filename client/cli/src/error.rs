@@ -36,6 +36,22 @@ pub enum Error {
 	/// Invalid listen multiaddress
 	#[display(fmt="Invalid listen multiaddress")]
 	InvalidListenMultiaddress,
+	/// The chain spec file does not exist.
+	#[display(fmt="Chain spec file {} not found", _0)]
+	#[from(ignore)]
+	SpecNotFound(String),
+	/// The chain is neither built into the executable nor a path to an existing file.
+	#[display(fmt="Unknown chain '{}', expected a path to a chain spec file or one of: {}", _0, _1)]
+	#[from(ignore)]
+	UnknownChain(String, String),
+	/// The chain is neither known to the spec factory nor a path to a chain spec file.
+	#[display(fmt="Cannot load chain spec file {}: {}", _0, _1)]
+	#[from(ignore)]
+	InvalidSpecPath(String, String),
+	/// The chain spec file is not a valid JSON chain spec.
+	#[display(fmt="Invalid chain spec: {}", _0)]
+	#[from(ignore)]
+	InvalidSpecJson(String),
 	/// Other uncategorized error.
 	#[from(ignore)]
 	Other(String),
@@ -58,6 +74,10 @@ impl std::error::Error for Error {
 			Error::Client(ref err) => Some(err),
 			Error::Input(_) => None,
 			Error::InvalidListenMultiaddress => None,
+			Error::SpecNotFound(_) => None,
+			Error::UnknownChain(_, _) => None,
+			Error::InvalidSpecPath(_, _) => None,
+			Error::InvalidSpecJson(_) => None,
 			Error::Other(_) => None,
 		}
 	}
@@ -26,6 +26,7 @@ mod execution_strategy;
 pub mod error;
 mod runtime;
 mod node_key;
+mod config_file;
 
 use sc_client_api::execution_extensions::ExecutionStrategies;
 use sc_service::{
@@ -45,7 +46,7 @@ use sc_network::{
 
 use std::{
 	io::Write, iter, fmt::Debug, fs,
-	net::{Ipv4Addr, SocketAddr}, path::PathBuf,
+	net::{Ipv4Addr, SocketAddr}, path::{Path, PathBuf},
 };
 
 use regex::Regex;
@@ -56,8 +57,8 @@ use params::{
 };
 pub use params::{
 	SharedParams, ImportParams, ExecutionStrategy, Subcommand, RunCmd, BuildSpecCmd,
-	ExportBlocksCmd, ImportBlocksCmd, CheckBlockCmd, PurgeChainCmd, RevertCmd,
-	WasmExecutionMethod,
+	ValidateSpecCmd, ExportBlocksCmd, ImportBlocksCmd, CheckBlockCmd, PurgeChainCmd, RevertCmd,
+	BlocksPruningCheckCmd, WasmExecutionMethod, RpcMethods,
 };
 pub use traits::GetSharedParams;
 use app_dirs::{AppInfo, AppDataType};
@@ -77,9 +78,19 @@ const DEFAULT_DB_CONFIG_PATH : &'static str = "db";
 /// default sub directory for the key store
 const DEFAULT_KEYSTORE_CONFIG_PATH : &'static str = "keystore";
 
+/// The port the Prometheus endpoint listens on unless `--prometheus-port` is given.
+const DEFAULT_PROMETHEUS_PORT: u16 = 9615;
+
+/// The chain key reading the chain spec from standard input.
+const STDIN_CHAIN_KEY: &str = "-";
+
 /// The maximum number of characters for a node name.
 const NODE_NAME_MAX_LENGTH: usize = 32;
 
+/// The maximum number of bytes of a node name, as some telemetry backends silently drop
+/// nodes whose name is longer when encoded as UTF-8.
+const NODE_NAME_MAX_BYTES: usize = 64;
+
 fn get_chain_key(cli: &SharedParams) -> String {
 	match cli.chain {
 		Some(ref chain) => chain.clone(),
@@ -101,21 +112,48 @@ fn generate_node_name() -> String {
 }
 
 /// Load spec to `Configuration` from shared params and spec factory.
+///
+/// The chain `-` reads the spec from standard input.
 pub fn load_spec<'a, G, E, F>(
+	config: &'a mut Configuration<G, E>,
+	cli: &SharedParams,
+	factory: F,
+) -> error::Result<&'a ChainSpec<G, E>> where
+	G: RuntimeGenesis,
+	E: ChainSpecExtension,
+	F: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
+{
+	load_spec_with_stdin(config, cli, factory, std::io::stdin())
+}
+
+fn load_spec_with_stdin<'a, G, E, F, R>(
 	mut config: &'a mut Configuration<G, E>,
 	cli: &SharedParams,
 	factory: F,
+	stdin: R,
 ) -> error::Result<&'a ChainSpec<G, E>> where
 	G: RuntimeGenesis,
 	E: ChainSpecExtension,
 	F: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
+	R: std::io::Read,
 {
 	let chain_key = get_chain_key(cli);
-	let spec = match factory(&chain_key)? {
-		Some(spec) => spec,
-		None => ChainSpec::from_json_file(PathBuf::from(chain_key))?
+	let mut spec = if chain_key == STDIN_CHAIN_KEY {
+		info!("Loading chain spec from standard input");
+		read_spec(stdin)?
+	} else {
+		match factory(&chain_key)? {
+			Some(spec) => spec,
+			None => {
+				let path = resolve_spec_path(&chain_key)?;
+				info!("Loading chain spec from {}", path.display());
+				read_spec_file(&path)?
+			},
+		}
 	};
 
+	apply_extension_overrides(&mut spec, &cli.chain_spec_extensions)?;
+
 	config.network.boot_nodes = spec.boot_nodes().to_vec();
 	config.telemetry_endpoints = spec.telemetry_endpoints().clone();
 
@@ -124,17 +162,118 @@ pub fn load_spec<'a, G, E, F>(
 	Ok(config.chain_spec.as_ref().unwrap())
 }
 
-fn base_path(cli: &SharedParams, version: &VersionInfo) -> PathBuf {
-	cli.base_path.clone()
-		.unwrap_or_else(||
-			app_dirs::get_app_root(
-				AppDataType::UserData,
-				&AppInfo {
-					name: version.executable_name,
-					author: version.author
-				}
-			).expect("app directories exist on all supported platforms; qed")
-		)
+/// Resolve the path of a chain spec file against the current working directory.
+fn resolve_spec_path(chain_key: &str) -> error::Result<PathBuf> {
+	resolve_spec_path_in(chain_key, &std::env::current_dir()?)
+}
+
+/// Resolve the path of a chain spec file against `cwd`, returning the absolute
+/// path of the file. Absolute paths are kept as they are.
+fn resolve_spec_path_in(chain_key: &str, cwd: &Path) -> error::Result<PathBuf> {
+	let path = cwd.join(chain_key);
+	let path = path.canonicalize().map_err(|e| spec_io_error(&path, e))?;
+
+	if !path.is_file() {
+		return Err(error::Error::InvalidSpecPath(path.display().to_string(), "not a file".into()));
+	}
+
+	Ok(path)
+}
+
+/// Read the chain spec file at `path`, which may be gzip-compressed.
+fn read_spec_file<G, E>(path: &Path) -> error::Result<ChainSpec<G, E>> where
+	E: ChainSpecExtension,
+{
+	let bytes = fs::read(path).map_err(|e| spec_io_error(path, e))?;
+	let json = sc_service::decompress_spec(path, bytes).map_err(error::Error::InvalidSpecJson)?;
+	ChainSpec::from_json_bytes(json).map_err(error::Error::InvalidSpecJson)
+}
+
+/// Read a chain spec from `reader`, e.g. standard input.
+fn read_spec<G, E, R>(mut reader: R) -> error::Result<ChainSpec<G, E>> where
+	E: ChainSpecExtension,
+	R: std::io::Read,
+{
+	let mut json = Vec::new();
+	reader.read_to_end(&mut json)?;
+	if json.iter().all(u8::is_ascii_whitespace) {
+		return Err(error::Error::InvalidSpecJson("standard input is empty".into()));
+	}
+
+	ChainSpec::from_json_bytes(json).map_err(error::Error::InvalidSpecJson)
+}
+
+/// Returns the names of the chain specs built into the executable, one per line.
+pub fn chain_list(version: &VersionInfo) -> String {
+	version.chains.iter().map(|chain| format!("{}\n", chain)).collect()
+}
+
+fn spec_io_error(path: &Path, e: std::io::Error) -> error::Error {
+	match e.kind() {
+		std::io::ErrorKind::NotFound => error::Error::SpecNotFound(path.display().to_string()),
+		_ => error::Error::InvalidSpecPath(path.display().to_string(), e.to_string()),
+	}
+}
+
+/// Replace fields of the chain spec extensions with the given JSON values.
+///
+/// Every key must name an existing field and its value must deserialize to
+/// the type of that field.
+fn apply_extension_overrides<G, E>(
+	spec: &mut ChainSpec<G, E>,
+	overrides: &[(String, serde_json::Value)],
+) -> error::Result<()> where
+	E: ChainSpecExtension,
+{
+	if overrides.is_empty() {
+		return Ok(());
+	}
+
+	let mut extensions = serde_json::to_value(spec.extensions()).map_err(|e|
+		error::Error::Input(format!("Cannot serialize chain spec extensions: {}", e))
+	)?;
+
+	for (key, value) in overrides {
+		match extensions.as_object_mut().and_then(|fields| fields.get_mut(key)) {
+			Some(field) => *field = value.clone(),
+			None => return Err(error::Error::Input(
+				format!("Unknown chain spec extension '{}'", key)
+			)),
+		}
+
+		*spec.extensions_mut() = serde_json::from_value(extensions.clone()).map_err(|e|
+			error::Error::Input(format!("Invalid value for chain spec extension '{}': {}", key, e))
+		)?;
+	}
+
+	Ok(())
+}
+
+fn base_path(cli: &SharedParams, version: &VersionInfo) -> error::Result<PathBuf> {
+	base_path_with(cli, version, |app_info| app_dirs::get_app_root(AppDataType::UserData, app_info))
+}
+
+/// Returns the base path given on the command line or, if none was given, the
+/// one returned by `resolve` for the application.
+///
+/// The application is identified by `version.base_path_identity` if set, and by
+/// its executable name and author otherwise.
+fn base_path_with<R>(
+	cli: &SharedParams,
+	version: &VersionInfo,
+	resolve: R,
+) -> error::Result<PathBuf> where
+	R: FnOnce(&AppInfo) -> Result<PathBuf, app_dirs::AppDirsError>,
+{
+	let (name, author) = version.base_path_identity
+		.unwrap_or((version.executable_name, version.author));
+	match cli.base_path.clone() {
+		Some(path) => Ok(path),
+		None => resolve(&AppInfo { name, author }).map_err(|e| error::Error::Input(format!(
+			"Cannot determine the default base path: {}. Use `--base-path` to specify one.",
+			e,
+		))),
+	}
 }
 
 /// Helper function used to parse the command line arguments. This is the equivalent of
@@ -176,7 +315,10 @@ where
 		.about(version.description)
 		.version(full_version.as_str());
 
-	T::from_clap(&app.get_matches_from(iter))
+	let args = config_file::with_config_file(iter)
+		.unwrap_or_else(|e| clap::Error::with_description(&e, clap::ErrorKind::InvalidValue).exit());
+
+	T::from_clap(&app.get_matches_from(args))
 }
 
 /// Helper function used to parse the command line arguments. This is the equivalent of
@@ -210,12 +352,17 @@ where
 		.about(version.description)
 		.version(full_version.as_str());
 
-	let matches = app.get_matches_from_safe(iter)?;
+	let args = config_file::with_config_file(iter)
+		.map_err(|e| clap::Error::with_description(&e, clap::ErrorKind::InvalidValue))?;
+	let matches = app.get_matches_from_safe(args)?;
 
 	Ok(T::from_clap(&matches))
 }
 
 /// A helper function that initializes and runs the node
+///
+/// Returns [`Initialized::ListChains`] without running the node if asked to list the chains, for
+/// the caller to print them.
 pub fn run<F, G, E, FNL, FNF, SL, SF>(
 	mut config: Configuration<G, E>,
 	run_cmd: RunCmd,
@@ -223,7 +370,7 @@ pub fn run<F, G, E, FNL, FNF, SL, SF>(
 	new_full: FNF,
 	spec_factory: F,
 	version: &VersionInfo,
-) -> error::Result<()>
+) -> error::Result<Initialized>
 where
 	F: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
 	FNL: FnOnce(Configuration<G, E>) -> Result<SL, sc_service::error::Error>,
@@ -233,19 +380,26 @@ where
 	SL: AbstractService + Unpin,
 	SF: AbstractService + Unpin,
 {
-	init(&run_cmd.shared_params, version)?;
-	init_config(&mut config, &run_cmd.shared_params, version, spec_factory)?;
-	run_cmd.run(config, new_light, new_full, version)
+	let shared_params = &run_cmd.shared_params;
+	init(shared_params, version)?;
+	let initialized = init_config(&mut config, shared_params, version, spec_factory)?;
+	if initialized != Initialized::Run {
+		return Ok(initialized);
+	}
+	run_cmd.run(config, new_light, new_full, version).map(|_| Initialized::Run)
 }
 
 /// A helper function that initializes and runs any of the subcommand variants of `CoreParams`.
+///
+/// Returns [`Initialized::ListChains`] without running the subcommand if asked to list the
+/// chains, for the caller to print them.
 pub fn run_subcommand<F, G, E, B, BC, BB>(
 	mut config: Configuration<G, E>,
 	subcommand: Subcommand,
 	spec_factory: F,
 	builder: B,
 	version: &VersionInfo,
-) -> error::Result<()>
+) -> error::Result<Initialized>
 where
 	F: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
 	B: FnOnce(Configuration<G, E>) -> Result<BC, sc_service::error::Error>,
@@ -258,8 +412,11 @@ where
 {
 	let shared_params = subcommand.get_shared_params();
 	init(shared_params, version)?;
-	init_config(&mut config, shared_params, version, spec_factory)?;
-	subcommand.run(config, builder)
+	let initialized = init_config(&mut config, shared_params, version, spec_factory)?;
+	if initialized != Initialized::Run {
+		return Ok(initialized);
+	}
+	subcommand.run(config, builder).map(|_| Initialized::Run)
 }
 
 /// Initialize substrate. This must be done only once.
@@ -269,6 +426,7 @@ where
 /// 1. Set the panic handler
 /// 2. Raise the FD limit
 /// 3. Initialize the logger
+/// 4. Warn about the keys of the `--config` file which aren't flags
 pub fn init(shared_params: &SharedParams, version: &VersionInfo) -> error::Result<()> {
 	let full_version = sc_service::config::full_version_from_strs(
 		version.version,
@@ -277,40 +435,93 @@ pub fn init(shared_params: &SharedParams, version: &VersionInfo) -> error::Resul
 	sp_panic_handler::set(version.support_url, &full_version);
 
 	fdlimit::raise_fd_limit();
-	init_logger(shared_params.log.as_ref().map(|v| v.as_ref()).unwrap_or(""));
+	init_logger_with(&shared_params.logger_config());
+
+	if let Some(path) = &shared_params.config {
+		for key in config_file::unknown_keys(path)? {
+			log::warn!("Ignoring unknown key '{}' of config file {}", key, path.display());
+		}
+	}
 
 	Ok(())
 }
 
+/// What is left to do once [`init_config`] returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Initialized {
+	/// The configuration is ready and the command should run.
+	Run,
+	/// The chains built into the executable were asked for instead, see [`chain_list`]. The
+	/// caller should print them and exit successfully.
+	ListChains(String),
+}
+
 /// Initialize the given `config`.
 ///
-/// This will load the chain spec, set the `config_dir` and the `database_dir`.
+/// This will load the chain spec, set the `config_dir` and the `database_dir`. With
+/// `--list-chains` it returns the chains built into the executable instead, as
+/// [`Initialized::ListChains`], leaving `config` untouched.
 pub fn init_config<G, E, F>(
 	config: &mut Configuration<G, E>,
 	shared_params: &SharedParams,
 	version: &VersionInfo,
 	spec_factory: F,
-) -> error::Result<()> where
+) -> error::Result<Initialized> where
 	F: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
 	G: RuntimeGenesis,
 	E: ChainSpecExtension,
 {
-	load_spec(config, shared_params, spec_factory)?;
+	if shared_params.list_chains {
+		return Ok(Initialized::ListChains(chain_list(version)));
+	}
+
+	load_spec(config, shared_params, spec_factory).map_err(|e| match e {
+		error::Error::SpecNotFound(_) if !version.chains.is_empty() => error::Error::UnknownChain(
+			get_chain_key(shared_params),
+			version.chains.join(", "),
+		),
+		e => e,
+	})?;
 
 	if config.config_dir.is_none() {
-		config.config_dir = Some(base_path(&shared_params, version));
+		config.config_dir = Some(base_path(&shared_params, version)?);
 	}
 
 	if config.database.is_none() {
-		config.database = Some(DatabaseConfig::Path {
-			path: config
+		let path = match shared_params.database_path.clone() {
+			Some(path) => {
+				ensure_writable_dir(&path)?;
+				path
+			},
+			None => config
 				.in_chain_config_dir(DEFAULT_DB_CONFIG_PATH)
 				.expect("We provided a base_path/config_dir."),
+		};
+
+		config.database = Some(DatabaseConfig::Path {
+			path,
 			cache_size: None,
 		});
 	}
 
-	Ok(())
+	shared_params.validate()?;
+
+	Ok(Initialized::Run)
+}
+
+/// Create the directory at `path` if needed and make sure it is writable.
+fn ensure_writable_dir(path: &Path) -> error::Result<()> {
+	fs::create_dir_all(path).map_err(|e| error::Error::Input(
+		format!("Cannot create directory {}: {}", path.display(), e)
+	))?;
+
+	// The permission bits don't tell about ACLs, read-only mounts and the like, so try it out.
+	let probe = path.join(format!(".write-probe-{}", std::process::id()));
+	fs::File::create(&probe)
+		.and_then(|_| fs::remove_file(&probe))
+		.map_err(|e| error::Error::Input(
+			format!("Directory {} is not writable: {}", path.display(), e)
+		))
 }
 
 /// Run the node
@@ -360,6 +571,79 @@ pub fn display_role<G, E>(config: &Configuration<G, E>) -> String {
 	}
 }
 
+/// Returns a human-readable description of the resolved parts of `config`.
+///
+/// This is what `--print-config` outputs. Secrets, such as the keystore
+/// password, are redacted.
+pub fn describe_config<G, E>(config: &Configuration<G, E>) -> String {
+	let mut lines = Vec::new();
+
+	if let Some(spec) = config.chain_spec.as_ref() {
+		lines.push(format!("Chain specification: {} ({})", spec.name(), spec.id()));
+	}
+	lines.push(format!(
+		"Base path: {}",
+		config.config_dir.as_ref().map_or("none".into(), |p| p.display().to_string()),
+	));
+	lines.push(match &config.database {
+		Some(DatabaseConfig::Path { path, cache_size }) => format!(
+			"Database: {} (cache size: {})",
+			path.display(),
+			cache_size.map_or("default".into(), |s| format!("{} MiB", s)),
+		),
+		Some(DatabaseConfig::Custom(_)) => "Database: custom".into(),
+		None => "Database: none".into(),
+	});
+	lines.push(match &config.keystore {
+		KeystoreConfig::Path { path, password } => format!(
+			"Keystore: {} (password: {})",
+			path.display(),
+			if password.is_some() { "<redacted>" } else { "none" },
+		),
+		KeystoreConfig::InMemory => "Keystore: in memory".into(),
+		KeystoreConfig::None => "Keystore: none".into(),
+	});
+	lines.push(format!("Pruning: {:?}", config.pruning));
+	lines.push(format!("Block bodies: {:?}", config.keep_blocks));
+
+	let exec = &config.execution_strategies;
+	lines.push("Execution strategies:".into());
+	lines.push(format!("  syncing: {:?}", exec.syncing));
+	lines.push(format!("  importing: {:?}", exec.importing));
+	lines.push(format!("  block construction: {:?}", exec.block_construction));
+	lines.push(format!("  offchain worker: {:?}", exec.offchain_worker));
+	lines.push(format!("  other: {:?}", exec.other));
+
+	match &config.telemetry_endpoints {
+		Some(endpoints) => {
+			lines.push("Telemetry endpoints:".into());
+			for (url, verbosity) in endpoints.endpoints() {
+				lines.push(format!("  {} (verbosity: {})", url, verbosity));
+			}
+		},
+		None => lines.push("Telemetry endpoints: none".into()),
+	}
+
+	lines.push(String::new());
+	lines.join("\n")
+}
+
+/// Returns the endpoints of the chain spec `spec` followed by the `added` ones, those added
+/// with the URL of an endpoint of the spec replacing its verbosity.
+fn merge_telemetry_endpoints(
+	spec: Vec<(String, u8)>,
+	added: Vec<(String, u8)>,
+) -> Vec<(String, u8)> {
+	let mut endpoints = spec;
+	for (url, verbosity) in added {
+		match endpoints.iter_mut().find(|(u, _)| *u == url) {
+			Some(endpoint) => endpoint.1 = verbosity,
+			None => endpoints.push((url, verbosity)),
+		}
+	}
+	endpoints
+}
+
 /// Fill the given `PoolConfiguration` by looking at the cli parameters.
 fn fill_transaction_pool_configuration<G, E>(
 	options: &mut Configuration<G, E>,
@@ -377,6 +661,46 @@ fn fill_transaction_pool_configuration<G, E>(
 	Ok(())
 }
 
+/// Parse the multiaddresses given by `--listen-addr`, naming the index of the first malformed
+/// one.
+fn parse_listen_addresses(addresses: &[String]) -> error::Result<Vec<sc_network::Multiaddr>> {
+	addresses.iter().enumerate().map(|(index, addr)| {
+		addr.parse().map_err(|e| error::Error::Input(format!(
+			"Invalid `--listen-addr` at index {} ('{}'): {}",
+			index,
+			addr,
+			e,
+		)))
+	}).collect()
+}
+
+/// Check the addresses given by `--reserved-nodes`, naming the index of the first malformed one.
+///
+/// Every address must end with the peer id of the node, e.g. `/ip4/127.0.0.1/tcp/30333/p2p/Qm..`.
+fn check_reserved_nodes(nodes: &[String]) -> error::Result<()> {
+	for (index, node) in nodes.iter().enumerate() {
+		sc_network::config::parse_str_addr(node).map_err(|e| error::Error::Input(format!(
+			"Invalid `--reserved-nodes` at index {} ('{}'): {}",
+			index,
+			node,
+			e,
+		)))?;
+	}
+	Ok(())
+}
+
+/// Returns a warning if `--reserved-only` is set while there are no reserved `nodes`, as the
+/// node then never connects to any peer.
+fn reserved_only_warning(reserved_only: bool, nodes: &[String]) -> Option<String> {
+	if !reserved_only || !nodes.is_empty() {
+		return None;
+	}
+	Some(
+		"`--reserved-only` is set without any `--reserved-nodes`, so the node won't connect to \
+		any peer.".to_string()
+	)
+}
+
 /// Fill the given `NetworkConfiguration` by looking at the cli parameters.
 fn fill_network_configuration(
 	cli: NetworkConfigurationParams,
@@ -385,20 +709,26 @@ fn fill_network_configuration(
 	client_id: String,
 	is_dev: bool,
 ) -> error::Result<()> {
+	if cli.no_default_bootnodes {
+		config.boot_nodes.clear();
+	}
 	config.boot_nodes.extend(cli.bootnodes.into_iter());
 	config.config_path = Some(config_path.to_string_lossy().into());
 	config.net_config_path = config.config_path.clone();
 
+	check_reserved_nodes(&cli.reserved_nodes)?;
 	config.reserved_nodes.extend(cli.reserved_nodes.into_iter());
 	if cli.reserved_only {
 		config.non_reserved_mode = NonReservedPeerMode::Deny;
 	}
+	if let Some(warning) = reserved_only_warning(cli.reserved_only, &config.reserved_nodes) {
+		log::warn!("{}", warning);
+	}
 
 	config.sentry_nodes.extend(cli.sentry_nodes.into_iter());
 
-	for addr in cli.listen_addr.iter() {
-		let addr = addr.parse().ok().ok_or(error::Error::InvalidListenMultiaddress)?;
-		config.listen_addresses.push(addr);
+	if !cli.listen_addr.is_empty() {
+		config.listen_addresses = parse_listen_addresses(&cli.listen_addr)?;
 	}
 
 	if config.listen_addresses.is_empty() {
@@ -417,11 +747,17 @@ fn fill_network_configuration(
 	config.client_version = client_id;
 	config.node_key = node_key::node_key_config(cli.node_key_params, &config.net_config_path)?;
 
+	if cli.out_peers == 0 {
+		return Err(error::Error::Input(
+			"`--out-peers 0` never connects to any peer, so the node could not sync. \
+			Use `--reserved-only` to restrict the peers instead.".to_string()
+		));
+	}
 	config.in_peers = cli.in_peers;
 	config.out_peers = cli.out_peers;
 
 	config.transport = TransportConfig::Normal {
-		enable_mdns: !is_dev && !cli.no_mdns,
+		enable_mdns: cli.discover_local || (!is_dev && !cli.no_mdns),
 		allow_private_ipv4: !cli.no_private_ipv4,
 		wasm_external_transport: None,
 		use_yamux_flow_control: cli.use_yamux_flow_control
@@ -480,6 +816,72 @@ fn fill_config_keystore_password_and_path<G, E>(
 	Ok(())
 }
 
+/// Check that `pruning` doesn't keep the state of blocks whose bodies `keep_blocks` prunes.
+fn check_keep_blocks(
+	keep_blocks: sc_service::config::KeepBlocks,
+	pruning: &PruningMode,
+) -> error::Result<()> {
+	let count = match keep_blocks {
+		sc_service::config::KeepBlocks::All => return Ok(()),
+		sc_service::config::KeepBlocks::Some(count) => count,
+	};
+	let state = match pruning {
+		PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => "all blocks".to_string(),
+		PruningMode::Constrained(constraints) => match constraints.max_blocks {
+			Some(max_blocks) if max_blocks > count => format!("{} blocks", max_blocks),
+			_ => return Ok(()),
+		},
+	};
+	Err(error::Error::Input(format!(
+		"`--keep-blocks {}` prunes the bodies of blocks whose state is kept, as the state of {} \
+		is kept by `--pruning`. Keep at least as many blocks as states.",
+		count,
+		state,
+	)))
+}
+
+/// How much of a chain its pruning settings keep, see [`pruning_report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruningReport {
+	/// The number of block bodies kept.
+	pub blocks_retained: u64,
+	/// The number of block bodies pruned.
+	pub blocks_pruned: u64,
+	/// The number of block states kept.
+	pub states_retained: u64,
+	/// The number of block states pruned.
+	pub states_pruned: u64,
+}
+
+/// Returns how much of a chain with the blocks `0..=best`, of which those up to `finalized` are
+/// finalized, the state pruning mode `pruning` and the block pruning `keep_blocks` keep.
+///
+/// Only finalized blocks are pruned, a limit of `n` blocks keeping the last `n` of them. The
+/// database prunes the bodies on finalization, including those kept before the limit was set.
+pub fn pruning_report(
+	best: u64,
+	finalized: u64,
+	pruning: &PruningMode,
+	keep_blocks: sc_service::config::KeepBlocks,
+) -> PruningReport {
+	let finalized = finalized.min(best);
+	// Returns how many blocks are retained and pruned if the last `limit` finalized are kept.
+	let split = |limit: Option<u32>| {
+		let pruned = limit.map_or(0, |limit| (finalized + 1).saturating_sub(limit as u64));
+		(best + 1 - pruned, pruned)
+	};
+
+	let (blocks_retained, blocks_pruned) = split(match keep_blocks {
+		sc_service::config::KeepBlocks::All => None,
+		sc_service::config::KeepBlocks::Some(count) => Some(count),
+	});
+	let (states_retained, states_pruned) = split(match pruning {
+		PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
+		PruningMode::Constrained(constraints) => Some(constraints.max_blocks.unwrap_or(0)),
+	});
+	PruningReport { blocks_retained, blocks_pruned, states_retained, states_pruned }
+}
+
 /// Put block import CLI params into `config` object.
 pub fn fill_import_params<G, E>(
 	config: &mut Configuration<G, E>,
@@ -490,6 +892,8 @@ pub fn fill_import_params<G, E>(
 where
 	G: RuntimeGenesis,
 {
+	cli.validate()?;
+
 	if let Some(DatabaseConfig::Path { ref mut cache_size, .. }) = config.database {
 		*cache_size = Some(cli.database_cache_size);
 	}
@@ -518,11 +922,21 @@ where
 		},
 	};
 
+	// the state an archive database kept around until now would be pruned for good, so the
+	// database refuses to open without the same acknowledgement.
+	config.unsafe_pruning = cli.unsafe_pruning;
+
+	config.keep_blocks = cli.keep_blocks.unwrap_or(sc_service::config::KeepBlocks::All);
+	check_keep_blocks(config.keep_blocks, &config.pruning)?;
+
 	config.wasm_method = cli.wasm_method.into();
 
 	let exec = &cli.execution_strategies;
+	let native_runtime = config.native_runtime;
 	let exec_all_or = |strat: ExecutionStrategy, default: ExecutionStrategy| {
-		exec.execution.unwrap_or(if strat == default && is_dev {
+		exec.execution.unwrap_or(if strat == default && !native_runtime {
+			ExecutionStrategy::Wasm
+		} else if strat == default && is_dev {
 			ExecutionStrategy::Native
 		} else {
 			strat
@@ -538,6 +952,44 @@ where
 			exec_all_or(exec.execution_offchain_worker, DEFAULT_EXECUTION_OFFCHAIN_WORKER),
 		other: exec_all_or(exec.execution_other, DEFAULT_EXECUTION_OTHER),
 	};
+	check_native_runtime(&config.execution_strategies, config.native_runtime)
+}
+
+/// Returns an error if one of the execution `strategies` can't run without the native runtime,
+/// while the executable doesn't include one.
+fn check_native_runtime(
+	strategies: &ExecutionStrategies,
+	native_runtime: bool,
+) -> error::Result<()> {
+	if native_runtime {
+		return Ok(());
+	}
+
+	let contexts = [
+		("syncing", strategies.syncing, "--execution-syncing"),
+		("importing blocks", strategies.importing, "--execution-import-block"),
+		("constructing blocks", strategies.block_construction, "--execution-block-construction"),
+		("off-chain workers", strategies.offchain_worker, "--execution-offchain-worker"),
+		("other calls", strategies.other, "--execution-other"),
+	];
+	for (context, strategy, flag) in contexts.iter() {
+		let needs_native = match strategy {
+			sc_client_api::ExecutionStrategy::NativeWhenPossible |
+			sc_client_api::ExecutionStrategy::Both => true,
+			sc_client_api::ExecutionStrategy::AlwaysWasm |
+			sc_client_api::ExecutionStrategy::NativeElseWasm => false,
+		};
+		if needs_native {
+			return Err(error::Error::Input(format!(
+				"The execution strategy {:?} for {} needs the native runtime, but this executable \
+				only includes the Wasm runtime. Use `{} wasm` or `--execution wasm`.",
+				strategy,
+				context,
+				flag,
+			)));
+		}
+	}
+
 	Ok(())
 }
 
@@ -623,6 +1075,7 @@ where
 		config.rpc_ws = Some(parse_address(&format!("{}:{}", ws_interface, 9944), cli.ws_port)?);
 	}
 
+	config.rpc_methods = rpc_methods(cli.rpc_methods, &[config.rpc_http, config.rpc_ws]);
 	config.rpc_ws_max_connections = cli.ws_max_connections;
 	config.rpc_cors = cli.rpc_cors.unwrap_or_else(|| if is_dev {
 		log::warn!("Running in --dev mode, RPC CORS has been disabled.");
@@ -637,21 +1090,28 @@ where
 			"https://substrate-ui.parity.io".into(),
 		])
 	}).into();
+	if let Some(warning) = cors_warning(&config.rpc_cors, &[config.rpc_http, config.rpc_ws]) {
+		log::warn!("{}", warning);
+	}
 
 	// Override telemetry
 	if cli.no_telemetry {
 		config.telemetry_endpoints = None;
 	} else if !cli.telemetry_endpoints.is_empty() {
-		config.telemetry_endpoints = Some(TelemetryEndpoints::new(cli.telemetry_endpoints));
+		let spec_endpoints = match (&config.telemetry_endpoints, cli.telemetry_url_replace) {
+			(Some(endpoints), false) => endpoints.endpoints().to_vec(),
+			_ => Vec::new(),
+		};
+		config.telemetry_endpoints = Some(TelemetryEndpoints::new(
+			merge_telemetry_endpoints(spec_endpoints, cli.telemetry_endpoints)
+		));
 	}
 	// Override prometheus
-	if cli.no_prometheus {
-		config.prometheus_port = None;
-	} else {
-		let prometheus_interface: &str = if cli.prometheus_external { "0.0.0.0" } else { "127.0.0.1" };
-		config.prometheus_port = Some(
-		parse_address(&format!("{}:{}", prometheus_interface, 9615), cli.prometheus_port)?);
-	}
+	config.prometheus_port = prometheus_address(
+		cli.no_prometheus,
+		cli.prometheus_external,
+		cli.prometheus_port,
+	)?;
 
 	config.tracing_targets = cli.import_params.tracing_targets.into();
 	config.tracing_receiver = cli.import_params.tracing_receiver.into();
@@ -662,6 +1122,64 @@ where
 	Ok(())
 }
 
+/// Returns the address the Prometheus endpoint listens on, `None` if it is disabled.
+///
+/// The endpoint listens on the local interface and port 9615 unless `external` or `port` say
+/// otherwise. Listening on all interfaces is logged as a warning, as anyone reaching the node
+/// can read the metrics.
+fn prometheus_address(
+	disabled: bool,
+	external: bool,
+	port: Option<u16>,
+) -> Result<Option<SocketAddr>, String> {
+	if disabled {
+		return Ok(None);
+	}
+
+	let interface = if external { "0.0.0.0" } else { "127.0.0.1" };
+	let address = parse_address(&format!("{}:{}", interface, DEFAULT_PROMETHEUS_PORT), port)?;
+	if external {
+		log::warn!(
+			"The Prometheus endpoint listens on all interfaces, its metrics can be read by anyone \
+			reaching port {}. Use a firewall or drop `--prometheus-external` to restrict access.",
+			address.port(),
+		);
+	}
+	Ok(Some(address))
+}
+
+/// Returns a warning if RPC servers listening on `addresses` accept requests from any origin,
+/// i.e. `cors` is `None`, while listening on a public interface.
+///
+/// Any website opened by anyone reaching the node can then call its RPC methods.
+fn cors_warning(cors: &Option<Vec<String>>, addresses: &[Option<SocketAddr>]) -> Option<String> {
+	if cors.is_some() {
+		return None;
+	}
+	addresses.iter().flatten().find(|a| !a.ip().is_loopback()).map(|address| format!(
+		"The RPC server listening on {} accepts requests from any origin (`--rpc-cors all`), \
+		so any website can call it from the browser of someone reaching the node. \
+		Restrict `--rpc-cors` to the origins of your dapps.",
+		address,
+	))
+}
+
+/// Resolve the RPC methods to expose by servers listening on `addresses`.
+fn rpc_methods(
+	methods: RpcMethods,
+	addresses: &[Option<SocketAddr>],
+) -> sc_service::config::RpcMethods {
+	match methods {
+		RpcMethods::Safe => sc_service::config::RpcMethods::Safe,
+		RpcMethods::Unsafe => sc_service::config::RpcMethods::Unsafe,
+		RpcMethods::Auto => if addresses.iter().flatten().any(|a| !a.ip().is_loopback()) {
+			sc_service::config::RpcMethods::Safe
+		} else {
+			sc_service::config::RpcMethods::Unsafe
+		},
+	}
+}
+
 fn interface_str(
 	is_external: bool,
 	is_unsafe_external: bool,
@@ -697,8 +1215,22 @@ fn parse_address(
 	Ok(address)
 }
 
+/// Configuration of the logger initialized by [`init_logger_with`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoggerConfig {
+	/// The logging filter, e.g. `info,sync=debug`.
+	pub pattern: String,
+	/// Include the module path and source location of every record.
+	pub detailed_output: bool,
+}
+
 /// Initialize the logger
 pub fn init_logger(pattern: &str) {
+	init_logger_with(&LoggerConfig { pattern: pattern.into(), ..Default::default() })
+}
+
+/// Initialize the logger with the given `config`.
+pub fn init_logger_with(config: &LoggerConfig) {
 	use ansi_term::Colour;
 
 	let mut builder = env_logger::Builder::new();
@@ -715,9 +1247,10 @@ pub fn init_logger(pattern: &str) {
 		builder.parse_filters(&lvl);
 	}
 
-	builder.parse_filters(pattern);
+	builder.parse_filters(&config.pattern);
 	let isatty = atty::is(atty::Stream::Stderr);
 	let enable_color = isatty;
+	let detailed_output = config.detailed_output;
 
 	builder.format(move |buf, record| {
 		let now = time::now();
@@ -725,7 +1258,7 @@ pub fn init_logger(pattern: &str) {
 			time::strftime("%Y-%m-%d %H:%M:%S", &now)
 				.expect("Error formatting log timestamp");
 
-		let mut output = if log::max_level() <= log::LevelFilter::Info {
+		let mut output = if log::max_level() <= log::LevelFilter::Info && !detailed_output {
 			format!("{} {}", Colour::Black.bold().paint(timestamp), record.args())
 		} else {
 			let name = ::std::thread::current()
@@ -733,12 +1266,23 @@ pub fn init_logger(pattern: &str) {
 				.map_or_else(Default::default, |x| format!("{}", Colour::Blue.bold().paint(x)));
 			let millis = (now.tm_nsec as f32 / 1000000.0).round() as usize;
 			let timestamp = format!("{}.{:03}", timestamp, millis);
+			let location = if detailed_output {
+				format!(
+					" {} {}:{}",
+					record.module_path().unwrap_or("<unknown>"),
+					record.file().unwrap_or("<unknown>"),
+					record.line().unwrap_or(0),
+				)
+			} else {
+				String::new()
+			};
 			format!(
-				"{} {} {} {}  {}",
+				"{} {} {} {}{}  {}",
 				Colour::Black.bold().paint(timestamp),
 				name,
 				record.level(),
 				record.target(),
+				location,
 				record.args()
 			)
 		};
@@ -780,8 +1324,48 @@ mod tests {
 		author: "author",
 		support_url: "http://example.org",
 		copyright_start_year: 2020,
+		base_path_identity: None,
+		chains: &["dev", "local"],
+		native_runtime: true,
 	};
 
+	/// The chain spec `test-id`, without any genesis, booting from `boot_nodes`.
+	fn test_chain_spec(
+		boot_nodes: Vec<String>,
+		telemetry_endpoints: Option<TelemetryEndpoints>,
+	) -> ChainSpec<(), Option<()>> {
+		ChainSpec::from_genesis(
+			"test",
+			"test-id",
+			|| (),
+			boot_nodes,
+			telemetry_endpoints,
+			None,
+			None,
+			None::<()>,
+		)
+	}
+
+	/// Configures a node of `version` for `chain_spec` with the flags `args` the way the binary
+	/// does, in `/test/path`.
+	fn configure_node(
+		version: &VersionInfo,
+		chain_spec: ChainSpec<(), Option<()>>,
+		args: &[&str],
+	) -> error::Result<Configuration<(), Option<()>>> {
+		let cli = RunCmd::from_iter(iter::once("node-test").chain(args.iter().cloned()));
+		let mut config = Configuration::new(version);
+		config.config_dir = Some(PathBuf::from("/test/path"));
+		init_config(&mut config, &cli.shared_params, version, |_| Ok(Some(chain_spec)))?;
+		update_config_for_running_node(&mut config, cli)?;
+		Ok(config)
+	}
+
+	/// Configures a node for the test chain spec with the flags `args`.
+	fn configure(args: &[&str]) -> error::Result<Configuration<(), Option<()>>> {
+		configure_node(TEST_VERSION_INFO, test_chain_spec(vec![], None), args)
+	}
+
 	#[test]
 	fn keystore_path_is_generated_correctly() {
 		let chain_spec = ChainSpec::from_genesis(
@@ -878,4 +1462,756 @@ mod tests {
 		assert!(config.network.config_path.is_some());
 		assert!(!config.network.listen_addresses.is_empty());
 	}
+
+	#[test]
+	fn describe_config_reflects_flags() {
+		let config = configure(&[
+			"--pruning", "archive",
+			"--execution", "wasm",
+			"--password", "secret-password",
+			"--telemetry-url", "wss://telemetry.example.org/submit/ 5",
+		]).unwrap();
+
+		let dump = describe_config(&config);
+
+		assert!(dump.contains("Base path: /test/path"));
+		assert!(dump.contains("Database: /test/path/chains/test-id/db"));
+		assert!(dump.contains("Keystore: /test/path/chains/test-id/keystore (password: <redacted>)"));
+		assert!(dump.contains("Pruning: ArchiveAll"));
+		assert!(dump.contains("syncing: AlwaysWasm"));
+		assert!(dump.contains("other: AlwaysWasm"));
+		assert!(dump.contains("wss://telemetry.example.org/submit/ (verbosity: 5)"));
+		assert!(!dump.contains("secret-password"));
+	}
+
+	#[test]
+	fn database_path_overrides_default_derivation() {
+		let db_dir = tempfile::tempdir().unwrap();
+		let db_path = db_dir.path().join("fast-disk").join("db");
+
+		for database_path in vec![None, Some(db_path.clone())] {
+			let args = match &database_path {
+				Some(path) => vec!["--database-path", path.to_str().unwrap()],
+				None => vec![],
+			};
+			let config = configure(&args).unwrap();
+
+			let expected_path = match database_path {
+				Some(path) => {
+					assert!(path.is_dir());
+					assert_eq!(fs::read_dir(&path).unwrap().count(), 0, "the probe is left behind");
+					path
+				},
+				None => PathBuf::from("/test/path/chains/test-id/db"),
+			};
+
+			match config.database {
+				Some(DatabaseConfig::Path { path, .. }) => assert_eq!(path, expected_path),
+				_ => panic!("invalid config.database variant"),
+			}
+		}
+	}
+
+	#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+	#[serde(rename_all = "camelCase")]
+	struct TestExtension {
+		fork_block: Option<u64>,
+		bad_blocks: Vec<String>,
+	}
+
+	impl ChainSpecExtension for TestExtension {
+		type Forks = Option<()>;
+
+		fn get<T: 'static>(&self) -> Option<&T> { None }
+	}
+
+	#[test]
+	fn load_spec_applies_extension_overrides() {
+		let chain_spec = ChainSpec::from_genesis(
+			"test",
+			"test-id",
+			|| (),
+			vec![],
+			None,
+			None,
+			None,
+			TestExtension { fork_block: None, bad_blocks: vec!["0x01".into()] },
+		);
+		let load = |args: Vec<&str>| -> error::Result<TestExtension> {
+			let cli = SharedParams::from_iter(args);
+			let mut config = Configuration::new(TEST_VERSION_INFO);
+			load_spec(&mut config, &cli, |_| Ok(Some(chain_spec.clone())))?;
+			Ok(config.expect_chain_spec().extensions().clone())
+		};
+
+		let extensions = load(vec!["node-test", "--chain-spec-extension", "forkBlock=42"]).unwrap();
+		assert_eq!(extensions.fork_block, Some(42));
+		assert_eq!(extensions.bad_blocks, vec!["0x01".to_string()]);
+
+		let err = load(vec!["node-test", "--chain-spec-extension", "unknown=1"]).unwrap_err();
+		assert!(err.to_string().contains("'unknown'"));
+
+		let err = load(vec!["node-test", "--chain-spec-extension", "forkBlock=\"a\""]).unwrap_err();
+		assert!(err.to_string().contains("'forkBlock'"));
+
+		let err = SharedParams::from_iter_safe(vec![
+			"node-test", "--chain-spec-extension", "badBlocks=[0x",
+		]).unwrap_err();
+		assert!(err.to_string().contains("'badBlocks'"));
+	}
+
+	#[test]
+	fn base_path_resolution_failure_is_an_error() {
+		let not_supported = |_: &AppInfo| Err(app_dirs::AppDirsError::NotSupported);
+
+		let args: Vec<&str> = vec![];
+		let cli = SharedParams::from_iter(args);
+		let err = base_path_with(&cli, TEST_VERSION_INFO, not_supported).unwrap_err();
+		assert!(err.to_string().contains("Cannot determine the default base path"));
+
+		let cli = SharedParams::from_iter(vec!["node-test", "--base-path", "/test/path"]);
+		assert_eq!(
+			base_path_with(&cli, TEST_VERSION_INFO, not_supported).unwrap(),
+			PathBuf::from("/test/path"),
+		);
+	}
+
+	#[test]
+	fn base_path_identity_can_be_overridden() {
+		let resolve = |info: &AppInfo| -> Result<PathBuf, app_dirs::AppDirsError> {
+			Ok(PathBuf::from(format!("/data/{}/{}", info.author, info.name)))
+		};
+		let fork = VersionInfo {
+			base_path_identity: Some(("fork-node", "fork-author")),
+			..TEST_VERSION_INFO.clone()
+		};
+
+		let args: Vec<&str> = vec![];
+		let cli = SharedParams::from_iter(args);
+		let upstream = base_path_with(&cli, TEST_VERSION_INFO, resolve).unwrap();
+		let forked = base_path_with(&cli, &fork, resolve).unwrap();
+		assert_eq!(upstream, PathBuf::from("/data/author/node-test"));
+		assert_eq!(forked, PathBuf::from("/data/fork-author/fork-node"));
+		assert_eq!(fork.executable_name, TEST_VERSION_INFO.executable_name);
+	}
+
+	#[test]
+	fn offchain_worker_flag_sets_configuration() {
+		for (value, validator, expected) in vec![
+			("always", false, true),
+			("Always", true, true),
+			("never", false, false),
+			("never", true, false),
+			("whenvalidating", false, false),
+			("WhenValidating", true, true),
+		] {
+			let mut args = vec!["--offchain-worker", value];
+			if validator {
+				args.push("--validator");
+			}
+			let config = configure(&args).unwrap();
+
+			assert_eq!(config.offchain_worker, expected, "--offchain-worker {}", value);
+		}
+
+		let cli = RunCmd::from_iter(vec!["node-test"]);
+		match cli.offchain_worker {
+			params::OffchainWorkerEnabled::WhenValidating => {},
+			other => panic!("unexpected default --offchain-worker value: {:?}", other),
+		}
+
+		assert!(RunCmd::from_iter_safe(vec!["node-test", "--offchain-worker", "sometimes"]).is_err());
+	}
+
+	#[test]
+	fn build_configuration_matches_init_config() {
+		let telemetry = TelemetryEndpoints::new(vec![("foo".to_string(), 42)]);
+		let chain_spec = test_chain_spec(vec!["boo".to_string()], Some(telemetry));
+		let cli = SharedParams::from_iter(vec!["node-test", "--base-path", "/test/path"]);
+
+		let mut expected = Configuration::new(TEST_VERSION_INFO);
+		init_config(&mut expected, &cli, TEST_VERSION_INFO, |_| Ok(Some(chain_spec.clone()))).unwrap();
+
+		let config = cli.build_configuration(|_| Ok(Some(chain_spec.clone())), TEST_VERSION_INFO)
+			.unwrap()
+			.unwrap();
+
+		assert_eq!(config.impl_name, expected.impl_name);
+		assert_eq!(config.config_dir, expected.config_dir);
+		assert_eq!(config.expect_chain_spec().id(), expected.expect_chain_spec().id());
+		assert_eq!(config.network.boot_nodes, expected.network.boot_nodes);
+		assert_eq!(config.telemetry_endpoints, expected.telemetry_endpoints);
+		match (config.database, expected.database) {
+			(
+				Some(DatabaseConfig::Path { path, cache_size }),
+				Some(DatabaseConfig::Path { path: expected_path, cache_size: expected_cache_size }),
+			) => {
+				assert_eq!(path, expected_path);
+				assert_eq!(cache_size, expected_cache_size);
+			},
+			_ => panic!("invalid config.database variant"),
+		}
+
+		assert!(
+			cli.build_configuration::<(), Option<()>, _>(|_| Err("no spec".into()), TEST_VERSION_INFO)
+				.is_err()
+		);
+	}
+
+	#[test]
+	fn no_default_bootnodes_clears_spec_bootnodes() {
+		let spec_bootnode = "/ip4/127.0.0.1/tcp/30333/p2p/QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV";
+		let cli_bootnode = "/ip4/127.0.0.1/tcp/30334/p2p/QmWv9Ww7znzgLFyCzf21SR6tUKXrmHCZH9KhebeH4gyE9f";
+		let chain_spec = test_chain_spec(vec![spec_bootnode.to_string()], None);
+
+		for (args, expected) in vec![
+			(vec![], vec![spec_bootnode]),
+			(vec!["--bootnodes", cli_bootnode], vec![spec_bootnode, cli_bootnode]),
+			(vec!["--no-default-bootnodes"], vec![]),
+			(vec!["--no-default-bootnodes", "--bootnodes", cli_bootnode], vec![cli_bootnode]),
+		] {
+			let config = configure_node(TEST_VERSION_INFO, chain_spec.clone(), &args).unwrap();
+			assert_eq!(config.network.boot_nodes, expected);
+		}
+	}
+
+	#[test]
+	fn relative_spec_paths_are_resolved_against_cwd() {
+		let cwd = tempfile::tempdir().unwrap();
+		fs::create_dir(cwd.path().join("specs")).unwrap();
+		fs::write(cwd.path().join("specs").join("spec.json"), "{}").unwrap();
+
+		let expected = cwd.path().canonicalize().unwrap().join("specs").join("spec.json");
+		assert_eq!(resolve_spec_path_in("specs/spec.json", cwd.path()).unwrap(), expected);
+		assert_eq!(resolve_spec_path_in("./specs/../specs/spec.json", cwd.path()).unwrap(), expected);
+		assert_eq!(
+			resolve_spec_path_in(expected.to_str().unwrap(), Path::new("/elsewhere")).unwrap(),
+			expected,
+		);
+
+		let err = resolve_spec_path_in("specs/missing.json", cwd.path()).unwrap_err();
+		assert!(err.to_string().contains("missing.json"));
+
+		let err = resolve_spec_path_in("specs", cwd.path()).unwrap_err();
+		assert!(err.to_string().contains("not a file"));
+	}
+
+	#[test]
+	fn spec_resolution_failures_map_to_variants() {
+		let dir = tempfile::tempdir().unwrap();
+		fs::write(dir.path().join("broken.json"), "{ \"name\": ").unwrap();
+
+		let load = |chain: &Path| -> error::Result<()> {
+			let cli = SharedParams::from_iter(vec!["node-test", "--chain", chain.to_str().unwrap()]);
+			let mut config = Configuration::<(), TestExtension>::new(TEST_VERSION_INFO);
+			load_spec(&mut config, &cli, |_| Ok(None)).map(|_| ())
+		};
+
+		match load(&dir.path().join("missing.json")) {
+			Err(error::Error::SpecNotFound(path)) => assert!(path.ends_with("missing.json")),
+			other => panic!("expected SpecNotFound, got {:?}", other),
+		}
+
+		match load(dir.path()) {
+			Err(error::Error::InvalidSpecPath(_, reason)) => assert_eq!(reason, "not a file"),
+			other => panic!("expected InvalidSpecPath, got {:?}", other),
+		}
+
+		match load(&dir.path().join("broken.json")) {
+			Err(error::Error::InvalidSpecJson(_)) => {},
+			other => panic!("expected InvalidSpecJson, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn gzipped_spec_files_are_decompressed() {
+		use std::io::Write;
+
+		let dir = tempfile::tempdir().unwrap();
+		let json = r#"{
+			"name": "Compressed",
+			"id": "compressed",
+			"bootNodes": [],
+			"telemetryEndpoints": null,
+			"protocolId": null,
+			"properties": null,
+			"forkBlock": null,
+			"badBlocks": [],
+			"consensusEngine": null,
+			"genesis": { "raw": { "top": {}, "children": {} } }
+		}"#;
+		let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+		encoder.write_all(json.as_bytes()).unwrap();
+		let compressed = encoder.finish().unwrap();
+
+		let load = |chain: &Path| -> error::Result<String> {
+			let cli = SharedParams::from_iter(vec!["node-test", "--chain", chain.to_str().unwrap()]);
+			let mut config = Configuration::<(), TestExtension>::new(TEST_VERSION_INFO);
+			load_spec(&mut config, &cli, |_| Ok(None)).map(|spec| spec.name().to_string())
+		};
+
+		let path = dir.path().join("spec.json.gz");
+		fs::write(&path, &compressed).unwrap();
+		assert_eq!(load(&path).unwrap(), "Compressed");
+
+		fs::write(&path, &compressed[..compressed.len() / 2]).unwrap();
+		match load(&path) {
+			Err(error::Error::InvalidSpecJson(e)) => assert!(e.contains("decompressing"), "{}", e),
+			other => panic!("expected InvalidSpecJson, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn spec_is_read_from_stdin() {
+		let json = r#"{
+			"name": "Piped",
+			"id": "piped",
+			"bootNodes": [],
+			"telemetryEndpoints": null,
+			"protocolId": null,
+			"properties": null,
+			"forkBlock": 7,
+			"badBlocks": [],
+			"consensusEngine": null,
+			"genesis": { "raw": { "top": {}, "children": {} } }
+		}"#;
+		let cli = SharedParams::from_iter(vec!["node-test", "--chain", "-"]);
+		let load = |stdin: &[u8]| -> error::Result<String> {
+			let mut config = Configuration::<(), TestExtension>::new(TEST_VERSION_INFO);
+			let factory = |_: &str| -> Result<Option<ChainSpec<(), TestExtension>>, String> {
+				panic!("factory must not be called")
+			};
+			load_spec_with_stdin(&mut config, &cli, factory, stdin)?;
+			assert_eq!(config.expect_chain_spec().extensions().fork_block, Some(7));
+			Ok(config.expect_chain_spec().id().to_owned())
+		};
+
+		assert_eq!(load(json.as_bytes()).unwrap(), "piped");
+
+		match load(&b" \n"[..]) {
+			Err(error::Error::InvalidSpecJson(e)) => assert!(e.contains("empty")),
+			other => panic!("expected InvalidSpecJson, got {:?}", other),
+		}
+
+		match load(&b"{ \"name\": "[..]) {
+			Err(error::Error::InvalidSpecJson(_)) => {},
+			other => panic!("expected InvalidSpecJson, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn rpc_methods_are_resolved_against_the_rpc_interfaces() {
+		for (value, expected) in vec![
+			("auto", RpcMethods::Auto),
+			("safe", RpcMethods::Safe),
+			("Unsafe", RpcMethods::Unsafe),
+		] {
+			assert_eq!(RunCmd::from_iter(vec!["node-test", "--rpc-methods", value]).rpc_methods, expected);
+		}
+		assert_eq!(RunCmd::from_iter(vec!["node-test"]).rpc_methods, RpcMethods::Auto);
+		assert!(RunCmd::from_iter_safe(vec!["node-test", "--rpc-methods", "some"]).is_err());
+
+		for (args, expected) in vec![
+			(vec![], sc_service::config::RpcMethods::Unsafe),
+			(vec!["--rpc-external"], sc_service::config::RpcMethods::Safe),
+			(vec!["--unsafe-ws-external"], sc_service::config::RpcMethods::Safe),
+			(vec!["--rpc-external", "--rpc-methods", "unsafe"], sc_service::config::RpcMethods::Unsafe),
+			(vec!["--rpc-methods", "safe"], sc_service::config::RpcMethods::Safe),
+		] {
+			assert_eq!(configure(&args).unwrap().rpc_methods, expected, "{:?}", args);
+		}
+	}
+
+	#[test]
+	fn tracing_flags_reach_the_configuration() {
+		let config = configure(&[]).unwrap();
+		assert_eq!(config.tracing_targets, None);
+
+		let config = configure(&[
+			"--tracing-targets", "pallet,frame_executive",
+			"--tracing-receiver", "telemetry",
+		]).unwrap();
+		assert_eq!(config.tracing_targets, Some("pallet,frame_executive".to_string()));
+		assert!(match config.tracing_receiver {
+			sc_tracing::TracingReceiver::Telemetry => true,
+			_ => false,
+		});
+
+		let err = RunCmd::from_iter_safe(vec!["node-test", "--tracing-receiver", "stdout"]).unwrap_err();
+		assert_eq!(err.kind, clap::ErrorKind::InvalidValue);
+	}
+
+	#[test]
+	fn telemetry_urls_are_merged_with_the_spec() {
+		let telemetry = TelemetryEndpoints::new(vec![
+			("wss://telemetry.polkadot.io/submit/".to_string(), 0),
+			("wss://telemetry.example.org/submit/".to_string(), 1),
+		]);
+		let chain_spec = test_chain_spec(vec![], Some(telemetry));
+		let endpoints = |args: &[&str]| {
+			let config = configure_node(TEST_VERSION_INFO, chain_spec.clone(), args).unwrap();
+			config.telemetry_endpoints.map(|e| e.endpoints().to_vec())
+		};
+		let endpoint = |url: &str, verbosity| (url.to_string(), verbosity);
+
+		assert_eq!(endpoints(&[]), Some(vec![
+			endpoint("wss://telemetry.polkadot.io/submit/", 0),
+			endpoint("wss://telemetry.example.org/submit/", 1),
+		]));
+		assert_eq!(endpoints(&[
+			"--telemetry-url", "wss://telemetry.local/submit/ 9",
+			"--telemetry-url", "wss://telemetry.example.org/submit/ 5",
+		]), Some(vec![
+			endpoint("wss://telemetry.polkadot.io/submit/", 0),
+			endpoint("wss://telemetry.example.org/submit/", 5),
+			endpoint("wss://telemetry.local/submit/", 9),
+		]));
+		assert_eq!(endpoints(&[
+			"--telemetry-url", "wss://telemetry.local/submit/ 9",
+			"--telemetry-url-replace",
+		]), Some(vec![endpoint("wss://telemetry.local/submit/", 9)]));
+		assert_eq!(endpoints(&["--no-telemetry"]), None);
+	}
+
+	#[test]
+	fn bad_telemetry_urls_are_reported() {
+		let err = RunCmd::from_iter_safe(vec![
+			"node-test",
+			"--telemetry-url", "wss://telemetry.local/submit/ 1",
+			"--telemetry-url", "wss://telemetry.example.org/submit/ 10",
+		]).unwrap_err();
+		let entry = "telemetry endpoint 'wss://telemetry.example.org/submit/ 10'";
+		assert!(err.message.contains(&format!("verbosity '10' of {}", entry)), "{}", err.message);
+
+		let err = RunCmd::from_iter_safe(vec!["node-test", "--telemetry-url", "not a url 1"])
+			.unwrap_err();
+		assert!(err.message.contains("verbosity 'a url 1'"), "{}", err.message);
+
+		let err = RunCmd::from_iter_safe(vec!["node-test", "--telemetry-url", "telemetry 1"])
+			.unwrap_err();
+		assert!(err.message.contains("Invalid URL 'telemetry'"), "{}", err.message);
+
+		let err = RunCmd::from_iter_safe(vec!["node-test", "--telemetry-url-replace"]).unwrap_err();
+		assert_eq!(err.kind, clap::ErrorKind::MissingRequiredArgument);
+	}
+
+	#[test]
+	fn peer_limits_reach_the_network_configuration() {
+		let configure_network = |args: &[&str]| configure(args).map(|config| config.network);
+
+		let network = configure_network(&[]).unwrap();
+		assert_eq!((network.in_peers, network.out_peers), (25, 25));
+
+		let network = configure_network(&["--in-peers", "0", "--out-peers", "8"]).unwrap();
+		assert_eq!((network.in_peers, network.out_peers), (0, 8));
+
+		let err = configure_network(&["--out-peers", "0"]).unwrap_err();
+		assert!(err.to_string().contains("`--out-peers 0`"), "{}", err);
+	}
+
+	#[test]
+	fn config_file_fills_in_flags_not_given() {
+		let dir = tempfile::tempdir().unwrap();
+		let json = dir.path().join("node.json");
+		fs::write(&json, r#"{ "chain": "local", "base_path": "/from/file", "log": ["sync=debug"] }"#)
+			.unwrap();
+		let toml = dir.path().join("node.toml");
+		let file = "chain = \"local\"\ndev = true\ndatabase_path = \"/from/file\"\nunknown = 1\n";
+		fs::write(&toml, file).unwrap();
+
+		let parse = |args: Vec<&str>| {
+			try_from_iter::<RunCmd, _>(iter::once("node-test").chain(args), TEST_VERSION_INFO)
+				.unwrap()
+				.shared_params
+		};
+
+		let json = json.to_str().unwrap();
+		let params = parse(vec!["--config", json]);
+		assert_eq!(params.chain, Some("local".into()));
+		assert_eq!(params.base_path, Some(PathBuf::from("/from/file")));
+		assert_eq!(params.log, vec!["sync=debug".to_string()]);
+
+		// Flags on the command line take precedence over the file.
+		let params = parse(vec!["--chain", "dev", "-d", "/from/flag", "--config", json]);
+		assert_eq!(params.chain, Some("dev".into()));
+		assert_eq!(params.base_path, Some(PathBuf::from("/from/flag")));
+		assert_eq!(params.log, vec!["sync=debug".to_string()]);
+
+		let toml = format!("--config={}", toml.display());
+		let params = parse(vec![&toml, "--database-path", "/from/flag"]);
+		assert!(params.dev);
+		assert_eq!(params.database_path, Some(PathBuf::from("/from/flag")));
+		assert_eq!(config_file::unknown_keys(&toml).unwrap(), vec!["unknown".to_string()]);
+
+		let missing = dir.path().join("missing.json");
+		let err = try_from_iter::<RunCmd, _>(
+			vec!["node-test", "--config", missing.to_str().unwrap()],
+			TEST_VERSION_INFO,
+		).unwrap_err();
+		assert_eq!(err.kind, clap::ErrorKind::InvalidValue);
+		assert!(err.message.contains("Error reading config file"), "{}", err.message);
+	}
+
+	#[test]
+	fn block_and_state_pruning_are_independent() {
+		use sc_service::config::KeepBlocks;
+
+		let configure = |args: Vec<&str>| {
+			let cli = RunCmd::from_iter(iter::once("node-test").chain(args));
+			let mut config = Configuration::<(), ()>::new(TEST_VERSION_INFO);
+			fill_import_params(&mut config, &cli.import_params, sc_service::Roles::FULL, false)
+				.map(|_| (config.pruning, config.keep_blocks))
+		};
+
+		let (pruning, keep_blocks) = configure(vec![]).unwrap();
+		assert_eq!((pruning.is_archive(), keep_blocks), (false, KeepBlocks::All));
+
+		let (pruning, keep_blocks) = configure(vec!["--pruning", "archive"]).unwrap();
+		assert_eq!((pruning.is_archive(), keep_blocks), (true, KeepBlocks::All));
+
+		let (pruning, keep_blocks) = configure(vec!["--pruning", "100", "--keep-blocks", "1000"])
+			.unwrap();
+		assert_eq!((pruning.is_archive(), keep_blocks), (false, KeepBlocks::Some(1000)));
+
+		let (_, keep_blocks) = configure(vec!["--keep-blocks", "archive"]).unwrap();
+		assert_eq!(keep_blocks, KeepBlocks::All);
+
+		let err = configure(vec!["--pruning", "archive", "--keep-blocks", "100"]).unwrap_err();
+		assert!(err.to_string().contains("the state of all blocks"), "{}", err);
+		let err = configure(vec!["--pruning", "1000", "--keep-blocks", "100"]).unwrap_err();
+		assert!(err.to_string().contains("the state of 1000 blocks"), "{}", err);
+
+		let err = RunCmd::from_iter_safe(vec!["node-test", "--keep-blocks", "0"]).unwrap_err();
+		assert!(err.message.contains("Invalid `--keep-blocks` '0'"), "{}", err.message);
+	}
+
+	#[test]
+	fn pruning_reports_count_what_the_settings_keep() {
+		use sc_service::config::KeepBlocks;
+
+		let report = |pruning: &PruningMode, keep_blocks| {
+			let report = pruning_report(1_000, 990, pruning, keep_blocks);
+			(
+				(report.blocks_retained, report.blocks_pruned),
+				(report.states_retained, report.states_pruned),
+			)
+		};
+
+		for archive in &[PruningMode::ArchiveAll, PruningMode::ArchiveCanonical] {
+			assert_eq!(report(archive, KeepBlocks::All), ((1_001, 0), (1_001, 0)));
+		}
+		// The 10 unfinalized blocks are always kept, on top of the last 256 finalized.
+		assert_eq!(
+			report(&PruningMode::keep_blocks(256), KeepBlocks::Some(500)),
+			((510, 491), (266, 735)),
+		);
+		assert_eq!(
+			report(&PruningMode::keep_blocks(5_000), KeepBlocks::Some(5_000)),
+			((1_001, 0), (1_001, 0)),
+		);
+		// Without a limit only the unfinalized states are kept.
+		let unlimited = PruningMode::Constrained(Default::default());
+		assert_eq!(report(&unlimited, KeepBlocks::All), ((1_001, 0), (10, 991)));
+	}
+
+	#[test]
+	fn prometheus_flags_reach_the_configuration() {
+		let prometheus_port = |args: &[&str]| configure(args).unwrap().prometheus_port;
+
+		assert_eq!(prometheus_port(&[]), Some("127.0.0.1:9615".parse().unwrap()));
+		assert_eq!(
+			prometheus_port(&["--prometheus-port", "9000"]),
+			Some("127.0.0.1:9000".parse().unwrap()),
+		);
+		assert_eq!(
+			prometheus_port(&["--prometheus-external", "--prometheus-port", "9000"]),
+			Some("0.0.0.0:9000".parse().unwrap()),
+		);
+		assert_eq!(prometheus_port(&["--no-prometheus"]), None);
+
+		let err = RunCmd::from_iter_safe(
+			vec!["node-test", "--no-prometheus", "--prometheus-port", "9000"],
+		).unwrap_err();
+		assert_eq!(err.kind, clap::ErrorKind::ArgumentConflict);
+	}
+
+	#[test]
+	fn local_discovery_flags_set_mdns() {
+		let discovery = |args: &[&str]| {
+			let config = configure(args).unwrap();
+			let enable_mdns = match config.network.transport {
+				TransportConfig::Normal { enable_mdns, .. } => enable_mdns,
+				TransportConfig::MemoryOnly => panic!("The CLI configures a normal transport"),
+			};
+			(enable_mdns, config.network.boot_nodes)
+		};
+
+		assert_eq!(discovery(&[]).0, true);
+		assert_eq!(discovery(&["--no-mdns"]).0, false);
+		assert_eq!(discovery(&["--dev"]).0, false);
+		assert_eq!(discovery(&["--dev", "--discover-local"]).0, true);
+
+		let bootnode = "/ip4/127.0.0.1/tcp/30333/p2p/QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV";
+		let (enable_mdns, boot_nodes) = discovery(&["--discover-local", "--bootnodes", bootnode]);
+		assert!(enable_mdns);
+		assert_eq!(boot_nodes, vec![bootnode.to_string()]);
+
+		let err = RunCmd::from_iter_safe(vec!["node-test", "--no-mdns", "--discover-local"])
+			.unwrap_err();
+		assert_eq!(err.kind, clap::ErrorKind::ArgumentConflict);
+	}
+
+	#[test]
+	fn listen_addresses_replace_the_default() {
+		let listen_addresses = |args: &[&str]| configure(args).map(|config| {
+			config.network.listen_addresses.iter().map(ToString::to_string).collect::<Vec<_>>()
+		});
+
+		assert_eq!(listen_addresses(&[]).unwrap(), vec!["/ip4/0.0.0.0/tcp/30333".to_string()]);
+		assert_eq!(
+			listen_addresses(&["--listen-addr", "/ip4/127.0.0.1/tcp/30400"]).unwrap(),
+			vec!["/ip4/127.0.0.1/tcp/30400".to_string()],
+		);
+		assert_eq!(
+			listen_addresses(&[
+				"--listen-addr", "/ip4/0.0.0.0/tcp/30400",
+				"--listen-addr", "/ip6/::/tcp/30400",
+			]).unwrap(),
+			vec!["/ip4/0.0.0.0/tcp/30400".to_string(), "/ip6/::/tcp/30400".to_string()],
+		);
+
+		let err = listen_addresses(&[
+			"--listen-addr", "/ip4/0.0.0.0/tcp/30400",
+			"--listen-addr", "/ip4/0.0.0.0/tcp/port",
+		]).unwrap_err();
+		assert!(
+			err.to_string().contains("Invalid `--listen-addr` at index 1 ('/ip4/0.0.0.0/tcp/port')"),
+			"{}",
+			err,
+		);
+	}
+
+	#[test]
+	fn rpc_cors_reaches_the_configuration() {
+		let config = configure(&[]).unwrap();
+		assert!(config.rpc_cors.as_ref().unwrap().contains(&"http://localhost:*".to_string()));
+		assert_eq!(cors_warning(&config.rpc_cors, &[config.rpc_http, config.rpc_ws]), None);
+
+		let config = configure(&["--rpc-cors", "https://dapp.example.com,null"]).unwrap();
+		assert_eq!(
+			config.rpc_cors,
+			Some(vec!["https://dapp.example.com".to_string(), "null".to_string()]),
+		);
+
+		let config = configure(&["--rpc-cors", "all"]).unwrap();
+		assert_eq!(config.rpc_cors, None);
+		// Listening locally, only websites opened on this machine can reach the server.
+		assert_eq!(cors_warning(&config.rpc_cors, &[config.rpc_http, config.rpc_ws]), None);
+
+		let config = configure(&["--rpc-cors", "all", "--rpc-external"]).unwrap();
+		let warning = cors_warning(&config.rpc_cors, &[config.rpc_http, config.rpc_ws]).unwrap();
+		assert!(warning.contains("0.0.0.0:9933"), "{}", warning);
+	}
+
+	#[test]
+	fn chains_built_in_are_listed_and_named_by_errors() {
+		assert_eq!(chain_list(TEST_VERSION_INFO), "dev\nlocal\n");
+		let cli = SharedParams::from_iter(vec!["node-test", "--list-chains"]);
+		let mut config = Configuration::<(), TestExtension>::new(TEST_VERSION_INFO);
+		let init = init_config(&mut config, &cli, TEST_VERSION_INFO, |_| Ok(None)).unwrap();
+		assert_eq!(init, Initialized::ListChains("dev\nlocal\n".into()));
+		assert!(config.chain_spec.is_none());
+		assert!(cli.build_configuration(|_| Ok(None), TEST_VERSION_INFO).unwrap().is_none());
+
+		let dir = tempfile::tempdir().unwrap();
+		let missing = dir.path().join("missing.json");
+		let cli = SharedParams::from_iter(vec!["node-test", "--chain", missing.to_str().unwrap()]);
+		let mut config = Configuration::<(), TestExtension>::new(TEST_VERSION_INFO);
+		let err = init_config(&mut config, &cli, TEST_VERSION_INFO, |_| Ok(None)).unwrap_err();
+		assert_eq!(
+			err.to_string(),
+			format!(
+				"Unknown chain '{}', expected a path to a chain spec file or one of: dev, local",
+				missing.display(),
+			),
+		);
+	}
+
+	#[test]
+	fn wasm_only_builds_reject_native_strategies() {
+		use sc_client_api::ExecutionStrategy::{AlwaysWasm, NativeElseWasm};
+
+		let wasm_only = VersionInfo { native_runtime: false, ..TEST_VERSION_INFO.clone() };
+		let execution_strategies = |args: &[&str]| {
+			configure_node(&wasm_only, test_chain_spec(vec![], None), args)
+				.map(|config| config.execution_strategies)
+		};
+		let all = |strategies: &ExecutionStrategies, expected: sc_client_api::ExecutionStrategy| [
+			strategies.syncing,
+			strategies.importing,
+			strategies.block_construction,
+			strategies.offchain_worker,
+			strategies.other,
+		].iter().all(|strategy| *strategy == expected);
+
+		// The defaults resolve to Wasm, in development mode too.
+		for args in &[&[][..], &["--dev"][..]] {
+			assert!(all(&execution_strategies(args).unwrap(), AlwaysWasm), "{:?}", args);
+		}
+		assert!(all(&execution_strategies(&["--execution", "wasm"]).unwrap(), AlwaysWasm));
+		assert!(all(
+			&execution_strategies(&["--execution", "NativeElseWasm"]).unwrap(),
+			NativeElseWasm,
+		));
+		let strategies = execution_strategies(&["--execution-other", "NativeElseWasm"]).unwrap();
+		assert_eq!((strategies.syncing, strategies.other), (AlwaysWasm, NativeElseWasm));
+
+		let err = execution_strategies(&["--execution", "native"]).unwrap_err().to_string();
+		assert!(err.contains("NativeWhenPossible"), "{}", err);
+		assert!(err.contains("needs the native runtime"), "{}", err);
+
+		let err = execution_strategies(&["--execution-syncing", "both"]).unwrap_err().to_string();
+		assert!(err.contains("Both for syncing"), "{}", err);
+		assert!(err.contains("--execution-syncing wasm"), "{}", err);
+	}
+
+	#[test]
+	fn reserved_nodes_are_checked_and_can_be_the_only_peers() {
+		let configure_network = |args: &[&str]| configure(args).map(|config| config.network);
+		let alice = "/ip4/127.0.0.1/tcp/30333/p2p/QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV";
+		let bob = "/ip4/127.0.0.1/tcp/30334/p2p/QmWv9Ww7znzgLFyCzf21SR6tUKXrmHCZH9KhebeH4gyE9f";
+
+		let network = configure_network(&[]).unwrap();
+		assert!(network.reserved_nodes.is_empty());
+		assert_eq!(network.non_reserved_mode, NonReservedPeerMode::Accept);
+
+		let network = configure_network(&["--reserved-nodes", alice, "--reserved-nodes", bob])
+			.unwrap();
+		assert_eq!(network.reserved_nodes, vec![alice.to_string(), bob.to_string()]);
+		assert_eq!(network.non_reserved_mode, NonReservedPeerMode::Accept);
+
+		let network = configure_network(&["--reserved-nodes", alice, "--reserved-only"]).unwrap();
+		assert_eq!(network.reserved_nodes, vec![alice.to_string()]);
+		assert_eq!(network.non_reserved_mode, NonReservedPeerMode::Deny);
+
+		let err = configure_network(&[
+			"--reserved-nodes", alice,
+			"--reserved-nodes", "/ip4/127.0.0.1/tcp/30334",
+		]).unwrap_err();
+		assert!(
+			err.to_string().contains("Invalid `--reserved-nodes` at index 1 ('/ip4/127.0.0.1/tcp/30334')"),
+			"{}",
+			err,
+		);
+	}
+
+	#[test]
+	fn reserved_only_without_reserved_nodes_warns() {
+		let alice = "/ip4/127.0.0.1/tcp/30333/p2p/QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV";
+		assert_eq!(reserved_only_warning(false, &[]), None);
+		assert_eq!(reserved_only_warning(true, &[alice.to_string()]), None);
+		let warning = reserved_only_warning(true, &[]).unwrap();
+		assert!(warning.contains("won't connect to any peer"), "{}", warning);
+	}
+
 }
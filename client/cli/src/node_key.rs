@@ -38,6 +38,14 @@ pub fn is_node_name_valid(_name: &str) -> Result<(), &str> {
 		return Err("Node name too long");
 	}
 
+	if name.len() > crate::NODE_NAME_MAX_BYTES {
+		return Err("Node name is too many bytes long for telemetry");
+	}
+
+	if name.chars().any(char::is_control) {
+		return Err("Node name should not contain control characters such as newlines");
+	}
+
 	let invalid_chars = r"[\\.@]";
 	let re = Regex::new(invalid_chars).unwrap();
 	if re.is_match(&name) {
@@ -78,7 +86,11 @@ fn invalid_node_key(e: impl std::fmt::Display) -> error::Error {
 
 /// Parse a Ed25519 secret key from a hex string into a `sc_network::Secret`.
 fn parse_ed25519_secret(hex: &String) -> error::Result<sc_network::config::Ed25519Secret> {
-	H256::from_str(&hex).map_err(invalid_node_key).and_then(|bytes|
+	let digits = hex.trim_start_matches("0x").len();
+	if digits != 64 {
+		return Err(invalid_node_key(format!("expected 64 hex characters, found {}", digits)));
+	}
+	H256::from_str(hex.trim_start_matches("0x")).map_err(invalid_node_key).and_then(|bytes|
 		sc_network::config::identity::ed25519::SecretKey::from_bytes(bytes)
 			.map(sc_network::config::Secret::Input)
 			.map_err(invalid_node_key))
@@ -111,6 +123,24 @@ mod tests {
 		assert!(is_node_name_valid("email@domain").is_err());
 	}
 
+	#[test]
+	fn tests_node_name_control_chars() {
+		assert_eq!(
+			is_node_name_valid("two\nlines"),
+			Err("Node name should not contain control characters such as newlines"),
+		);
+		assert!(is_node_name_valid("bell\u{7}").is_err());
+		assert!(is_node_name_valid("tab\tbed").is_err());
+	}
+
+	#[test]
+	fn tests_node_name_too_many_bytes() {
+		// Fewer characters than the limit, but each takes four bytes.
+		let name = "\u{1F980}".repeat(20);
+		assert!(name.chars().count() < crate::NODE_NAME_MAX_LENGTH);
+		assert_eq!(is_node_name_valid(&name), Err("Node name is too many bytes long for telemetry"));
+	}
+
 	#[test]
 	fn test_node_key_config_input() {
 		fn secret_input(net_config_dir: Option<String>) -> error::Result<()> {
@@ -206,4 +236,36 @@ mod tests {
 		assert!(no_config_dir().is_ok());
 		assert!(some_config_dir("x".to_string()).is_ok());
 	}
+
+	#[test]
+	fn node_key_hex_must_be_32_bytes() {
+		let params = |node_key: &str| NodeKeyParams {
+			node_key_type: NodeKeyType::Ed25519,
+			node_key: Some(node_key.to_string()),
+			node_key_file: None,
+		};
+		let key = format!("{:x}", H256::from_slice(ed25519::SecretKey::generate().as_ref()));
+
+		assert!(node_key_config::<String>(params(&key), &None).is_ok());
+		assert!(node_key_config::<String>(params(&format!("0x{}", key)), &None).is_ok());
+		let err = node_key_config::<String>(params(&key[2..]), &None).unwrap_err();
+		assert_eq!(err.to_string(), "Invalid node key: expected 64 hex characters, found 62");
+		assert!(node_key_config::<String>(params(&"zz".repeat(32)), &None).is_err());
+	}
+
+	#[test]
+	fn node_key_and_node_key_file_are_exclusive() {
+		use structopt::StructOpt;
+
+		let key = "0000000000000000000000000000000000000000000000000000000000000001";
+		let params = NodeKeyParams::from_iter(vec!["node-test", "--node-key-file", "/test/key"]);
+		assert_eq!(params.node_key_file, Some(PathBuf::from("/test/key")));
+		let params = NodeKeyParams::from_iter(vec!["node-test", "--node-key", key]);
+		assert_eq!(params.node_key.as_ref().map(|k| &k[..]), Some(key));
+
+		let err = NodeKeyParams::from_iter_safe(vec![
+			"node-test", "--node-key", key, "--node-key-file", "/test/key",
+		]).unwrap_err();
+		assert_eq!(err.kind, structopt::clap::ErrorKind::ArgumentConflict);
+	}
 }
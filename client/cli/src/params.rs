@@ -18,13 +18,13 @@ use std::{str::FromStr, path::PathBuf};
 use structopt::{StructOpt, clap::arg_enum};
 use sc_service::{
 	AbstractService, Configuration, ChainSpecExtension, RuntimeGenesis, ServiceBuilderCommand,
-	config::DatabaseConfig,
+	ChainSpec, config::DatabaseConfig,
 };
-use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use sp_runtime::{BuildStorage, traits::{Block as BlockT, Header as HeaderT}};
 use crate::VersionInfo;
 use crate::error;
 use std::fmt::Debug;
-use log::info;
+use log::{info, warn};
 use sc_network::config::build_multiaddr;
 use std::io;
 use std::fs;
@@ -101,13 +101,33 @@ arg_enum! {
 	}
 }
 
+arg_enum! {
+	/// Which RPC methods to expose.
+	#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+	pub enum RpcMethods {
+		// Expose every method if the RPC servers only listen locally, and only the safe ones
+		// otherwise.
+		Auto,
+		// Only expose the methods that are safe to call publicly.
+		Safe,
+		// Expose every method.
+		Unsafe,
+	}
+}
+
 /// Shared parameters used by all `CoreParams`.
 #[derive(Debug, StructOpt, Clone)]
 pub struct SharedParams {
-	/// Specify the chain specification (one of dev, local or staging).
+	/// Specify the chain specification, either a path to a file or one of the chains built in.
+	///
+	/// Use `-` to read the chain specification JSON from standard input.
 	#[structopt(long = "chain", value_name = "CHAIN_SPEC")]
 	pub chain: Option<String>,
 
+	/// List the chains `--chain` accepts besides paths to chain spec files, and exit.
+	#[structopt(long = "list-chains")]
+	pub list_chains: bool,
+
 	/// Specify the development chain.
 	#[structopt(long = "dev")]
 	pub dev: bool,
@@ -116,9 +136,148 @@ pub struct SharedParams {
 	#[structopt(long = "base-path", short = "d", value_name = "PATH", parse(from_os_str))]
 	pub base_path: Option<PathBuf>,
 
+	/// Specify custom database path.
+	///
+	/// By default the database is stored in the chain directory inside the
+	/// base path. This allows putting it on a different disk.
+	#[structopt(long = "database-path", value_name = "PATH", parse(from_os_str))]
+	pub database_path: Option<PathBuf>,
+
 	/// Sets a custom logging filter.
+	///
+	/// This flag can be passed multiple times. The directives are applied in
+	/// order, so a later directive for a target overrides an earlier one
+	/// (e.g. `-l info -l sync=debug`).
 	#[structopt(short = "l", long = "log", value_name = "LOG_PATTERN")]
-	pub log: Option<String>,
+	pub log: Vec<String>,
+
+	/// Include the module path and source location in every log line.
+	#[structopt(long = "detailed-log-output")]
+	pub detailed_log_output: bool,
+
+	/// Override a field of the chain specification extensions.
+	///
+	/// The value is parsed as JSON and replaces the field named `KEY` of the
+	/// loaded chain spec. This flag can be passed multiple times.
+	#[structopt(
+		long = "chain-spec-extension",
+		value_name = "KEY=VALUE",
+		parse(try_from_str = parse_chain_spec_extension)
+	)]
+	pub chain_spec_extensions: Vec<(String, serde_json::Value)>,
+
+	/// Read defaults for the flags above from this file.
+	///
+	/// The file is a TOML table if its extension is `toml` and a JSON object otherwise, keyed by
+	/// the long names of the flags. Flags given on the command line take precedence.
+	#[structopt(long = "config", value_name = "PATH", parse(from_os_str))]
+	pub config: Option<PathBuf>,
+}
+
+impl SharedParams {
+	/// Returns the logging filter made of all `--log` directives, in order.
+	pub fn log_filters(&self) -> String {
+		self.log.join(",")
+	}
+
+	/// Returns the configuration of the logger.
+	pub fn logger_config(&self) -> crate::LoggerConfig {
+		crate::LoggerConfig {
+			pattern: self.log_filters(),
+			detailed_output: self.detailed_log_output,
+		}
+	}
+
+	/// Build a new `Configuration` from these parameters.
+	///
+	/// This is the same as calling [`init_config`](crate::init_config) on a
+	/// `Configuration::new(version)`, but a partially initialized config is
+	/// never handed out on failure. Returns `None` if only the chains are to be
+	/// listed, see [`Initialized::ListChains`](crate::Initialized::ListChains).
+	pub fn build_configuration<G, E, F>(
+		&self,
+		spec_factory: F,
+		version: &VersionInfo,
+	) -> error::Result<Option<Configuration<G, E>>> where
+		F: FnOnce(&str) -> Result<Option<ChainSpec<G, E>>, String>,
+		G: RuntimeGenesis,
+		E: ChainSpecExtension,
+	{
+		let mut config = Configuration::new(version);
+		match crate::init_config(&mut config, self, version, spec_factory)? {
+			crate::Initialized::Run => Ok(Some(config)),
+			crate::Initialized::ListChains(_) => Ok(None),
+		}
+	}
+
+	/// Check for combinations of flags that don't make sense together.
+	///
+	/// Combinations that are merely suspicious are logged as warnings, all others are
+	/// returned as a single error listing every problem found.
+	pub fn validate(&self) -> error::Result<()> {
+		let mut errors = Vec::new();
+
+		if self.dev {
+			match self.chain.as_ref().map(String::as_str) {
+				None | Some("dev") => {},
+				Some(chain) => errors.push(
+					format!("`--dev` conflicts with `--chain {}`, the dev chain would be ignored", chain)
+				),
+			}
+
+			if let Some(base_path) = &self.base_path {
+				warn!(
+					"The development chain keeps its state in the custom base path {} across runs",
+					base_path.display(),
+				);
+			}
+		}
+
+		for (i, (key, _)) in self.chain_spec_extensions.iter().enumerate() {
+			let first = self.chain_spec_extensions.iter().position(|(k, _)| k == key);
+			if first == Some(i) && self.chain_spec_extensions[i + 1..].iter().any(|(k, _)| k == key) {
+				errors.push(format!("`--chain-spec-extension` overrides '{}' more than once", key));
+			}
+		}
+
+		flag_errors(errors)
+	}
+}
+
+/// Parse a `KEY=VALUE` chain spec extension override, the value being JSON.
+fn parse_chain_spec_extension(s: &str) -> Result<(String, serde_json::Value), String> {
+	let pos = s.find('=').ok_or_else(||
+		format!("Invalid chain spec extension override '{}', expected KEY=VALUE", s)
+	)?;
+	let key = s[..pos].to_owned();
+	let value = serde_json::from_str(&s[pos + 1..]).map_err(|e|
+		format!("Invalid JSON value for chain spec extension '{}': {}", key, e)
+	)?;
+
+	Ok((key, value))
+}
+
+/// Parse a `--keep-blocks` value, a positive number of blocks or `archive`.
+fn parse_keep_blocks(s: &str) -> Result<sc_service::config::KeepBlocks, String> {
+	match s {
+		"archive" => Ok(sc_service::config::KeepBlocks::All),
+		s => match s.parse() {
+			Ok(count) if count > 0 => Ok(sc_service::config::KeepBlocks::Some(count)),
+			_ => Err(format!(
+				"Invalid `--keep-blocks` '{}', expected a positive number of blocks or 'archive'",
+				s,
+			)),
+		},
+	}
+}
+
+/// Turns the problems `validate` found into a single error, if there are any.
+fn flag_errors(errors: Vec<String>) -> error::Result<()> {
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(error::Error::Input(format!("Invalid flag combination: {}", errors.join("; "))))
+	}
 }
 
 /// Parameters for block import.
@@ -137,9 +296,22 @@ pub struct ImportParams {
 	/// When running as a validator it is highly recommended to disable state
 	/// pruning (i.e. 'archive') which is the default. The node will refuse to
 	/// start as a validator if pruning is enabled unless this option is set.
+	/// It is also required to enable pruning on a database that was previously
+	/// run as an archive.
 	#[structopt(long = "unsafe-pruning")]
 	pub unsafe_pruning: bool,
 
+	/// Specify the block body pruning mode, a number of finalized blocks to keep or 'archive'.
+	///
+	/// This is independent of `--pruning`, but the state of a block can't be kept longer
+	/// than its body. Default is to keep all block bodies.
+	#[structopt(
+		long = "keep-blocks",
+		value_name = "COUNT",
+		parse(try_from_str = parse_keep_blocks)
+	)]
+	pub keep_blocks: Option<sc_service::config::KeepBlocks>,
+
 	/// Method for executing Wasm runtime code.
 	#[structopt(
 		long = "wasm-execution",
@@ -177,6 +349,44 @@ pub struct ImportParams {
 	pub tracing_receiver: TracingReceiver,
 }
 
+/// The default of `--state-cache-size`, below which an archive node is warned about.
+///
+/// Keep in line with the `default_value` of [`ImportParams::state_cache_size`].
+const DEFAULT_STATE_CACHE_SIZE: usize = 67108864;
+
+impl ImportParams {
+	/// Check for combinations of flags that don't make sense together, like
+	/// [`SharedParams::validate`].
+	pub fn validate(&self) -> error::Result<()> {
+		let mut errors = Vec::new();
+
+		match self.pruning.as_ref().map(String::as_str) {
+			None => {},
+			Some("archive") => {
+				if self.state_cache_size < DEFAULT_STATE_CACHE_SIZE {
+					warn!(
+						"An archive node with a state cache of {} bytes serves queries of old \
+						states mostly from the database",
+						self.state_cache_size,
+					);
+				}
+
+				if self.unsafe_pruning {
+					warn!("`--unsafe-pruning` has no effect as `--pruning archive` prunes nothing");
+				}
+			},
+			Some(blocks) => if blocks.parse::<u32>().is_err() {
+				errors.push(format!(
+					"`--pruning {}` is neither 'archive' nor a number of blocks to keep",
+					blocks,
+				));
+			},
+		}
+
+		flag_errors(errors)
+	}
+}
+
 /// Parameters used to create the network configuration.
 #[derive(Debug, StructOpt, Clone)]
 pub struct NetworkConfigurationParams {
@@ -184,6 +394,12 @@ pub struct NetworkConfigurationParams {
 	#[structopt(long = "bootnodes", value_name = "URL")]
 	pub bootnodes: Vec<String>,
 
+	/// Ignore the bootnodes of the chain specification.
+	///
+	/// Only the bootnodes given with `--bootnodes`, if any, are used.
+	#[structopt(long = "no-default-bootnodes")]
+	pub no_default_bootnodes: bool,
+
 	/// Specify a list of reserved node addresses.
 	#[structopt(long = "reserved-nodes", value_name = "URL")]
 	pub reserved_nodes: Vec<String>,
@@ -204,6 +420,8 @@ pub struct NetworkConfigurationParams {
 	pub sentry_nodes: Vec<String>,
 
 	/// Listen on this multiaddress.
+	///
+	/// Can be given multiple times, replacing the default of listening on all interfaces.
 	#[structopt(long = "listen-addr", value_name = "LISTEN_ADDR")]
 	pub listen_addr: Vec<String>,
 
@@ -234,6 +452,12 @@ pub struct NetworkConfigurationParams {
 	#[structopt(long = "no-mdns")]
 	pub no_mdns: bool,
 
+	/// Discover other nodes on the local network with mDNS, even with `--dev`.
+	///
+	/// Nodes found this way are added to those given by `--bootnodes`.
+	#[structopt(long = "discover-local", conflicts_with = "no-mdns")]
+	pub discover_local: bool,
+
 	/// Maximum number of peers to ask the same blocks in parallel.
 	///
 	/// This allows downlading announced blocks from multiple peers. Decrease to save
@@ -271,12 +495,12 @@ pub struct NodeKeyParams {
 	///   The value is parsed as a hex-encoded Ed25519 32 bytes secret key,
 	///   i.e. 64 hex characters.
 	///
-	/// The value of this option takes precedence over `--node-key-file`.
+	/// This option can't be combined with `--node-key-file`.
 	///
 	/// WARNING: Secrets provided as command-line arguments are easily exposed.
 	/// Use of this option should be limited to development and testing. To use
 	/// an externally managed secret key, use `--node-key-file` instead.
-	#[structopt(long = "node-key", value_name = "KEY")]
+	#[structopt(long = "node-key", value_name = "KEY", conflicts_with = "node-key-file")]
 	pub node_key: Option<String>,
 
 	/// The type of secret key to use for libp2p networking.
@@ -484,9 +708,24 @@ pub struct RunCmd {
 	#[structopt(long = "unsafe-ws-external")]
 	pub unsafe_ws_external: bool,
 
+	/// RPC methods to expose.
+	///
+	/// `auto` exposes only the safe methods if an RPC server listens on a non-local interface,
+	/// and every method otherwise. `safe` always exposes only the safe methods, `unsafe` always
+	/// exposes every method.
+	#[structopt(
+		long = "rpc-methods",
+		value_name = "METHOD SET",
+		possible_values = &RpcMethods::variants(),
+		case_insensitive = true,
+		default_value = "Auto",
+	)]
+	pub rpc_methods: RpcMethods,
+
 	/// Listen to all Prometheus endpoint interfaces.
 	///
-	/// Default is local.
+	/// Default is local. Anyone reaching the node can then read its metrics, which is logged
+	/// as a warning.
 	#[structopt(long = "prometheus-external")]
 	pub prometheus_external: bool,
 
@@ -519,7 +758,10 @@ pub struct RunCmd {
 	/// Do not expose a Prometheus metric endpoint.
 	///
 	/// Prometheus metric endpoint is enabled by default.
-	#[structopt(long = "no-prometheus")]
+	#[structopt(
+		long = "no-prometheus",
+		conflicts_with_all = &[ "prometheus-port", "prometheus-external" ]
+	)]
 	pub no_prometheus: bool,
 
 	/// The human-readable name for this node.
@@ -539,10 +781,15 @@ pub struct RunCmd {
 	/// This flag can be passed multiple times as a mean to specify multiple
 	/// telemetry endpoints. Verbosity levels range from 0-9, with 0 denoting
 	/// the least verbosity. If no verbosity level is specified the default is
-	/// 0.
+	/// 0. The endpoints are added to those of the chain spec, an endpoint of
+	/// the chain spec with the same URL taking the verbosity given here.
 	#[structopt(long = "telemetry-url", value_name = "URL VERBOSITY", parse(try_from_str = parse_telemetry_endpoints))]
 	pub telemetry_endpoints: Vec<(String, u8)>,
 
+	/// Replace the telemetry endpoints of the chain spec with those of `--telemetry-url`.
+	#[structopt(long = "telemetry-url-replace", requires = "telemetry-endpoints")]
+	pub telemetry_url_replace: bool,
+
 	/// Should execute offchain workers on every block.
 	///
 	/// By default it's only enabled for nodes that are authoring new blocks.
@@ -555,6 +802,12 @@ pub struct RunCmd {
 	)]
 	pub offchain_worker: OffchainWorkerEnabled,
 
+	/// Print the resolved configuration and exit without starting the node.
+	///
+	/// Secrets such as the keystore password are redacted from the output.
+	#[structopt(long = "print-config")]
+	pub print_config: bool,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub shared_params: SharedParams,
@@ -652,19 +905,36 @@ impl RunCmd {
 	}
 }
 
+/// The highest verbosity level of a telemetry endpoint.
+const MAX_TELEMETRY_VERBOSITY: u8 = 9;
+
 /// Default to verbosity level 0, if none is provided.
-fn parse_telemetry_endpoints(s: &str) -> Result<(String, u8), Box<dyn std::error::Error>> {
-	let pos = s.find(' ');
-	match pos {
-		None => {
-			Ok((s.to_owned(), 0))
+fn parse_telemetry_endpoints(s: &str) -> Result<(String, u8), String> {
+	let (url, verbosity) = match s.find(' ') {
+		None => (s, 0),
+		Some(pos) => {
+			let verbosity = &s[pos + 1..];
+			let verbosity = verbosity.parse().ok()
+				.filter(|v| *v <= MAX_TELEMETRY_VERBOSITY)
+				.ok_or_else(|| format!(
+					"Invalid verbosity '{}' of telemetry endpoint '{}', expected 0 to {}",
+					verbosity,
+					s,
+					MAX_TELEMETRY_VERBOSITY,
+				))?;
+			(&s[..pos], verbosity)
 		},
-		Some(pos_) => {
-			let verbosity = s[pos_ + 1..].parse()?;
-			let url = s[..pos_].parse()?;
-			Ok((url, verbosity))
-		}
+	};
+
+	if !is_valid_telemetry_url(url) {
+		return Err(format!("Invalid URL '{}' of telemetry endpoint '{}'", url, s));
 	}
+	Ok((url.to_owned(), verbosity))
+}
+
+/// Whether `url` is in one of the formats accepted by the telemetry worker.
+fn is_valid_telemetry_url(url: &str) -> bool {
+	url.parse::<sc_network::Multiaddr>().is_ok() || sc_network::multiaddr::from_url(url).is_ok()
 }
 
 /// CORS setting
@@ -728,6 +998,14 @@ pub struct BuildSpecCmd {
 	pub node_key_params: NodeKeyParams,
 }
 
+/// The `validate-spec` command used to check a chain specification without starting a node.
+#[derive(Debug, StructOpt, Clone)]
+pub struct ValidateSpecCmd {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
 /// Wrapper type of `String` that holds an unsigned integer of arbitrary size, formatted as a decimal.
 #[derive(Debug, Clone)]
 pub struct BlockNumber(String);
@@ -859,6 +1137,18 @@ pub struct PurgeChainCmd {
 	pub shared_params: SharedParams,
 }
 
+/// The `blocks-pruning-check` command used to preview what pruning discards.
+#[derive(Debug, StructOpt, Clone)]
+pub struct BlocksPruningCheckCmd {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
 /// All core commands that are provided by default.
 ///
 /// The core commands are split into multiple subcommands and `Run` is the default subcommand. From
@@ -869,6 +1159,9 @@ pub enum Subcommand {
 	/// Build a spec.json file, outputing to stdout.
 	BuildSpec(BuildSpecCmd),
 
+	/// Validate a chain specification without starting the node.
+	ValidateSpec(ValidateSpecCmd),
+
 	/// Export blocks to a file.
 	ExportBlocks(ExportBlocksCmd),
 
@@ -883,6 +1176,9 @@ pub enum Subcommand {
 
 	/// Remove the whole chain data.
 	PurgeChain(PurgeChainCmd),
+
+	/// Report how many blocks and states the pruning settings keep, without pruning any.
+	BlocksPruningCheck(BlocksPruningCheckCmd),
 }
 
 impl Subcommand {
@@ -892,11 +1188,13 @@ impl Subcommand {
 
 		match self {
 			BuildSpec(params) => &params.shared_params,
+			ValidateSpec(params) => &params.shared_params,
 			ExportBlocks(params) => &params.shared_params,
 			ImportBlocks(params) => &params.shared_params,
 			CheckBlock(params) => &params.shared_params,
 			Revert(params) => &params.shared_params,
 			PurgeChain(params) => &params.shared_params,
+			BlocksPruningCheck(params) => &params.shared_params,
 		}
 	}
 
@@ -919,11 +1217,13 @@ impl Subcommand {
 
 		match self {
 			Subcommand::BuildSpec(cmd) => cmd.run(config),
+			Subcommand::ValidateSpec(cmd) => cmd.run(config),
 			Subcommand::ExportBlocks(cmd) => cmd.run(config, builder),
 			Subcommand::ImportBlocks(cmd) => cmd.run(config, builder),
 			Subcommand::CheckBlock(cmd) => cmd.run(config, builder),
 			Subcommand::PurgeChain(cmd) => cmd.run(config),
 			Subcommand::Revert(cmd) => cmd.run(config, builder),
+			Subcommand::BlocksPruningCheck(cmd) => cmd.run(config),
 		}
 	}
 }
@@ -947,8 +1247,14 @@ impl RunCmd {
 	{
 		assert!(config.chain_spec.is_some(), "chain_spec must be present before continuing");
 
+		let print_config = self.print_config;
 		crate::update_config_for_running_node(&mut config, self)?;
 
+		if print_config {
+			print!("{}", crate::describe_config(&config));
+			return Ok(());
+		}
+
 		crate::run_node(config, new_light, new_full, &version)
 	}
 }
@@ -994,6 +1300,63 @@ impl BuildSpecCmd {
 	}
 }
 
+impl ValidateSpecCmd {
+	/// Run the validate-spec command
+	pub fn run<G, E>(
+		self,
+		config: Configuration<G, E>,
+	) -> error::Result<()>
+	where
+		G: RuntimeGenesis,
+		E: ChainSpecExtension,
+	{
+		assert!(config.chain_spec.is_some(), "chain_spec must be present before continuing");
+
+		let spec = config.expect_chain_spec();
+		validate_chain_spec(spec)?;
+
+		println!("Chain specification: {} ({})", spec.name(), spec.id());
+		println!("  boot nodes: {}", spec.boot_nodes().len());
+		println!(
+			"  telemetry endpoints: {}",
+			spec.telemetry_endpoints().as_ref().map_or(0, |e| e.endpoints().len()),
+		);
+		println!("  genesis: ok");
+
+		Ok(())
+	}
+}
+
+/// Check that the fields of the given chain spec can be used by a node.
+///
+/// Boot nodes must be multiaddresses ending with a peer id, telemetry URLs must be either
+/// multiaddresses or `ws://`-style URLs and the genesis storage must build. Stops at the first
+/// problem found.
+pub fn validate_chain_spec<G, E>(spec: &ChainSpec<G, E>) -> error::Result<()>
+where
+	G: RuntimeGenesis,
+{
+	for addr in spec.boot_nodes() {
+		sc_network::config::parse_str_addr(addr).map_err(|e| error::Error::Input(
+			format!("Invalid boot node address '{}': {}", addr, e)
+		))?;
+	}
+
+	if let Some(endpoints) = spec.telemetry_endpoints() {
+		for (url, _) in endpoints.endpoints() {
+			if !is_valid_telemetry_url(url) {
+				return Err(error::Error::Input(format!("Invalid telemetry URL '{}'", url)));
+			}
+		}
+	}
+
+	spec.build_storage().map_err(|e| error::Error::Input(
+		format!("Invalid genesis: {}", e)
+	))?;
+
+	Ok(())
+}
+
 impl ExportBlocksCmd {
 	/// Run the export-blocks command
 	pub fn run<G, E, B, BC, BB>(
@@ -1200,3 +1563,187 @@ impl RevertCmd {
 		Ok(())
 	}
 }
+
+impl BlocksPruningCheckCmd {
+	/// Run the blocks-pruning-check command
+	pub fn run<G, E>(
+		self,
+		mut config: Configuration<G, E>,
+	) -> error::Result<()>
+	where
+		G: RuntimeGenesis,
+		E: ChainSpecExtension,
+	{
+		assert!(config.chain_spec.is_some(), "chain_spec must be present before continuing");
+
+		crate::fill_import_params(
+			&mut config,
+			&self.import_params,
+			sc_service::Roles::FULL,
+			self.shared_params.dev,
+		)?;
+
+		let path = match config.expect_database() {
+			DatabaseConfig::Path { path, .. } => path,
+			_ => return Err(error::Error::Input(
+				"Cannot check the pruning of a custom database implementation".to_string()
+			)),
+		};
+		let (best, finalized) = match sc_client_db::stored_block_numbers(path)? {
+			Some(numbers) => numbers,
+			None => {
+				println!("There are no blocks in the database at {}.", path.display());
+				return Ok(());
+			},
+		};
+
+		let report = crate::pruning_report(best, finalized, &config.pruning, config.keep_blocks);
+		println!("Best block: #{}, finalized block: #{}", best, finalized);
+		println!(
+			"Block bodies: {} retained, {} pruned",
+			report.blocks_retained,
+			report.blocks_pruned,
+		);
+		println!(
+			"Block states: {} retained, {} pruned",
+			report.states_retained,
+			report.states_pruned,
+		);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn spec_json(boot_nodes: &str, telemetry: &str, top: &str) -> String {
+		format!(
+			r#"{{
+				"name": "Test",
+				"id": "test",
+				"bootNodes": [{}],
+				"telemetryEndpoints": [{}],
+				"protocolId": null,
+				"properties": null,
+				"consensusEngine": null,
+				"genesis": {{ "raw": {{ "top": {{ {} }}, "children": {{}} }} }}
+			}}"#,
+			boot_nodes,
+			telemetry,
+			top,
+		)
+	}
+
+	fn validate(json: String) -> error::Result<()> {
+		let spec = ChainSpec::<(), Option<()>>::from_json_bytes(json.into_bytes())?;
+		validate_chain_spec(&spec)
+	}
+
+	const BOOT_NODE: &str =
+		r#""/ip4/127.0.0.1/tcp/30333/p2p/QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV""#;
+	const TELEMETRY: &str = r#"["wss://telemetry.polkadot.io/submit/", 0]"#;
+	const TOP: &str = r#""0x3a636f6465": "0x00""#;
+
+	#[test]
+	fn log_directives_are_combined_in_order() {
+		let params = SharedParams::from_iter(vec!["node-test"]);
+		assert_eq!(params.log_filters(), "");
+
+		let params = SharedParams::from_iter(vec![
+			"node-test", "-l", "info", "--log", "sync=debug", "-l", "sync=trace,afg=warn",
+		]);
+		assert_eq!(params.log_filters(), "info,sync=debug,sync=trace,afg=warn");
+	}
+
+	#[test]
+	fn detailed_log_output_is_off_by_default() {
+		let params = SharedParams::from_iter(vec!["node-test", "-l", "sync=debug"]);
+		assert_eq!(params.logger_config(), crate::LoggerConfig {
+			pattern: "sync=debug".into(),
+			detailed_output: false,
+		});
+
+		let params = SharedParams::from_iter(vec!["node-test", "--detailed-log-output"]);
+		assert!(params.logger_config().detailed_output);
+	}
+
+	#[test]
+	fn validate_spec_accepts_valid_spec() {
+		assert!(validate(spec_json(BOOT_NODE, TELEMETRY, TOP)).is_ok());
+		assert!(validate(spec_json("", "", "")).is_ok());
+	}
+
+	#[test]
+	fn validate_spec_rejects_malformed_specs() {
+		// not a chain spec at all
+		assert!(validate("{ \"name\": \"Test\" }".into()).is_err());
+
+		// boot node without a peer id
+		let err = validate(spec_json(r#""/ip4/127.0.0.1/tcp/30333""#, TELEMETRY, TOP)).unwrap_err();
+		assert!(err.to_string().contains("Invalid boot node address"));
+
+		// boot node that is not a multiaddress
+		let err = validate(spec_json(r#""127.0.0.1:30333""#, TELEMETRY, TOP)).unwrap_err();
+		assert!(err.to_string().contains("Invalid boot node address"));
+
+		// telemetry endpoint that is neither a multiaddress nor an URL
+		let err = validate(spec_json(BOOT_NODE, r#"["not a url", 0]"#, TOP)).unwrap_err();
+		assert!(err.to_string().contains("Invalid telemetry URL"));
+
+		// genesis storage that does not decode
+		let err = validate(spec_json(BOOT_NODE, TELEMETRY, r#""0xzz": "0x00""#)).unwrap_err();
+		assert!(err.to_string().contains("Invalid genesis"));
+	}
+
+	#[test]
+	fn validate_accepts_consistent_flags() {
+		for args in vec![
+			vec!["node-test"],
+			vec!["node-test", "--dev", "--chain", "dev"],
+			vec!["node-test", "--dev", "--base-path", "/tmp/dev"],
+			vec!["node-test", "--chain-spec-extension", "a=1", "--chain-spec-extension", "b=2"],
+		] {
+			assert!(SharedParams::from_iter(args.clone()).validate().is_ok(), "{:?}", args);
+		}
+	}
+
+	#[test]
+	fn validate_rejects_conflicting_flags() {
+		let err = SharedParams::from_iter(vec!["node-test", "--dev", "--chain", "local"])
+			.validate()
+			.unwrap_err();
+		assert!(err.to_string().contains("`--dev` conflicts with `--chain local`"), "{}", err);
+
+		let err = SharedParams::from_iter(vec![
+			"node-test", "--chain-spec-extension", "a=1", "--chain-spec-extension", "a=2",
+		]).validate().unwrap_err();
+		assert!(err.to_string().contains("overrides 'a' more than once"), "{}", err);
+
+		// All problems are reported at once.
+		let err = SharedParams::from_iter(vec![
+			"node-test", "--dev", "--chain", "local",
+			"--chain-spec-extension", "a=1", "--chain-spec-extension", "a=2",
+			"--chain-spec-extension", "a=3",
+		]).validate().unwrap_err();
+		assert!(err.to_string().contains("--chain local"), "{}", err);
+		assert_eq!(err.to_string().matches("more than once").count(), 1, "{}", err);
+	}
+
+	#[test]
+	fn validate_checks_the_import_flags() {
+		for args in vec![
+			vec!["node-test"],
+			vec!["node-test", "--pruning", "256"],
+			vec!["node-test", "--pruning", "archive", "--state-cache-size", "1024"],
+			vec!["node-test", "--pruning", "archive", "--unsafe-pruning"],
+		] {
+			assert!(ImportParams::from_iter(args.clone()).validate().is_ok(), "{:?}", args);
+		}
+
+		let err = ImportParams::from_iter(vec!["node-test", "--pruning", "all"])
+			.validate()
+			.unwrap_err();
+		assert!(err.to_string().contains("`--pruning all` is neither"), "{}", err);
+	}
+}
@@ -263,10 +263,23 @@ pub struct DatabaseSettings {
 	pub state_cache_child_ratio: Option<(usize, usize)>,
 	/// Pruning mode.
 	pub pruning: PruningMode,
+	/// Whether to enable pruning on a database which was run as an archive so far.
+	pub unsafe_pruning: bool,
+	/// How many block bodies are kept.
+	pub keep_blocks: KeepBlocks,
 	/// Where to find the database.
 	pub source: DatabaseSettingsSrc,
 }
 
+/// How many block bodies are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepBlocks {
+	/// Keep the bodies of all blocks.
+	All,
+	/// Keep the bodies of the given number of latest finalized blocks.
+	Some(u32),
+}
+
 /// Where to find the database..
 pub enum DatabaseSettingsSrc {
 	/// Load a database from a given path. Recommended for most uses.
@@ -281,6 +294,36 @@ pub enum DatabaseSettingsSrc {
 	Custom(Arc<dyn KeyValueDB>),
 }
 
+/// Returns the numbers of the best and the finalized block recorded in the database at `path`,
+/// or `None` if there is no database at `path` or it has no blocks yet.
+///
+/// The database is only read, unlike when opening a backend, which checks the pruning mode
+/// against the one recorded. A database is only opened if there is one at `path`, so none is
+/// created in its place.
+#[cfg(any(feature = "kvdb-rocksdb", test))]
+pub fn stored_block_numbers(path: &std::path::Path) -> ClientResult<Option<(u64, u64)>> {
+	// Every RocksDB database has a `CURRENT` file naming its manifest.
+	if !path.join("CURRENT").is_file() {
+		return Ok(None);
+	}
+
+	let path = path.to_str()
+		.ok_or_else(|| sp_blockchain::Error::Backend("Invalid database path".into()))?;
+	let db_config = kvdb_rocksdb::DatabaseConfig::with_columns(utils::NUM_COLUMNS);
+	let db = kvdb_rocksdb::Database::open(&db_config, path).map_err(utils::db_err)?;
+	let number = |key: &[u8]| -> ClientResult<Option<u64>> {
+		match db.get(columns::META, key).map_err(utils::db_err)? {
+			Some(lookup_key) => utils::lookup_key_to_number::<u32>(&lookup_key)
+				.map(|number| Some(number.into())),
+			None => Ok(None),
+		}
+	};
+	match number(meta_keys::BEST_BLOCK)? {
+		Some(best) => Ok(Some((best, number(meta_keys::FINALIZED_BLOCK)?.unwrap_or(0)))),
+		None => Ok(None),
+	}
+}
+
 /// Create an instance of db-backed client.
 pub fn new_client<E, S, Block, RA>(
 	settings: DatabaseSettings,
@@ -752,6 +795,7 @@ pub struct Backend<Block: BlockT> {
 	shared_cache: SharedCache<Block>,
 	import_lock: RwLock<()>,
 	is_archive: bool,
+	keep_blocks: KeepBlocks,
 	io_stats: FrozenForDuration<(kvdb::IoStats, StateUsageInfo)>,
 	state_usage: StateUsageStats,
 }
@@ -773,6 +817,8 @@ impl<Block: BlockT> Backend<Block> {
 			state_cache_size: 16777216,
 			state_cache_child_ratio: Some((50, 100)),
 			pruning: PruningMode::keep_blocks(keep_blocks),
+			unsafe_pruning: false,
+			keep_blocks: KeepBlocks::All,
 			source: DatabaseSettingsSrc::Custom(db),
 		};
 
@@ -790,6 +836,16 @@ impl<Block: BlockT> Backend<Block> {
 		let map_e = |e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from(
 			format!("State database error: {:?}", e)
 		);
+		let stored_pruning = sc_state_db::stored_pruning_mode(&StateMetaDb(&*db)).map_err(map_e)?;
+		if let Some(stored) = stored_pruning {
+			if prunes_archive(&stored, &config.pruning, config.unsafe_pruning)? {
+				// Record the new mode, which the state DB refuses to switch to otherwise.
+				let mut transaction = DBTransaction::new();
+				let change = sc_state_db::pruning_mode_change(&config.pruning);
+				apply_state_commit(&mut transaction, change);
+				db.write(transaction).map_err(db_err)?;
+			}
+		}
 		let state_db: StateDb<_, _> = StateDb::new(config.pruning.clone(), &StateMetaDb(&*db))
 			.map_err(map_e)?;
 		let storage_db = StorageDb {
@@ -824,6 +880,7 @@ impl<Block: BlockT> Backend<Block> {
 			),
 			import_lock: Default::default(),
 			is_archive: is_archive_pruning,
+			keep_blocks: config.keep_blocks,
 			io_stats: FrozenForDuration::new(std::time::Duration::from_secs(1)),
 			state_usage: StateUsageStats::new(),
 		})
@@ -1308,10 +1365,62 @@ impl<Block: BlockT> Backend<Block> {
 			&mut Some(ref mut displaced) => displaced.merge(new_displaced),
 		}
 
+		self.prune_block_body(transaction, f_num)
+	}
+
+	// write the removal of the body that falls out of the `keep_blocks` window when block
+	// `finalized` is finalized to a transaction. Blocks are finalized one after another, so
+	// this drops every body below the window.
+	fn prune_block_body(
+		&self,
+		transaction: &mut DBTransaction,
+		finalized: NumberFor<Block>,
+	) -> ClientResult<()> {
+		let keep_blocks = match self.keep_blocks {
+			KeepBlocks::All => return Ok(()),
+			// The latest finalized block is always kept.
+			KeepBlocks::Some(keep_blocks) => NumberFor::<Block>::from(keep_blocks.max(1)),
+		};
+		if finalized < keep_blocks {
+			return Ok(());
+		}
+
+		// Bodies kept before the limit was set are pruned down to the first one already pruned.
+		let canonical = |number| ::sc_client::blockchain::HeaderBackend::hash(&self.blockchain, number);
+		let mut number = finalized - keep_blocks;
+		while let Some(hash) = canonical(number)? {
+			let key = utils::number_and_hash_to_lookup_key(number, hash)?;
+			if self.storage.db.get(columns::BODY, &key).map_err(db_err)?.is_none() {
+				break;
+			}
+			trace!(target: "db", "Prune body of block #{} ({:?})", number, hash);
+			transaction.delete(columns::BODY, &key);
+			if number.is_zero() {
+				break;
+			}
+			number -= One::one();
+		}
+
 		Ok(())
 	}
 }
 
+/// Returns whether enabling `pruning` on a database which recorded the pruning mode `stored`
+/// prunes a former archive, which must be acknowledged by `unsafe_pruning`.
+fn prunes_archive(stored: &[u8], pruning: &PruningMode, unsafe_pruning: bool) -> ClientResult<bool> {
+	if pruning.is_archive() || !PruningMode::is_archive_id(stored) {
+		return Ok(false);
+	}
+	if !unsafe_pruning {
+		return Err(sp_blockchain::Error::Backend(
+			"The database was previously run as an archive (i.e. `--pruning archive`). Enabling \
+			pruning will irreversibly delete the state of old blocks, which can't be recovered \
+			without syncing again. You can ignore this check with `--unsafe-pruning`.".into()
+		));
+	}
+	Ok(true)
+}
+
 fn apply_state_commit(transaction: &mut DBTransaction, commit: sc_state_db::CommitSet<Vec<u8>>) {
 	for (key, val) in commit.data.inserted.into_iter() {
 		transaction.put(columns::STATE, &key[..], &val);
@@ -1759,6 +1868,8 @@ pub(crate) mod tests {
 			state_cache_size: 16777216,
 			state_cache_child_ratio: Some((50, 100)),
 			pruning: PruningMode::keep_blocks(1),
+			unsafe_pruning: false,
+			keep_blocks: KeepBlocks::All,
 			source: DatabaseSettingsSrc::Custom(backing),
 		}, 0).unwrap();
 		assert_eq!(backend.blockchain().info().best_number, 9);
@@ -2282,4 +2393,130 @@ pub(crate) mod tests {
 			backend.commit_operation(op).unwrap_err();
 		}
 	}
+
+	#[test]
+	fn block_bodies_below_the_window_are_pruned_on_finalization() {
+		let backend = Backend::<Block>::new(DatabaseSettings {
+			state_cache_size: 16777216,
+			state_cache_child_ratio: Some((50, 100)),
+			pruning: PruningMode::keep_blocks(2),
+			unsafe_pruning: false,
+			keep_blocks: KeepBlocks::Some(2),
+			source: DatabaseSettingsSrc::Custom(Arc::new(kvdb_memorydb::create(utils::NUM_COLUMNS))),
+		}, 0).unwrap();
+
+		let mut blocks = Vec::new();
+		let mut parent = Default::default();
+		for number in 0..5 {
+			parent = insert_header(&backend, number, parent, None, Default::default());
+			blocks.push(parent);
+		}
+		let body = |number: usize| backend.blockchain().body(BlockId::Hash(blocks[number])).unwrap();
+		assert!((0..5).all(|number| body(number).is_some()));
+
+		for number in 1..5 {
+			backend.finalize_block(BlockId::Number(number), None).unwrap();
+		}
+		for number in 0..3 {
+			assert_eq!(body(number), None, "body of block {}", number);
+			assert!(backend.blockchain().header(BlockId::Hash(blocks[number])).unwrap().is_some());
+		}
+		assert_eq!(body(3), Some(vec![]));
+		assert_eq!(body(4), Some(vec![]));
+	}
+
+	#[test]
+	fn block_bodies_kept_before_the_window_was_set_are_pruned() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		let path = db_dir.path().join("db");
+		let open = |keep_blocks| Backend::<Block>::new(DatabaseSettings {
+			state_cache_size: 16777216,
+			state_cache_child_ratio: Some((50, 100)),
+			pruning: PruningMode::keep_blocks(2),
+			unsafe_pruning: false,
+			keep_blocks,
+			source: DatabaseSettingsSrc::Path { path: path.clone(), cache_size: None },
+		}, 0).unwrap();
+
+		let mut blocks = Vec::new();
+		{
+			let backend = open(KeepBlocks::All);
+			let mut parent = Default::default();
+			for number in 0..6 {
+				parent = insert_header(&backend, number, parent, None, Default::default());
+				blocks.push(parent);
+			}
+			for number in 1..5 {
+				backend.finalize_block(BlockId::Number(number), None).unwrap();
+			}
+		}
+
+		let backend = open(KeepBlocks::Some(2));
+		let body = |number: usize| backend.blockchain().body(BlockId::Hash(blocks[number])).unwrap();
+		assert!((0..6).all(|number| body(number).is_some()));
+
+		backend.finalize_block(BlockId::Number(5), None).unwrap();
+		for number in 0..4 {
+			assert_eq!(body(number), None, "body of block {}", number);
+		}
+		assert_eq!(body(4), Some(vec![]));
+		assert_eq!(body(5), Some(vec![]));
+	}
+
+	#[test]
+	fn pruning_an_archive_database_requires_acknowledgement() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		let path = db_dir.path().join("db");
+		let open = |pruning, unsafe_pruning| Backend::<Block>::new(DatabaseSettings {
+			state_cache_size: 16777216,
+			state_cache_child_ratio: Some((50, 100)),
+			pruning,
+			unsafe_pruning,
+			keep_blocks: KeepBlocks::All,
+			source: DatabaseSettingsSrc::Path { path: path.clone(), cache_size: None },
+		}, 0);
+
+		{
+			let backend = open(PruningMode::ArchiveAll, false).unwrap();
+			insert_header(&backend, 0, Default::default(), None, Default::default());
+		}
+
+		let err = open(PruningMode::keep_blocks(256), false).err().unwrap();
+		assert!(err.to_string().contains("--unsafe-pruning"), "{}", err);
+		assert!(open(PruningMode::ArchiveAll, false).is_ok());
+
+		// Once acknowledged the database is pruned from then on, and no archive anymore.
+		assert!(open(PruningMode::keep_blocks(256), true).is_ok());
+		assert!(open(PruningMode::keep_blocks(256), false).is_ok());
+		assert!(open(PruningMode::ArchiveAll, false).is_err());
+	}
+
+	#[test]
+	fn stored_block_numbers_are_read_from_the_database() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		let path = db_dir.path().join("db");
+		assert_eq!(stored_block_numbers(&path).unwrap(), None);
+		// No database is created in a directory without one.
+		std::fs::create_dir(&path).unwrap();
+		assert_eq!(stored_block_numbers(&path).unwrap(), None);
+		assert_eq!(std::fs::read_dir(&path).unwrap().count(), 0);
+
+		{
+			let backend = Backend::<Block>::new(DatabaseSettings {
+				state_cache_size: 16777216,
+				state_cache_child_ratio: Some((50, 100)),
+				pruning: PruningMode::ArchiveAll,
+				unsafe_pruning: false,
+				keep_blocks: KeepBlocks::All,
+				source: DatabaseSettingsSrc::Path { path: path.clone(), cache_size: None },
+			}, 0).unwrap();
+			let mut parent = Default::default();
+			for number in 0..10 {
+				parent = insert_header(&backend, number, parent, None, Default::default());
+			}
+			backend.finalize_block(BlockId::Number(6), None).unwrap();
+		}
+
+		assert_eq!(stored_block_numbers(&path).unwrap(), Some((9, 6)));
+	}
 }
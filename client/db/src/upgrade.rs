@@ -147,7 +147,7 @@ fn version_file_path(path: &Path) -> PathBuf {
 #[cfg(test)]
 mod tests {
 	use sc_state_db::PruningMode;
-	use crate::{DatabaseSettings, DatabaseSettingsSrc};
+	use crate::{DatabaseSettings, DatabaseSettingsSrc, KeepBlocks};
 	use crate::tests::Block;
 	use super::*;
 
@@ -166,6 +166,8 @@ mod tests {
 			state_cache_size: 0,
 			state_cache_child_ratio: None,
 			pruning: PruningMode::ArchiveAll,
+			unsafe_pruning: false,
+			keep_blocks: KeepBlocks::All,
 			source: DatabaseSettingsSrc::Path { path: db_path.to_owned(), cache_size: None },
 		}, DatabaseType::Full).map(|_| ())
 	}
@@ -29,6 +29,31 @@ const MAX_PAYLOAD: usize = 15 * 1024 * 1024;
 /// Default maximum number of connections for WS RPC servers.
 const WS_MAX_CONNECTIONS: usize = 100;
 
+/// The methods that change the node, e.g. its keystore or its peers, or expose its internals.
+pub const UNSAFE_METHODS: &[&str] = &[
+	"author_insertKey",
+	"author_rotateKeys",
+	"author_removeExtrinsic",
+	"author_hasKey",
+	"author_hasSessionKeys",
+	"babe_epochAuthorship",
+	"offchain_localStorageGet",
+	"offchain_localStorageSet",
+	"system_addReservedPeer",
+	"system_removeReservedPeer",
+	"system_networkState",
+	"system_peers",
+];
+
+/// Whether an RPC handler leaves out the [`UNSAFE_METHODS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DenyUnsafe {
+	/// Leave out the unsafe methods, calling them fails as if they didn't exist.
+	Yes,
+	/// Expose every method.
+	No,
+}
+
 /// The RPC IoHandler containing all requested APIs.
 pub type RpcHandler<T> = pubsub::PubSubHandler<T>;
 
@@ -36,10 +61,22 @@ pub use self::inner::*;
 
 /// Construct rpc `IoHandler`
 pub fn rpc_handler<M: PubSubMetadata>(
-	extension: impl IoHandlerExtension<M>
+	extension: impl IoHandlerExtension<M>,
+	deny_unsafe: DenyUnsafe,
 ) -> RpcHandler<M> {
 	let mut io = pubsub::PubSubHandler::default();
-	extension.augment(&mut io);
+	match deny_unsafe {
+		DenyUnsafe::No => extension.augment(&mut io),
+		DenyUnsafe::Yes => {
+			let mut all = pubsub::PubSubHandler::default();
+			extension.augment(&mut all);
+			io.extend_with(all.iter()
+				.filter(|(name, _)| !UNSAFE_METHODS.contains(&name.as_str()))
+				.map(|(name, method)| (name.clone(), method.clone()))
+				.collect::<Vec<_>>()
+			);
+		},
+	}
 
 	// add an endpoint to list all available methods.
 	let mut methods = io.iter().map(|x| x.0.clone()).collect::<Vec<String>>();
@@ -133,3 +170,47 @@ mod inner {
 #[cfg(target_os = "unknown")]
 mod inner {
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::Arc;
+
+	#[derive(Clone, Default)]
+	struct Meta;
+
+	impl jsonrpc_core::Metadata for Meta {}
+
+	impl PubSubMetadata for Meta {
+		fn session(&self) -> Option<Arc<pubsub::Session>> {
+			None
+		}
+	}
+
+	fn handler(deny_unsafe: DenyUnsafe) -> RpcHandler<Meta> {
+		let mut io = jsonrpc_core::IoHandler::<Meta>::default();
+		io.add_method("system_name", |_| Ok(serde_json::json!("node")));
+		io.add_method("author_insertKey", |_| Ok(serde_json::Value::Null));
+		rpc_handler(io, deny_unsafe)
+	}
+
+	fn call(handler: &RpcHandler<Meta>, method: &str) -> serde_json::Value {
+		let request = format!(r#"{{"jsonrpc":"2.0","method":"{}","params":[],"id":1}}"#, method);
+		let response = handler.handle_request_sync(&request, Meta).unwrap();
+		serde_json::from_str(&response).unwrap()
+	}
+
+	#[test]
+	fn unsafe_methods_are_denied_on_request() {
+		let all = handler(DenyUnsafe::No);
+		assert_eq!(call(&all, "author_insertKey")["result"], serde_json::Value::Null);
+		assert!(call(&all, "author_insertKey")["error"].is_null());
+
+		let safe = handler(DenyUnsafe::Yes);
+		assert_eq!(call(&safe, "system_name")["result"], "node");
+		assert_eq!(call(&safe, "author_insertKey")["error"]["code"], -32601);
+
+		let methods = call(&safe, "rpc_methods")["result"]["methods"].clone();
+		assert_eq!(methods, serde_json::json!(["system_name"]));
+	}
+}
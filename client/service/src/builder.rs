@@ -17,7 +17,7 @@
 use crate::{Service, NetworkStatus, NetworkState, error::Error, DEFAULT_PROTOCOL_ID, MallocSizeOfWasm};
 use crate::{SpawnTaskHandle, start_rpc_servers, build_network_future, TransactionPoolAdapter};
 use crate::status_sinks;
-use crate::config::{Configuration, DatabaseConfig, KeystoreConfig};
+use crate::config::{Configuration, DatabaseConfig, KeystoreConfig, RpcMethods};
 use sc_client_api::{
 	self,
 	BlockchainEvents,
@@ -237,6 +237,8 @@ fn new_full_parts<TBl, TRtApi, TExecDisp, TGen, TCSExt>(
 			state_cache_child_ratio:
 			config.state_cache_child_ratio.map(|v| (v, 100)),
 			pruning: config.pruning.clone(),
+			unsafe_pruning: config.unsafe_pruning,
+			keep_blocks: config.keep_blocks,
 			source: match config.expect_database() {
 				DatabaseConfig::Path { path, cache_size } =>
 					sc_client_db::DatabaseSettingsSrc::Path {
@@ -350,6 +352,8 @@ where TGen: RuntimeGenesis, TCSExt: Extension {
 				state_cache_child_ratio:
 					config.state_cache_child_ratio.map(|v| (v, 100)),
 				pruning: config.pruning.clone(),
+				unsafe_pruning: config.unsafe_pruning,
+				keep_blocks: config.keep_blocks,
 				source: match config.expect_database() {
 					DatabaseConfig::Path { path, cache_size } =>
 						sc_client_db::DatabaseSettingsSrc::Path {
@@ -1180,7 +1184,7 @@ ServiceBuilder<
 
 		// RPC
 		let (system_rpc_tx, system_rpc_rx) = mpsc::unbounded();
-		let gen_handler = || {
+		let gen_handler = |deny_unsafe: sc_rpc_server::DenyUnsafe| {
 			use sc_rpc::{chain, state, author, system, offchain};
 
 			let system_info = sc_rpc::system::SystemInfo {
@@ -1237,7 +1241,7 @@ ServiceBuilder<
 						author::AuthorApi::to_delegate(author),
 						system::SystemApi::to_delegate(system),
 						rpc_extensions.clone(),
-					))
+					), deny_unsafe)
 				},
 				None => sc_rpc_server::rpc_handler((
 					state::StateApi::to_delegate(state),
@@ -1245,11 +1249,16 @@ ServiceBuilder<
 					author::AuthorApi::to_delegate(author),
 					system::SystemApi::to_delegate(system),
 					rpc_extensions.clone(),
-				))
+				), deny_unsafe)
 			}
 		};
-		let rpc_handlers = gen_handler();
-		let rpc = start_rpc_servers(&config, gen_handler)?;
+		// The in-memory RPC queries come from the node itself, so they may call anything.
+		let rpc_handlers = gen_handler(sc_rpc_server::DenyUnsafe::No);
+		let deny_unsafe = match config.rpc_methods {
+			RpcMethods::Safe => sc_rpc_server::DenyUnsafe::Yes,
+			RpcMethods::Unsafe => sc_rpc_server::DenyUnsafe::No,
+		};
+		let rpc = start_rpc_servers(&config, || gen_handler(deny_unsafe))?;
 
 
 		let _ = to_spawn_tx.unbounded_send((
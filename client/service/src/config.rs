@@ -17,7 +17,7 @@
 //! Service configuration.
 
 pub use sc_client::ExecutionStrategies;
-pub use sc_client_db::{kvdb::KeyValueDB, PruningMode};
+pub use sc_client_db::{kvdb::KeyValueDB, KeepBlocks, PruningMode};
 pub use sc_network::config::{ExtTransport, NetworkConfiguration, Roles};
 pub use sc_executor::WasmExecutionMethod;
 
@@ -47,6 +47,39 @@ pub struct VersionInfo {
 	pub support_url: &'static str,
 	/// Copyright starting year (x-current year)
 	pub copyright_start_year: i32,
+	/// Name and author the default base path is derived from, instead of
+	/// `executable_name` and `author`.
+	///
+	/// This lets a fork keep its data apart from the node it was forked from.
+	pub base_path_identity: Option<(&'static str, &'static str)>,
+	/// Names of the chain specs built into the executable, which `--chain` accepts besides
+	/// paths to chain spec files.
+	pub chains: &'static [&'static str],
+	/// Whether the executable includes a native build of the runtime.
+	pub native_runtime: bool,
+}
+
+impl Default for VersionInfo {
+	/// Empty names, no chains built in and a native runtime, as executables had before they
+	/// could say otherwise.
+	///
+	/// This lets an executable set only the fields it needs, e.g.
+	/// `VersionInfo { name: "node", ..Default::default() }`.
+	fn default() -> Self {
+		VersionInfo {
+			name: "",
+			version: "",
+			commit: "",
+			executable_name: "",
+			description: "",
+			author: "",
+			support_url: "",
+			copyright_start_year: 0,
+			base_path_identity: None,
+			chains: &[],
+			native_runtime: true,
+		}
+	}
 }
 
 /// Service configuration.
@@ -77,6 +110,10 @@ pub struct Configuration<G, E = NoExtension> {
 	pub state_cache_child_ratio: Option<usize>,
 	/// Pruning settings.
 	pub pruning: PruningMode,
+	/// Whether pruning may be enabled on a database which was run as an archive so far.
+	pub unsafe_pruning: bool,
+	/// How many block bodies to keep, independently of the state pruning.
+	pub keep_blocks: KeepBlocks,
 	/// Chain configuration.
 	pub chain_spec: Option<ChainSpec<G, E>>,
 	/// Node name.
@@ -85,6 +122,10 @@ pub struct Configuration<G, E = NoExtension> {
 	pub wasm_method: WasmExecutionMethod,
 	/// Execution strategies.
 	pub execution_strategies: ExecutionStrategies,
+	/// Whether the executable includes a native build of the runtime.
+	///
+	/// Without it, only execution strategies always using the Wasm runtime can be satisfied.
+	pub native_runtime: bool,
 	/// RPC over HTTP binding address. `None` if disabled.
 	pub rpc_http: Option<SocketAddr>,
 	/// RPC over Websockets binding address. `None` if disabled.
@@ -93,6 +134,8 @@ pub struct Configuration<G, E = NoExtension> {
 	pub rpc_ws_max_connections: Option<usize>,
 	/// CORS settings for HTTP & WS servers. `None` if all origins are allowed.
 	pub rpc_cors: Option<Vec<String>>,
+	/// The RPC methods exposed by the HTTP & WS servers.
+	pub rpc_methods: RpcMethods,
 	/// Prometheus exporter Port. `None` if disabled.
 	pub prometheus_port: Option<SocketAddr>,
 	/// Telemetry service URL. `None` if disabled.
@@ -150,6 +193,15 @@ impl KeystoreConfig {
 	}
 }
 
+/// The RPC methods exposed by the RPC servers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcMethods {
+	/// Only methods that are safe to call publicly, see [`sc_rpc_server::UNSAFE_METHODS`].
+	Safe,
+	/// All methods, including those that change the node, e.g. insert keys into its keystore.
+	Unsafe,
+}
+
 /// Configuration of the database of the client.
 #[derive(Clone)]
 pub enum DatabaseConfig {
@@ -184,12 +236,16 @@ impl<G, E> Default for Configuration<G, E> {
 			state_cache_size: Default::default(),
 			state_cache_child_ratio: Default::default(),
 			pruning: PruningMode::default(),
+			unsafe_pruning: false,
+			keep_blocks: KeepBlocks::All,
 			wasm_method: WasmExecutionMethod::Interpreted,
 			execution_strategies: Default::default(),
+			native_runtime: true,
 			rpc_http: None,
 			rpc_ws: None,
 			rpc_ws_max_connections: None,
 			rpc_cors: Some(vec![]),
+			rpc_methods: RpcMethods::Unsafe,
 			prometheus_port: None,
 			telemetry_endpoints: None,
 			telemetry_external_transport: None,
@@ -212,6 +268,7 @@ impl<G, E> Configuration<G, E> {
 		config.impl_name = version.name;
 		config.impl_version = version.version;
 		config.impl_commit = version.commit;
+		config.native_runtime = version.native_runtime;
 
 		config
 	}
@@ -191,14 +191,18 @@ fn node_config<G, E: Clone> (
 		state_cache_size: 16777216,
 		state_cache_child_ratio: None,
 		pruning: Default::default(),
+		unsafe_pruning: false,
+		keep_blocks: sc_service::config::KeepBlocks::All,
 		chain_spec: Some((*spec).clone()),
 		name: format!("Node {}", index),
 		wasm_method: sc_service::config::WasmExecutionMethod::Interpreted,
 		execution_strategies: Default::default(),
+		native_runtime: true,
 		rpc_http: None,
 		rpc_ws: None,
 		rpc_ws_max_connections: None,
 		rpc_cors: None,
+		rpc_methods: sc_service::config::RpcMethods::Unsafe,
 		prometheus_port: None,
 		telemetry_endpoints: None,
 		telemetry_external_transport: None,
@@ -1974,7 +1974,7 @@ pub(crate) mod tests {
 	use substrate_test_runtime_client::{
 		prelude::*,
 		client_ext::ClientExt,
-		sc_client_db::{Backend, DatabaseSettings, DatabaseSettingsSrc, PruningMode},
+		sc_client_db::{Backend, DatabaseSettings, DatabaseSettingsSrc, KeepBlocks, PruningMode},
 		runtime::{self, Block, Transfer, RuntimeApi, TestAPI},
 	};
 	use hex_literal::hex;
@@ -2989,6 +2989,8 @@ pub(crate) mod tests {
 				state_cache_size: 1 << 20,
 				state_cache_child_ratio: None,
 				pruning: PruningMode::ArchiveAll,
+				unsafe_pruning: false,
+				keep_blocks: KeepBlocks::All,
 				source: DatabaseSettingsSrc::Path {
 					path: tmp.path().into(),
 					cache_size: None,
@@ -3191,6 +3193,8 @@ pub(crate) mod tests {
 					state_cache_size: 1 << 20,
 					state_cache_child_ratio: None,
 					pruning: PruningMode::keep_blocks(1),
+					unsafe_pruning: false,
+					keep_blocks: KeepBlocks::All,
 					source: DatabaseSettingsSrc::Path {
 						path: tmp.path().into(),
 						cache_size: None,
@@ -175,6 +175,11 @@ impl PruningMode {
 			PruningMode::Constrained(_) => PRUNING_MODE_CONSTRAINED,
 		}
 	}
+
+	/// Is `id` the identifier of an archive (either ArchiveAll or ArchiveCanonical) pruning mode?
+	pub fn is_archive_id(id: &[u8]) -> bool {
+		id == PRUNING_MODE_ARCHIVE || id == PRUNING_MODE_ARCHIVE_CANON
+	}
 }
 
 impl Default for PruningMode {
@@ -183,6 +188,20 @@ impl Default for PruningMode {
 	}
 }
 
+/// Returns the identifier of the pruning mode recorded in `db`, see [`PruningMode::id`], or
+/// `None` if no block was inserted yet.
+pub fn stored_pruning_mode<D: MetaDb>(db: &D) -> Result<Option<Vec<u8>>, Error<D::Error>> {
+	db.get_meta(&to_meta_key(PRUNING_MODE, &())).map_err(Error::Db)
+}
+
+/// Returns the changes recording `mode` as the pruning mode of the database, in place of the one
+/// recorded so far.
+pub fn pruning_mode_change<Key: Hash>(mode: &PruningMode) -> CommitSet<Key> {
+	let mut commit = CommitSet::default();
+	commit.meta.inserted.push((to_meta_key(PRUNING_MODE, &()), mode.id().into()));
+	commit
+}
+
 fn to_meta_key<S: Codec>(suffix: &[u8], data: &S) -> Vec<u8> {
 	let mut buffer = data.encode();
 	buffer.extend(suffix);
@@ -222,7 +241,7 @@ impl<BlockHash: Hash, Key: Hash> StateDbSync<BlockHash, Key> {
 	}
 
 	fn check_meta<D: MetaDb>(mode: &PruningMode, db: &D) -> Result<(), Error<D::Error>> {
-		let db_mode = db.get_meta(&to_meta_key(PRUNING_MODE, &())).map_err(Error::Db)?;
+		let db_mode = stored_pruning_mode(db)?;
 		trace!(target: "state-db",
 			"DB pruning mode: {:?}",
 			db_mode.as_ref().map(|v| std::str::from_utf8(&v))
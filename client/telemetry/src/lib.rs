@@ -102,6 +102,11 @@ impl TelemetryEndpoints {
 	pub fn new(endpoints: Vec<(String, u8)>) -> Self {
 		TelemetryEndpoints(endpoints)
 	}
+
+	/// Returns the URLs and their maximum verbosity level.
+	pub fn endpoints(&self) -> &[(String, u8)] {
+		&self.0
+	}
 }
 
 /// Log levels.
@@ -0,0 +1,97 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A pool handing out distinct accounts to the instancers of a benchmark.
+
+use sp_std::prelude::*;
+use codec::{Encode, Decode};
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+/// The most accounts derived in a row that were already handed out, before giving up.
+const MAX_ATTEMPTS: u32 = 1_000;
+
+/// Hands out accounts which are distinct from every account handed out by the pool before.
+///
+/// Instancers deriving accounts from their own name and component value can accidentally pick
+/// the same account, e.g. a sender and a recipient indexed by two components of equal value.
+/// Drawing all accounts of an instance from one pool rules that out.
+pub struct AccountPool<AccountId> {
+	seed: &'static str,
+	next_index: u32,
+	issued: Vec<AccountId>,
+}
+
+impl<AccountId: Decode + PartialEq + Clone> AccountPool<AccountId> {
+	/// Create a pool deriving its accounts from `seed`.
+	///
+	/// Pools with the same seed hand out the same accounts in the same order.
+	pub fn new(seed: &'static str) -> Self {
+		AccountPool { seed, next_index: 0, issued: Vec::new() }
+	}
+
+	/// Returns an account which wasn't handed out by this pool before.
+	///
+	/// Returns an error if `AccountId` can't be decoded from a hash, or if the pool seems to
+	/// have run out of distinct accounts.
+	pub fn account(&mut self) -> Result<AccountId, &'static str> {
+		for _ in 0..MAX_ATTEMPTS {
+			let entropy = BlakeTwo256::hash_of(&(self.seed, self.next_index));
+			self.next_index += 1;
+			let account = AccountId::decode(&mut &entropy.encode()[..])
+				.map_err(|_| "Account can't be decoded from a hash")?;
+			if !self.issued.contains(&account) {
+				self.issued.push(account.clone());
+				return Ok(account);
+			}
+		}
+		Err("Account pool ran out of distinct accounts")
+	}
+
+	/// The accounts handed out so far, in the order they were.
+	pub fn issued(&self) -> &[AccountId] {
+		&self.issued
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn accounts_are_distinct() {
+		let mut pool = AccountPool::<u64>::new("transfer");
+		let accounts: Vec<u64> = (0..1_000).map(|_| pool.account().unwrap()).collect();
+		let mut distinct = accounts.clone();
+		distinct.sort();
+		distinct.dedup();
+		assert_eq!(distinct.len(), accounts.len());
+		assert_eq!(pool.issued(), &accounts[..]);
+
+		let mut again = AccountPool::<u64>::new("transfer");
+		assert_eq!(again.account(), Ok(accounts[0]));
+	}
+
+	#[test]
+	fn collisions_are_skipped() {
+		// Only 256 accounts exist, so deriving 200 of them collides many times.
+		let mut pool = AccountPool::<u8>::new("small");
+		let accounts: Vec<u8> = (0..200).map(|_| pool.account().unwrap()).collect();
+		let mut distinct = accounts.clone();
+		distinct.sort();
+		distinct.dedup();
+		assert_eq!(distinct.len(), 200);
+	}
+}
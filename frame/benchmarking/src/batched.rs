@@ -0,0 +1,157 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarking calls batched into one dispatch.
+
+use sp_std::prelude::*;
+use crate::{BenchmarkParameter, Wrap, Wrapped};
+
+/// Wraps the instances of a bare setup into batches, as many as the component `size` says.
+pub struct Batching<Batch> {
+	size: BenchmarkParameter,
+	batch: Batch,
+}
+
+impl<Call, RawOrigin, BatchCall, BatchOrigin, Batch> Wrap<Call, RawOrigin, BatchCall, BatchOrigin>
+	for Batching<Batch>
+where
+	Batch: Fn(Vec<Call>, RawOrigin, &[(BenchmarkParameter, u32)])
+		-> Result<(BatchCall, BatchOrigin), &'static str>,
+{
+	/// A batch of just the call, as every prerequisite is dispatched.
+	fn wrap(&self, call: Call, caller: RawOrigin, components: &[(BenchmarkParameter, u32)])
+		-> Result<(BatchCall, BatchOrigin), &'static str>
+	{
+		(self.batch)(vec![call], caller, components)
+	}
+
+	fn wrap_instance(
+		&self,
+		instance: &mut dyn FnMut() -> Result<(Call, RawOrigin), &'static str>,
+		components: &[(BenchmarkParameter, u32)],
+	) -> Result<(BatchCall, BatchOrigin), &'static str> {
+		let size = components.iter()
+			.find(|(param, _)| *param == self.size)
+			.map(|(_, size)| *size)
+			.ok_or("The batch size is not a component of the benchmark")?;
+
+		let (first, caller) = instance()?;
+		let mut calls = Vec::with_capacity(size as usize);
+		if size > 0 {
+			calls.push(first);
+		}
+		for _ in 1..size {
+			calls.push(instance()?.0);
+		}
+		(self.batch)(calls, caller, components)
+	}
+}
+
+/// Benchmarks a batch of the calls of the bare setup `Setup`, as many as the component `size`
+/// says, which `Batch` turns into a single dispatch, e.g. a call of a utility pallet.
+///
+/// Every call of the batch is prepared by `Setup` for the same components and origin, and the
+/// batch is dispatched by the origin of the first. With `size` at zero one instance is still
+/// prepared for its origin, and the batch is empty. Everything but the instances is the same as
+/// for `Setup`.
+pub type Batched<Setup, Batch, Call, RawOrigin> = Wrapped<Setup, Batching<Batch>, Call, RawOrigin>;
+
+impl<Setup, Batch, Call, RawOrigin> Batched<Setup, Batch, Call, RawOrigin> {
+	/// Benchmark batches of `size` calls of `setup`, dispatched by `batch`.
+	pub fn new(setup: Setup, size: BenchmarkParameter, batch: Batch) -> Self {
+		Wrapped::with(setup, Batching { size, batch })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::{DispatchError, DispatchResult, traits::Dispatchable};
+	use std::sync::atomic::{AtomicU32, Ordering};
+	use crate::{BenchmarkingSetup, BenchmarkParameter::*};
+
+	// The number of instances prepared, and of remarks dispatched.
+	static PREPARED: AtomicU32 = AtomicU32::new(0);
+	static REMARKS: AtomicU32 = AtomicU32::new(0);
+
+	struct Remark;
+	impl Dispatchable for Remark {
+		type Origin = Option<u64>;
+		type Trait = ();
+		fn dispatch(self, origin: Self::Origin) -> DispatchResult {
+			origin.ok_or(DispatchError::BadOrigin)?;
+			REMARKS.fetch_add(1, Ordering::SeqCst);
+			Ok(())
+		}
+	}
+
+	// Stands in for a utility pallet dispatching every call of the batch.
+	struct Batch { calls: Vec<Remark> }
+	impl Dispatchable for Batch {
+		type Origin = Option<u64>;
+		type Trait = ();
+		fn dispatch(self, origin: Self::Origin) -> DispatchResult {
+			self.calls.into_iter().try_for_each(|call| call.dispatch(origin))
+		}
+	}
+
+	struct RemarkSetup;
+	impl BenchmarkingSetup<(), Remark, Option<u64>> for RemarkSetup {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			vec![(N, 0, 10)]
+		}
+
+		fn instance(&self, _: &[(BenchmarkParameter, u32)])
+			-> Result<(Remark, Option<u64>), &'static str>
+		{
+			PREPARED.fetch_add(1, Ordering::SeqCst);
+			Ok((Remark, Some(1)))
+		}
+	}
+
+	#[test]
+	fn batches_are_as_large_as_their_component() {
+		type BatchedSetup = dyn BenchmarkingSetup<(), Batch, Option<u64>>;
+
+		let batched = Batched::<_, _, Remark, Option<u64>>::new(
+			RemarkSetup,
+			N,
+			|calls, caller, _: &[(BenchmarkParameter, u32)]|
+				-> Result<(Batch, Option<u64>), &'static str>
+			{
+				Ok((Batch { calls }, caller))
+			},
+		);
+		let batched: &BatchedSetup = &batched;
+		assert_eq!(batched.components(), RemarkSetup.components());
+
+		// Returns the number of instances prepared and remarks dispatched for a batch of `n`.
+		let counts = || (PREPARED.load(Ordering::SeqCst), REMARKS.load(Ordering::SeqCst));
+		let dispatch = |n| {
+			let (prepared, remarks) = counts();
+			let (batch, caller) = batched.instance(&[(N, n)]).unwrap();
+			assert_eq!(caller, Some(1));
+			batch.dispatch(caller).unwrap();
+			let (now_prepared, now_remarks) = counts();
+			(now_prepared - prepared, now_remarks - remarks)
+		};
+
+		assert_eq!(dispatch(0), (1, 0));
+		assert_eq!(dispatch(1), (1, 1));
+		assert_eq!(dispatch(4), (4, 4));
+		assert!(batched.instance(&[(A, 4)]).is_err());
+	}
+}
@@ -0,0 +1,53 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Criterion harnesses driving benchmarks natively, for iterating on them locally.
+
+use criterion::{BatchSize, Criterion};
+use sp_runtime::traits::Dispatchable;
+use crate::{BenchmarkingSetup, snake_case};
+
+/// Register a Criterion group for the benchmark `name`, measuring the dispatch of the call
+/// `setup` prepares for its worst case assignment.
+///
+/// Every iteration is set up and dispatched inside `execute_with`, which should provide the
+/// externalities the setup and the call need. Returns the name of the group, the snake case
+/// form of `name`.
+pub fn criterion_group<T, Call, RawOrigin>(
+	c: &mut Criterion,
+	name: &str,
+	setup: &dyn BenchmarkingSetup<T, Call, RawOrigin>,
+	execute_with: &mut dyn FnMut(&mut dyn FnMut()),
+) -> String
+where
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	let group_name = String::from_utf8(snake_case(name))
+		.expect("the snake case form of an identifier is valid UTF-8; qed");
+	let worst_case = setup.worst_case_assignment()
+		.expect("benchmarks driven by Criterion must have a worst case assignment");
+
+	let mut group = c.benchmark_group(group_name.as_str());
+	group.bench_function("worst_case", |b| execute_with(&mut || b.iter_batched(
+		|| setup.instance(&worst_case).expect("the worst case assignment can be set up"),
+		|(call, caller)| call.dispatch(caller.into()),
+		BatchSize::PerIteration,
+	)));
+	group.finish();
+
+	group_name
+}
@@ -18,13 +18,105 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use sp_std::vec::Vec;
+mod weighing;
+mod wrapped;
+mod signed;
+mod proxied;
+mod batched;
+mod account_pool;
+mod signatures;
+mod weight_builder;
+mod rng;
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "criterion")]
+mod harness;
+#[cfg(feature = "mock-host")]
+pub mod mock;
+
+use frame_support::weights::Weight;
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "std")]
+use sp_externalities::ExternalitiesExt;
+#[cfg(feature = "std")]
+use codec::Decode;
+
+#[cfg(feature = "std")]
+pub use snapshot::{read_snapshot, snapshot_path, write_snapshot, Snapshot, SnapshotDir};
+#[cfg(feature = "criterion")]
+pub use harness::criterion_group;
+#[cfg(feature = "criterion")]
+#[doc(hidden)]
+pub use criterion;
+#[cfg(feature = "criterion")]
+#[doc(hidden)]
+pub use sp_runtime::traits::Dispatchable;
+
+#[doc(hidden)]
+pub use frame_support::traits::GetCallName;
+#[doc(hidden)]
+pub use sp_std::vec::Vec;
+
+pub use wrapped::{Wrap, Wrapped};
+pub use signed::{Signed, SignedCall};
+pub use proxied::Proxied;
+pub use batched::Batched;
+pub use account_pool::AccountPool;
+pub use signatures::{generate_signatures, signatures_for, SignatureScheme, BENCHMARK_KEY_TYPE};
+pub use weight_builder::WeightBuilder;
+pub use rng::{rng, BenchmarkRng};
+pub use weighing::{
+	diagonal_assignments, estimate_duration, fill_storage_map, fill_storage_map_committing, fix_components,
+	instance_or_skip, merge_results, origin_assignments, run_all_benchmarks, run_benchmark,
+	run_benchmark_checked, run_benchmark_streaming, run_benchmark_timed, run_benchmark_with_stats,
+	summarize_results, sweep_assignments, sweep_dependent_assignments, sweep_points_assignments,
+	sweep_rest_assignments, timed_parameters, unreachable_highs, unused_components, worker_range,
+	worst_case_assignment,
+};
 
 /// An alphabet of possible parameters to use for benchmarking.
-#[derive(codec::Encode, codec::Decode, Clone, Copy, PartialEq, Debug)]
+#[derive(codec::Encode, codec::Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[allow(missing_docs)]
 pub enum BenchmarkParameter {
 	A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+	/// Pseudo-component tagging results with the index of the origin they were dispatched with,
+	/// for benchmarks declaring more than one origin.
+	Origin,
+	/// Pseudo-component tagging results measured with the storage changes of the dispatch
+	/// dropped, for benchmarks asking for [`BenchmarkingSetup::read_only_timing`].
+	ReadOnly,
+	/// Pseudo-component tagging results with whether they were measured with a cold (`0`) or a
+	/// warm (`1`) DB cache, for benchmarks asking for [`BenchmarkingSetup::cache_timing`].
+	Cache,
+}
+
+impl BenchmarkParameter {
+	/// Returns whether this is a pseudo-component tagging results, rather than a component the
+	/// cost of the dispatch scales with.
+	pub fn is_pseudo(&self) -> bool {
+		match self {
+			BenchmarkParameter::Origin | BenchmarkParameter::ReadOnly | BenchmarkParameter::Cache
+				=> true,
+			_ => false,
+		}
+	}
+}
+
+/// A bound of the range of a component.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ComponentBound {
+	/// A constant value.
+	Value(u32),
+	/// The current value of a component declared before the one this bound belongs to.
+	Component(BenchmarkParameter),
+}
+
+impl From<u32> for ComponentBound {
+	fn from(value: u32) -> Self {
+		ComponentBound::Value(value)
+	}
 }
 
 /// Results from running benchmarks on a FRAME pallet.
@@ -32,19 +124,238 @@ pub enum BenchmarkParameter {
 /// used for that benchmark result.
 pub type BenchmarkResults = (Vec<(BenchmarkParameter, u32)>, u128);
 
+/// Conversions of the time measured by a benchmark result.
+pub trait Elapsed {
+	/// The measured time in nanoseconds.
+	fn as_nanos(&self) -> u128;
+
+	/// The measured time in whole microseconds, rounded down.
+	fn as_micros(&self) -> u128 {
+		self.as_nanos() / 1_000
+	}
+
+	/// The weight of the measured time, at `per_ns` units of weight per nanosecond.
+	///
+	/// This assumes the weight of a dispatch is proportional to its execution time on the
+	/// benchmarking machine. The result saturates at the largest representable `Weight`.
+	fn to_weight(&self, per_ns: u64) -> Weight {
+		let weight = self.as_nanos().saturating_mul(per_ns as u128);
+		if weight > Weight::max_value() as u128 {
+			Weight::max_value()
+		} else {
+			weight as Weight
+		}
+	}
+}
+
+impl Elapsed for BenchmarkResults {
+	fn as_nanos(&self) -> u128 {
+		self.1
+	}
+}
+
+/// Summary of the results measured for one assignment of the components.
+#[derive(codec::Encode, codec::Decode, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ComponentStats {
+	/// The assignment of the components.
+	pub components: Vec<(BenchmarkParameter, u32)>,
+	/// The number of results measured for the assignment.
+	pub samples: u32,
+	/// The fastest time measured, in nanoseconds.
+	pub min: u128,
+	/// The slowest time measured, in nanoseconds.
+	pub max: u128,
+	/// The mean of the times measured, in nanoseconds, rounded down.
+	pub mean: u128,
+}
+
+/// A request to run a benchmark of a pallet.
+#[derive(codec::Encode, codec::Decode, Clone, PartialEq, Debug, Default)]
+pub struct BenchmarkRequest {
+	/// The name of the benchmark, e.g. `transfer`.
+	pub name: Vec<u8>,
+	/// The number of sample points to take across the range of every component.
+	pub steps: u32,
+	/// The number of times every sample point is measured.
+	pub repeat: u32,
+	/// Seed for any randomness used while setting up the benchmark.
+	///
+	/// Instancers draw their randomness from [`rng`], which is derived from it.
+	pub seed: u32,
+	/// Components pinned to a value instead of being swept.
+	pub fixed: Vec<(BenchmarkParameter, u32)>,
+	/// Components sampled at the given values instead of being stepped through their range.
+	pub points: Vec<(BenchmarkParameter, Vec<u32>)>,
+	/// The index of the worker running this part of the sweep.
+	pub worker: u32,
+	/// The number of workers the sweep is split across. `0` is treated as `1`.
+	pub workers: u32,
+	/// The longest a single iteration may take, in nanoseconds.
+	pub timeout: Option<u64>,
+	/// What to do with an iteration taking longer than `timeout`.
+	pub on_timeout: OnTimeout,
+	/// The path of a state snapshot file, as written by `write_snapshot`, to load before the sweep.
+	///
+	/// The iterations are then measured against this state and a warm cache, instead of
+	/// wiping the DB back to the genesis state after each.
+	pub warm_snapshot: Option<Vec<u8>>,
+	/// Where in their range the components not being swept sit while another one is.
+	pub rest: RestPolicy,
+	/// Give the host a chance to settle between two iterations, see
+	/// [`benchmarking::settle`].
+	pub settle: bool,
+	/// Measure every assignment until the mean of its times is stable, instead of exactly
+	/// `repeat` times, with `repeat` as the least number of times.
+	///
+	/// The number of times an assignment took is the number of its samples, see
+	/// [`summarize_results`].
+	pub adaptive: Option<AdaptiveRepeat>,
+}
+
+/// When to stop measuring an assignment whose iterations are repeated adaptively.
+#[derive(codec::Encode, codec::Decode, Clone, Copy, PartialEq, Debug)]
+pub struct AdaptiveRepeat {
+	/// The relative standard error of the mean of the times to stop at, in parts per thousand.
+	pub max_error_permille: u32,
+	/// The most times an assignment is measured, even if its mean is not stable by then.
+	pub max_repeat: u32,
+}
+
+/// Where in their range the components not being swept by a benchmark sit.
+#[derive(codec::Encode, codec::Decode, Clone, Copy, PartialEq, Debug)]
+pub enum RestPolicy {
+	/// The middle of the range.
+	Midpoint,
+	/// The low end of the range.
+	Low,
+	/// The high end of the range, usually the worst case.
+	High,
+}
+
+impl Default for RestPolicy {
+	fn default() -> Self {
+		RestPolicy::Midpoint
+	}
+}
+
+/// What to do with an iteration of a benchmark taking longer than its timeout.
+#[derive(codec::Encode, codec::Decode, Clone, Copy, PartialEq, Debug)]
+pub enum OnTimeout {
+	/// Leave the iteration and the remaining repetitions of its assignment out of the results,
+	/// and carry on with the next assignment.
+	Skip,
+	/// Abort the benchmark with an error.
+	Abort,
+}
+
+impl Default for OnTimeout {
+	fn default() -> Self {
+		OnTimeout::Skip
+	}
+}
+
+/// The results of running a benchmark of a pallet.
+#[derive(codec::Encode, codec::Decode, Clone, PartialEq, Debug)]
+pub struct BenchmarkBatch {
+	/// The pallet the benchmark belongs to.
+	pub pallet: Vec<u8>,
+	/// The name of the benchmark.
+	pub benchmark: Vec<u8>,
+	/// The results of the benchmark.
+	pub results: Vec<BenchmarkResults>,
+}
+
+/// The results of every benchmark run of a pallet.
+#[derive(codec::Encode, codec::Decode, Clone, PartialEq, Debug)]
+pub struct PalletResults {
+	/// The name of the pallet.
+	pub pallet: Vec<u8>,
+	/// The results of every benchmark, along with the name of the benchmark.
+	pub benchmarks: Vec<(Vec<u8>, Vec<BenchmarkResults>)>,
+}
+
+/// Group the results of `batches` by pallet, and within every pallet by benchmark.
+///
+/// Pallets are ordered by name, and so are the benchmarks of every pallet. The results of
+/// batches of the same benchmark are concatenated in the order of the batches.
+pub fn group_by_pallet(batches: Vec<BenchmarkBatch>) -> Vec<PalletResults> {
+	let mut pallets: Vec<PalletResults> = Vec::new();
+	for batch in batches {
+		let index = match pallets.binary_search_by(|p| p.pallet.cmp(&batch.pallet)) {
+			Ok(index) => index,
+			Err(index) => {
+				pallets.insert(index, PalletResults { pallet: batch.pallet, benchmarks: Vec::new() });
+				index
+			},
+		};
+		let benchmarks = &mut pallets[index].benchmarks;
+		match benchmarks.binary_search_by(|b| b.0.cmp(&batch.benchmark)) {
+			Ok(index) => benchmarks[index].1.extend(batch.results),
+			Err(index) => benchmarks.insert(index, (batch.benchmark, batch.results)),
+		}
+	}
+	pallets
+}
+
+/// Describes a benchmark of a pallet.
+#[derive(codec::Encode, codec::Decode, Clone, PartialEq, Debug)]
+pub struct BenchmarkMetadata {
+	/// The name of the benchmark.
+	pub name: Vec<u8>,
+	/// The components of the benchmark and their ranges.
+	pub components: Vec<(BenchmarkParameter, u32, u32)>,
+	/// What the components count, for those the benchmark describes.
+	pub units: Vec<(BenchmarkParameter, Vec<u8>)>,
+}
+
+/// A benchmark of a runtime, as listed in its manifest.
+#[derive(codec::Encode, codec::Decode, Clone, PartialEq, Debug)]
+pub struct BenchmarkMeta {
+	/// The pallet the benchmark belongs to.
+	pub pallet: Vec<u8>,
+	/// The name of the benchmark.
+	pub name: Vec<u8>,
+	/// The components of the benchmark and their ranges.
+	pub components: Vec<(BenchmarkParameter, u32, u32)>,
+	/// Extra information about the components, currently what they count.
+	pub extra: Vec<(BenchmarkParameter, Vec<u8>)>,
+}
+
+/// Flatten the benchmarks of every pallet, along with the name of the pallet, into a manifest
+/// listing every benchmark of the runtime.
+pub fn benchmark_manifest(pallets: Vec<(Vec<u8>, Vec<BenchmarkMetadata>)>) -> Vec<BenchmarkMeta> {
+	pallets.into_iter().flat_map(|(pallet, benchmarks)| {
+		benchmarks.into_iter().map(move |benchmark| BenchmarkMeta {
+			pallet: pallet.clone(),
+			name: benchmark.name,
+			components: benchmark.components,
+			extra: benchmark.units,
+		})
+	}).collect()
+}
+
 sp_api::decl_runtime_apis! {
 	/// Runtime api for benchmarking a FRAME runtime.
 	pub trait Benchmark {
-		/// Dispatch the given benchmark.
-		fn dispatch_benchmark(
-			module: Vec<u8>,
-			extrinsic: Vec<u8>,
-			steps: u32,
-			repeat: u32,
-		) -> Option<Vec<BenchmarkResults>>;
+		/// Dispatch the benchmark `request` of the pallet `module`.
+		fn dispatch_benchmark(module: Vec<u8>, request: BenchmarkRequest) -> Option<BenchmarkBatch>;
+
+		/// List the benchmarks of every pallet, along with the name of the pallet.
+		fn list_benchmarks() -> Vec<(Vec<u8>, Vec<BenchmarkMetadata>)>;
+
+		/// List every benchmark of the runtime, see [`benchmark_manifest`].
+		fn benchmark_manifest() -> Vec<BenchmarkMeta>;
 	}
 }
 
+/// The host functions called by the runners, replaced by the [`mock`] with the `mock-host`
+/// feature.
+#[cfg(not(feature = "mock-host"))]
+use benchmarking as host;
+#[cfg(feature = "mock-host")]
+use mock as host;
+
 /// Interface that provides functions for benchmarking the runtime.
 #[sp_runtime_interface::runtime_interface]
 pub trait Benchmarking {
@@ -67,6 +378,71 @@ pub trait Benchmarking {
 	fn commit_db(&mut self) {
 		self.commit()
 	}
+
+	/// Drop all storage changes while `read_only` is set, instead of applying them.
+	fn set_read_only_db(&mut self, read_only: bool) {
+		self.set_read_only(read_only)
+	}
+
+	/// Let background work of the host, e.g. of the allocator, run before the next iteration.
+	///
+	/// The runners call this between the iterations of a benchmark asking to settle, so that
+	/// work doesn't bleed into the time of the next one.
+	fn settle() {
+		std::thread::yield_now()
+	}
+
+	/// Load the snapshot file at `path` into the storage, returning whether it could be read.
+	fn load_snapshot(&mut self, path: &[u8]) -> bool {
+		let path = std::path::Path::new(std::str::from_utf8(path).unwrap_or_default());
+		match read_snapshot(path) {
+			Ok(snapshot) => {
+				for (key, value) in snapshot {
+					self.set_storage(key, value);
+				}
+				true
+			},
+			Err(e) => {
+				frame_support::debug::native::error!(
+					"Failed to read benchmark snapshot {}: {}",
+					path.display(),
+					e,
+				);
+				false
+			},
+		}
+	}
+
+	/// Write the current storage to a snapshot file, if a [`SnapshotDir`] is registered.
+	///
+	/// `components` is the encoded component assignment the dispatch of benchmark `name`
+	/// failed for.
+	fn snapshot_db(&mut self, name: &[u8], components: &[u8]) {
+		let dir = match self.extension::<SnapshotDir>() {
+			Some(dir) => dir.0.clone(),
+			None => return,
+		};
+
+		let mut snapshot = Snapshot::new();
+		let mut key = Vec::new();
+		while let Some(next) = self.next_storage_key(&key) {
+			if let Some(value) = self.storage(&next) {
+				snapshot.push((next.clone(), value));
+			}
+			key = next;
+		}
+
+		let components = <Vec<(BenchmarkParameter, u32)>>::decode(&mut &components[..])
+			.unwrap_or_default();
+		let path = snapshot_path(&dir, name, &components);
+		if let Err(e) = write_snapshot(&path, &snapshot) {
+			frame_support::debug::native::warn!(
+				"Failed to write benchmark snapshot to {}: {}",
+				path.display(),
+				e,
+			);
+		}
+	}
 }
 
 /// The pallet benchmarking trait.
@@ -74,24 +450,330 @@ pub trait Benchmarking<T> {
 	/// Run the benchmarks for this pallet.
 	///
 	/// Parameters
-	/// - `extrinsic`: The name of extrinsic function you want to benchmark encoded as bytes.
-	/// - `steps`: The number of sample points you want to take across the range of parameters.
-	/// - `repeat`: The number of times you want to repeat a benchmark.
-	fn run_benchmark(extrinsic: Vec<u8>, steps: u32, repeat: u32) -> Result<Vec<T>, &'static str>;
+	/// - `request`: The benchmark to run, and how to sweep its components.
+	fn run_benchmark(request: &BenchmarkRequest) -> Result<Vec<T>, &'static str>;
+
+	/// List the benchmarks of this pallet.
+	fn benchmarks() -> Vec<BenchmarkMetadata>;
 }
 
+/// Error returned by [`BenchmarkingSetup::instance`] to skip a sample instead of failing the
+/// whole benchmark, e.g. when a component value can't be reached.
+pub const SKIP: &str = "Skip this benchmark sample";
+
+/// Error returned by the benchmark runners when built without the `runtime-benchmarks` feature,
+/// which promises that the executor provides the benchmarking host functions.
+pub const HOST_FUNCTIONS_UNAVAILABLE: &str =
+	"benchmarking host functions not available; build with --features runtime-benchmarks";
+
 /// The required setup for creating a benchmark.
 pub trait BenchmarkingSetup<T, Call, RawOrigin> {
 	/// Return the components and their ranges which should be tested in this benchmark.
 	fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)>;
 
+	/// Return the components and their ranges, whose bounds may reference components declared
+	/// before them.
+	///
+	/// Defaults to the constant ranges of [`components`](#tymethod.components), which should
+	/// stay the widest possible ranges when this is overridden.
+	fn component_ranges(&self) -> Vec<(BenchmarkParameter, ComponentBound, ComponentBound)> {
+		self.components().into_iter()
+			.map(|(name, low, high)| (name, ComponentBound::Value(low), ComponentBound::Value(high)))
+			.collect()
+	}
+
+	/// Return what each component counts, e.g. `items`, `bytes` or `signatures`.
+	///
+	/// Components left out are undescribed.
+	fn component_units(&self) -> Vec<(BenchmarkParameter, &'static str)> {
+		Vec::new()
+	}
+
+	/// Return the pairs of components which should additionally be swept together, both from
+	/// their low towards their high value in lockstep.
+	///
+	/// The resulting diagonal slice reveals costs growing with the product of both components,
+	/// which sweeping one component at a time can't tell from a sum.
+	fn paired_components(&self) -> Vec<(BenchmarkParameter, BenchmarkParameter)> {
+		Vec::new()
+	}
+
+	/// Return whether the benchmark accesses storage, i.e. needs the DB warmed up before the run
+	/// and wiped after every iteration.
+	///
+	/// Pure-compute benchmarks, e.g. of hashing, can return `false` to skip this and run much
+	/// faster. Skipping it wrongly makes iterations measure the state left behind by earlier ones.
+	fn uses_storage(&self) -> bool {
+		true
+	}
+
+	/// Return whether every iteration should also be timed with the storage changes of the
+	/// dispatch dropped, to tell the cost of its reads and compute from that of its writes.
+	///
+	/// These timings are recorded next to the full ones, tagged with the
+	/// `BenchmarkParameter::ReadOnly` pseudo-component. A dispatch reading back what it wrote
+	/// sees the storage as it was before, and its outcome in this mode is ignored.
+	fn read_only_timing(&self) -> bool {
+		false
+	}
+
+	/// Return whether every iteration should also be timed with a warm DB cache, to tell the
+	/// typical cost of the dispatch from its worst case.
+	///
+	/// The warm timing dispatches a fresh instance right after the cold one, without
+	/// committing in between, so it finds what the first dispatch read in the cache. The setup
+	/// thus runs on top of the changes of the first dispatch. Both timings are tagged with the
+	/// `BenchmarkParameter::Cache` pseudo-component.
+	fn cache_timing(&self) -> bool {
+		false
+	}
+
+	/// Return the assignment of the components expected to be the most expensive to dispatch.
+	///
+	/// Defaults to every component at the high end of its range. That is only an assumption,
+	/// benchmarks whose cost peaks elsewhere for some component should override it. Pass the
+	/// assignment as [`BenchmarkRequest::fixed`] to measure just the worst case.
+	fn worst_case_assignment(&self) -> Result<Vec<(BenchmarkParameter, u32)>, &'static str> {
+		weighing::worst_case_assignment(&self.component_ranges())
+	}
+
 	/// Set up the storage, and prepare a call and caller to test in a single run of the benchmark.
+	///
+	/// Returning [`SKIP`] omits the sample, any other error aborts the benchmark.
 	fn instance(&self, components: &[(BenchmarkParameter, u32)]) -> Result<(Call, RawOrigin), &'static str>;
+
+	/// Return the number of origins the call should be dispatched with.
+	///
+	/// Every origin is measured separately, see [`instance_with_origin`](#method.instance_with_origin).
+	fn origins(&self) -> u32 {
+		1
+	}
+
+	/// Like `instance`, but prepare the call to be dispatched by the `origin`-th origin out of
+	/// [`origins`](#method.origins).
+	fn instance_with_origin(&self, components: &[(BenchmarkParameter, u32)], origin: u32)
+		-> Result<(Call, RawOrigin), &'static str>
+	{
+		let _ = origin;
+		self.instance(components)
+	}
+
+	/// Return the calls to dispatch after the setup of every instance, before the timed call.
+	///
+	/// They bring the chain into a state only reachable by dispatching other calls, e.g. `bond`
+	/// before a timed `claim`. They are dispatched in order and untimed, again for every
+	/// iteration as the DB is wiped after each, and the benchmark is aborted if one fails.
+	fn prerequisites(&self, components: &[(BenchmarkParameter, u32)])
+		-> Result<Vec<(Call, RawOrigin)>, &'static str>
+	{
+		let _ = components;
+		Ok(Vec::new())
+	}
+}
+
+/// Returns the snake case form of the camel case `camel`, e.g. `set_balance` for `SetBalance`.
+pub fn snake_case(camel: &str) -> Vec<u8> {
+	let mut snake = Vec::with_capacity(camel.len());
+	for (i, c) in camel.bytes().enumerate() {
+		if i > 0 && c.is_ascii_uppercase() {
+			snake.push(b'_');
+		}
+		snake.push(c.to_ascii_lowercase());
+	}
+	snake
+}
+
+/// Returns whether `snake` is the snake case form of the camel case `camel`, e.g. `set_balance`
+/// of `SetBalance`.
+pub fn is_snake_case_of(snake: &str, camel: &str) -> bool {
+	let expected = camel.chars().enumerate().flat_map(|(i, c)| {
+		let separator = if i > 0 && c.is_ascii_uppercase() { Some('_') } else { None };
+		separator.into_iter().chain(core::iter::once(c.to_ascii_lowercase()))
+	});
+	snake.chars().eq(expected)
+}
+
+/// Returns the names of the `calls` of a pallet which none of its `benchmarks` is named after,
+/// in the order of `calls`.
+///
+/// A benchmark covers the call named like it, e.g. `set_balance` for `SetBalance`.
+pub fn uncovered_calls(
+	calls: &[&'static str],
+	benchmarks: &[BenchmarkMetadata],
+) -> Vec<&'static str> {
+	calls.iter()
+		.filter(|call| benchmarks.iter().all(|b| b.name != call.as_bytes()))
+		.cloned()
+		.collect()
+}
+
+/// Returns `percent` percent of `max`, rounded down.
+pub fn percent_of(percent: u32, max: u32) -> u32 {
+	let value = max as u64 * percent as u64 / 100;
+	value.min(u32::max_value() as u64) as u32
+}
+
+/// Expands to the given items if the `criterion` feature is enabled, and to nothing otherwise.
+#[cfg(feature = "criterion")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! if_criterion {
+	( $( $item:item )* ) => { $( $item )* };
+}
+
+/// Expands to the given items if the `criterion` feature is enabled, and to nothing otherwise.
+#[cfg(not(feature = "criterion"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! if_criterion {
+	( $( $item:item )* ) => {};
+}
+
+/// Declares a component and its range, as returned by [`BenchmarkingSetup::components`].
+///
+/// The bounds are either absolute, or percentages of a maximum such as a runtime constant,
+/// which are turned into absolute bounds whenever the components are requested. The high end
+/// of an absolute range may be a runtime constant as well, so the range can't drift from the
+/// configured bound.
+///
+/// ```nocompile
+/// fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+/// 	vec![
+/// 		component!(R in 1 .. MAX_REGISTRARS),
+/// 		component!(S in 0 .. T::MaxSubAccounts::get()),
+/// 		component!(X in 10% .. 100% of T::MaxAdditionalFields::get()),
+/// 	]
+/// }
+/// ```
+#[macro_export]
+macro_rules! component {
+	( $name:ident in $low:literal % .. $high:literal % of $max:expr ) => {{
+		let max: u32 = $max;
+		(
+			$crate::benchmark_parameter!($name),
+			$crate::percent_of($low, max),
+			$crate::percent_of($high, max),
+		)
+	}};
+	( $name:ident in $low:literal .. $high:expr ) => {
+		($crate::benchmark_parameter!($name), $low, $high)
+	};
+}
+
+/// Declares components whose bounds may depend on each other, as returned by
+/// [`BenchmarkingSetup::component_ranges`].
+///
+/// A bound is either a value or the name of a component declared before it. Every component is
+/// only in scope for the ranges following its own, so referencing a later component, or the
+/// component itself, fails to compile.
+///
+/// ```nocompile
+/// fn component_ranges(&self) -> Vec<(BenchmarkParameter, ComponentBound, ComponentBound)> {
+/// 	component_ranges![
+/// 		R in 1 .. MAX_REGISTRARS,
+/// 		J in 0 .. R,
+/// 	]
+/// }
+/// ```
+#[macro_export]
+macro_rules! component_ranges {
+	( $( $name:ident in $low:tt .. $high:expr ),* $(,)? ) => {{
+		let mut ranges = $crate::Vec::new();
+		$crate::component_ranges!(@push ranges; $( $name in $low .. $high ),*);
+		ranges
+	}};
+	(@push $ranges:ident; ) => {};
+	(@push $ranges:ident;
+		$name:ident in $low:tt .. $high:expr $( , $rest:ident in $rest_low:tt .. $rest_high:expr )*
+	) => {
+		$ranges.push((
+			$crate::benchmark_parameter!($name),
+			$crate::ComponentBound::from($low),
+			$crate::ComponentBound::from($high),
+		));
+		{
+			#[allow(dead_code)]
+			const $name: $crate::ComponentBound =
+				$crate::ComponentBound::Component($crate::benchmark_parameter!($name));
+			$crate::component_ranges!(@push $ranges; $( $rest in $rest_low .. $rest_high ),*);
+		}
+	};
+}
+
+/// Returns `Err($error)` from the enclosing instancer unless `$cond` holds.
+///
+/// Without an error the condition itself is named, e.g. `Benchmark precondition failed:
+/// r <= MAX_REGISTRARS`.
+///
+/// ```nocompile
+/// fn instance(&self, components: &[(BenchmarkParameter, u32)])
+/// 	-> Result<(crate::Call<T>, RawOrigin<T::AccountId>), &'static str>
+/// {
+/// 	let r = components[0].1;
+/// 	ensure_bench!(r > 0, "At least one registrar is needed to judge");
+/// 	ensure_bench!(r <= MAX_REGISTRARS);
+/// 	...
+/// }
+/// ```
+#[macro_export]
+macro_rules! ensure_bench {
+	( $cond:expr ) => {
+		$crate::ensure_bench!($cond, concat!("Benchmark precondition failed: ", stringify!($cond)))
+	};
+	( $cond:expr, $error:expr $(,)? ) => {
+		if !$cond {
+			let error: &'static str = $error;
+			return Err(error);
+		}
+	};
+}
+
+/// Resolves to the `BenchmarkParameter` named `$name`.
+///
+/// A name which isn't a component, e.g. a typo or a pseudo-component like `Origin`, fails to
+/// compile with an error naming it, rather than with a type error deep inside `component!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! benchmark_parameter {
+	(A) => { $crate::BenchmarkParameter::A };
+	(B) => { $crate::BenchmarkParameter::B };
+	(C) => { $crate::BenchmarkParameter::C };
+	(D) => { $crate::BenchmarkParameter::D };
+	(E) => { $crate::BenchmarkParameter::E };
+	(F) => { $crate::BenchmarkParameter::F };
+	(G) => { $crate::BenchmarkParameter::G };
+	(H) => { $crate::BenchmarkParameter::H };
+	(I) => { $crate::BenchmarkParameter::I };
+	(J) => { $crate::BenchmarkParameter::J };
+	(K) => { $crate::BenchmarkParameter::K };
+	(L) => { $crate::BenchmarkParameter::L };
+	(M) => { $crate::BenchmarkParameter::M };
+	(N) => { $crate::BenchmarkParameter::N };
+	(O) => { $crate::BenchmarkParameter::O };
+	(P) => { $crate::BenchmarkParameter::P };
+	(Q) => { $crate::BenchmarkParameter::Q };
+	(R) => { $crate::BenchmarkParameter::R };
+	(S) => { $crate::BenchmarkParameter::S };
+	(T) => { $crate::BenchmarkParameter::T };
+	(U) => { $crate::BenchmarkParameter::U };
+	(V) => { $crate::BenchmarkParameter::V };
+	(W) => { $crate::BenchmarkParameter::W };
+	(X) => { $crate::BenchmarkParameter::X };
+	(Y) => { $crate::BenchmarkParameter::Y };
+	(Z) => { $crate::BenchmarkParameter::Z };
+	($other:ident) => {
+		compile_error!(concat!(
+			"`",
+			stringify!($other),
+			"` is not a benchmark parameter, components are named by a capital letter from `A` to `Z`",
+		))
+	};
 }
 
 /// Creates a `SelectedBenchmark` enum implementing `BenchmarkingSetup`.
 ///
-/// Every variant must implement [`BenchmarkingSetup`].
+/// Every variant must implement [`BenchmarkingSetup`]. The enum also implements `FromStr`,
+/// parsing the snake case name of a variant, e.g. `set_balance` for `SetBalance`.
 ///
 /// ```nocompile
 ///
@@ -103,39 +785,580 @@ pub trait BenchmarkingSetup<T, Call, RawOrigin> {
 ///
 /// selected_benchmark!(Transfer, SetBalance);
 /// ```
+///
+/// The benchmarks of an instantiable pallet are selected for every instance by naming the
+/// instance parameter and its trait first, which produces `Call<T, I>` instead of `Call<T>`.
+///
+/// ```nocompile
+/// selected_benchmark!(<I: Instance> Transfer, SetBalance);
+/// ```
+///
+/// With the `criterion` feature, `SelectedBenchmark::criterion` also registers a Criterion
+/// group per benchmark, see [`criterion_group`].
+///
+/// `SelectedBenchmark::uncovered_calls` returns the calls of the pallet without a benchmark,
+/// see [`uncovered_calls`](fn.uncovered_calls.html), so a test can make sure every call has one.
 #[macro_export]
 macro_rules! selected_benchmark {
 	(
-		$( $bench:ident ),*
+		< $instance:ident : $instance_trait:path > $( $bench:ident ),*
+	) => {
+		$crate::selected_benchmark!(
+			@impl { T: Trait<$instance>, $instance: $instance_trait } { T, $instance } $( $bench ),*
+		);
+	};
+	(
+		@impl { $( $generics:tt )* } { $( $call_generics:tt )* } $( $bench:ident ),*
 	) => {
 		// The list of available benchmarks for this pallet.
+		#[derive(Clone, Copy)]
 		enum SelectedBenchmark {
 			$( $bench, )*
 		}
 
-		// Allow us to select a benchmark from the list of available benchmarks.
-		impl<T: Trait> $crate::BenchmarkingSetup<T, Call<T>, RawOrigin<T::AccountId>> for SelectedBenchmark {
-			fn components(&self) -> Vec<($crate::BenchmarkParameter, u32, u32)> {
+		impl SelectedBenchmark {
+			// All the available benchmarks along with the name of their setup.
+			fn all() -> Vec<(&'static str, Self)> {
+				vec![ $( (stringify!($bench), Self::$bench), )* ]
+			}
+
+			// The setup of the selected benchmark.
+			fn setup<$( $generics )*>(&self) -> &dyn $crate::BenchmarkingSetup<
+				T,
+				Call<$( $call_generics )*>,
+				RawOrigin<T::AccountId>,
+			> {
 				match self {
-					$( Self::$bench => <$bench as $crate::BenchmarkingSetup<
-						T,
-						Call<T>,
-						RawOrigin<T::AccountId>,
-					>>::components(&$bench), )*
+					$( Self::$bench => &$bench, )*
+				}
+			}
+
+			// The components and their ranges of the benchmark named `name`.
+			#[allow(dead_code)]
+			fn components_for<$( $generics )*>(name: &str)
+				-> Result<Vec<($crate::BenchmarkParameter, u32, u32)>, &'static str>
+			{
+				Ok(name.parse::<Self>()?.setup::<$( $call_generics )*>().components())
+			}
+
+			// The worst case assignment of the benchmark named `name`.
+			#[allow(dead_code)]
+			fn worst_case_assignment<$( $generics )*>(name: &str)
+				-> Result<Vec<($crate::BenchmarkParameter, u32)>, &'static str>
+			{
+				name.parse::<Self>()?.setup::<$( $call_generics )*>().worst_case_assignment()
+			}
+
+			// The calls of the pallet no benchmark is named after.
+			#[allow(dead_code)]
+			fn uncovered_calls<$( $generics )*>() -> Vec<&'static str>
+			where
+				Call<$( $call_generics )*>: $crate::GetCallName,
+			{
+				$crate::uncovered_calls(
+					<Call<$( $call_generics )*> as $crate::GetCallName>::get_call_names(),
+					&Self::list::<$( $call_generics )*>(),
+				)
+			}
+
+			// List the available benchmarks along with their components.
+			fn list<$( $generics )*>() -> Vec<$crate::BenchmarkMetadata> {
+				Self::all().into_iter().map(|(name, benchmark)| {
+					let setup = benchmark.setup::<$( $call_generics )*>();
+					$crate::BenchmarkMetadata {
+						name: $crate::snake_case(name),
+						components: setup.components(),
+						units: setup.component_units().into_iter()
+							.map(|(param, unit)| (param, unit.as_bytes().to_vec()))
+							.collect(),
+					}
+				}).collect()
+			}
+		}
+
+		$crate::if_criterion! {
+			impl SelectedBenchmark {
+				// Register a Criterion group for every available benchmark, returning their names.
+				#[allow(dead_code)]
+				fn criterion<$( $generics )*>(
+					c: &mut $crate::criterion::Criterion,
+					execute_with: &mut dyn FnMut(&mut dyn FnMut()),
+				) -> Vec<String>
+				where
+					Call<$( $call_generics )*>: $crate::Dispatchable,
+					RawOrigin<T::AccountId>:
+						Into<<Call<$( $call_generics )*> as $crate::Dispatchable>::Origin>,
+				{
+					Self::all().into_iter().map(|(name, benchmark)| {
+						let setup = benchmark.setup::<$( $call_generics )*>();
+						$crate::criterion_group(c, name, setup, execute_with)
+					}).collect()
 				}
 			}
+		}
+
+		// Allow us to select a benchmark by its name.
+		impl core::str::FromStr for SelectedBenchmark {
+			type Err = &'static str;
+
+			fn from_str(name: &str) -> Result<Self, Self::Err> {
+				Self::all().into_iter()
+					.find(|(camel, _)| $crate::is_snake_case_of(name, camel))
+					.map(|(_, benchmark)| benchmark)
+					.ok_or("Could not find extrinsic.")
+			}
+		}
+
+		// Allow us to select a benchmark from the list of available benchmarks.
+		impl<$( $generics )*> $crate::BenchmarkingSetup<
+			T,
+			Call<$( $call_generics )*>,
+			RawOrigin<T::AccountId>,
+		> for SelectedBenchmark {
+			fn components(&self) -> Vec<($crate::BenchmarkParameter, u32, u32)> {
+				self.setup::<$( $call_generics )*>().components()
+			}
+
+			fn component_ranges(&self) -> Vec<(
+				$crate::BenchmarkParameter,
+				$crate::ComponentBound,
+				$crate::ComponentBound,
+			)> {
+				self.setup::<$( $call_generics )*>().component_ranges()
+			}
+
+			fn component_units(&self) -> Vec<($crate::BenchmarkParameter, &'static str)> {
+				self.setup::<$( $call_generics )*>().component_units()
+			}
+
+			fn paired_components(&self)
+				-> Vec<($crate::BenchmarkParameter, $crate::BenchmarkParameter)>
+			{
+				self.setup::<$( $call_generics )*>().paired_components()
+			}
+
+			fn uses_storage(&self) -> bool {
+				self.setup::<$( $call_generics )*>().uses_storage()
+			}
+
+			fn read_only_timing(&self) -> bool {
+				self.setup::<$( $call_generics )*>().read_only_timing()
+			}
+
+			fn cache_timing(&self) -> bool {
+				self.setup::<$( $call_generics )*>().cache_timing()
+			}
+
+			fn worst_case_assignment(&self)
+				-> Result<Vec<($crate::BenchmarkParameter, u32)>, &'static str>
+			{
+				self.setup::<$( $call_generics )*>().worst_case_assignment()
+			}
 
 			fn instance(&self, components: &[($crate::BenchmarkParameter, u32)])
-				-> Result<(Call<T>, RawOrigin<T::AccountId>), &'static str>
+				-> Result<(Call<$( $call_generics )*>, RawOrigin<T::AccountId>), &'static str>
+			{
+				self.setup::<$( $call_generics )*>().instance(components)
+			}
+
+			fn origins(&self) -> u32 {
+				self.setup::<$( $call_generics )*>().origins()
+			}
+
+			fn instance_with_origin(&self, components: &[($crate::BenchmarkParameter, u32)], origin: u32)
+				-> Result<(Call<$( $call_generics )*>, RawOrigin<T::AccountId>), &'static str>
+			{
+				self.setup::<$( $call_generics )*>().instance_with_origin(components, origin)
+			}
+
+			fn prerequisites(&self, components: &[($crate::BenchmarkParameter, u32)])
+				-> Result<
+					Vec<(Call<$( $call_generics )*>, RawOrigin<T::AccountId>)>,
+					&'static str,
+				>
 			{
+				self.setup::<$( $call_generics )*>().prerequisites(components)
+			}
+		}
+	};
+	(
+		$( $bench:ident ),*
+	) => {
+		$crate::selected_benchmark!(@impl { T: Trait } { T } $( $bench ),*);
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::{Encode, Decode};
+
+	#[test]
+	fn request_round_trips() {
+		let request = BenchmarkRequest {
+			name: b"transfer".to_vec(),
+			steps: 10,
+			repeat: 5,
+			seed: 42,
+			fixed: vec![(BenchmarkParameter::E, 3)],
+			points: vec![(BenchmarkParameter::U, vec![1, 2, 4, 8])],
+			worker: 1,
+			workers: 2,
+			timeout: Some(1_000_000),
+			on_timeout: OnTimeout::Abort,
+			warm_snapshot: Some(b"/tmp/state.snapshot".to_vec()),
+			rest: RestPolicy::High,
+			settle: true,
+			adaptive: Some(AdaptiveRepeat { max_error_permille: 10, max_repeat: 100 }),
+		};
+
+		let encoded = request.encode();
+		assert_eq!(BenchmarkRequest::decode(&mut &encoded[..]).unwrap(), request);
+	}
+
+	#[test]
+	fn snake_case_of_camel_case() {
+		assert_eq!(snake_case("Set"), b"set".to_vec());
+		assert_eq!(snake_case("TransferBestCase"), b"transfer_best_case".to_vec());
+	}
+
+	#[test]
+	fn snake_case_names_match_camel_case_variants() {
+		assert!(is_snake_case_of("set", "Set"));
+		assert!(is_snake_case_of("transfer_best_case", "TransferBestCase"));
+		assert!(!is_snake_case_of("transferbestcase", "TransferBestCase"));
+		assert!(!is_snake_case_of("transfer", "TransferBestCase"));
+		assert!(!is_snake_case_of("Transfer", "Transfer"));
+	}
+
+	#[test]
+	fn elapsed_time_conversions() {
+		let result: BenchmarkResults = (vec![(BenchmarkParameter::N, 1)], 1_234_567);
+
+		assert_eq!(result.as_nanos(), 1_234_567);
+		assert_eq!(result.as_micros(), 1_234);
+		assert_eq!(result.to_weight(1), 1_234_567);
+		assert_eq!(result.to_weight(1_000), 1_234_567_000);
+		assert_eq!(result.to_weight(1_000_000), Weight::max_value());
+		assert_eq!(result.to_weight(0), 0);
+	}
+
+	#[test]
+	fn percentage_ranges_desugar_to_absolute_bounds() {
+		const MAX_LENGTH: u32 = 250;
+		let max = || 7;
+
+		assert_eq!(component!(L in 10% .. 100% of MAX_LENGTH), (BenchmarkParameter::L, 25, 250));
+		assert_eq!(component!(L in 0% .. 50% of max()), (BenchmarkParameter::L, 0, 3));
+		assert_eq!(component!(L in 1 .. MAX_LENGTH), (BenchmarkParameter::L, 1, 250));
+		assert_eq!(percent_of(200, u32::max_value()), u32::max_value());
+	}
+
+	#[test]
+	fn ensure_bench_returns_the_error_if_the_condition_fails() {
+		fn instance(r: u32) -> Result<u32, &'static str> {
+			ensure_bench!(r > 0, "At least one registrar is needed");
+			ensure_bench!(r <= 50);
+			Ok(r)
+		}
+
+		assert_eq!(instance(3), Ok(3));
+		assert_eq!(instance(0), Err("At least one registrar is needed"));
+		assert_eq!(instance(51), Err("Benchmark precondition failed: r <= 50"));
+	}
+
+	#[test]
+	fn ranges_follow_the_runtime_constant_they_reference() {
+		use frame_support::traits::Get;
+
+		thread_local!(static MAX_LENGTH: core::cell::Cell<u32> = core::cell::Cell::new(100));
+		struct MaxLength;
+		impl Get<u32> for MaxLength {
+			fn get() -> u32 {
+				MAX_LENGTH.with(|max| max.get())
+			}
+		}
+
+		trait Config {
+			type MaxLength: Get<u32>;
+		}
+		struct Runtime;
+		impl Config for Runtime {
+			type MaxLength = MaxLength;
+		}
+
+		struct Remark;
+		impl<T: Config> BenchmarkingSetup<T, (), ()> for Remark {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![component!(L in 0 .. T::MaxLength::get())]
+			}
+
+			fn instance(&self, _: &[(BenchmarkParameter, u32)]) -> Result<((), ()), &'static str> {
+				Ok(((), ()))
+			}
+		}
+
+		let components = || BenchmarkingSetup::<Runtime, (), ()>::components(&Remark);
+		assert_eq!(components(), vec![(BenchmarkParameter::L, 0, 100)]);
+		MAX_LENGTH.with(|max| max.set(64));
+		assert_eq!(components(), vec![(BenchmarkParameter::L, 0, 64)]);
+	}
+
+	#[test]
+	fn component_ranges_can_refer_to_earlier_components() {
+		use BenchmarkParameter::*;
+		let ranges: Vec<(BenchmarkParameter, ComponentBound, ComponentBound)> =
+			component_ranges![A in 0 .. 100, B in A .. 200, C in 1 .. A];
+
+		assert_eq!(ranges, vec![
+			(A, ComponentBound::Value(0), ComponentBound::Value(100)),
+			(B, ComponentBound::Component(A), ComponentBound::Value(200)),
+			(C, ComponentBound::Value(1), ComponentBound::Component(A)),
+		]);
+	}
+
+	#[test]
+	fn manifest_lists_every_benchmark() {
+		let metadata = |name: &[u8], components, units| BenchmarkMetadata {
+			name: name.to_vec(),
+			components,
+			units,
+		};
+		let manifest = benchmark_manifest(vec![
+			(b"balances".to_vec(), vec![
+				metadata(b"transfer", vec![(BenchmarkParameter::U, 1, 1000)], vec![]),
+				metadata(b"set_balance", vec![], vec![]),
+			]),
+			(b"timestamp".to_vec(), vec![
+				metadata(
+					b"set",
+					vec![(BenchmarkParameter::N, 1, 100)],
+					vec![(BenchmarkParameter::N, b"milliseconds".to_vec())],
+				),
+			]),
+		]);
+
+		assert_eq!(manifest, vec![
+			BenchmarkMeta {
+				pallet: b"balances".to_vec(),
+				name: b"transfer".to_vec(),
+				components: vec![(BenchmarkParameter::U, 1, 1000)],
+				extra: vec![],
+			},
+			BenchmarkMeta {
+				pallet: b"balances".to_vec(),
+				name: b"set_balance".to_vec(),
+				components: vec![],
+				extra: vec![],
+			},
+			BenchmarkMeta {
+				pallet: b"timestamp".to_vec(),
+				name: b"set".to_vec(),
+				components: vec![(BenchmarkParameter::N, 1, 100)],
+				extra: vec![(BenchmarkParameter::N, b"milliseconds".to_vec())],
+			},
+		]);
+	}
+
+	#[test]
+	fn results_are_grouped_by_pallet_and_benchmark() {
+		let batch = |pallet: &[u8], benchmark: &[u8], time| BenchmarkBatch {
+			pallet: pallet.to_vec(),
+			benchmark: benchmark.to_vec(),
+			results: vec![(vec![(BenchmarkParameter::U, 1)], time)],
+		};
+		let grouped = group_by_pallet(vec![
+			batch(b"timestamp", b"set", 10),
+			batch(b"balances", b"transfer", 20),
+			batch(b"balances", b"set_balance", 30),
+			batch(b"balances", b"transfer", 40),
+		]);
+
+		assert_eq!(grouped, vec![
+			PalletResults {
+				pallet: b"balances".to_vec(),
+				benchmarks: vec![
+					(b"set_balance".to_vec(), vec![(vec![(BenchmarkParameter::U, 1)], 30)]),
+					(b"transfer".to_vec(), vec![
+						(vec![(BenchmarkParameter::U, 1)], 20),
+						(vec![(BenchmarkParameter::U, 1)], 40),
+					]),
+				],
+			},
+			PalletResults {
+				pallet: b"timestamp".to_vec(),
+				benchmarks: vec![(b"set".to_vec(), vec![(vec![(BenchmarkParameter::U, 1)], 10)])],
+			},
+		]);
+	}
+
+	#[test]
+	fn batch_round_trips() {
+		let batch = BenchmarkBatch {
+			pallet: b"balances".to_vec(),
+			benchmark: b"transfer".to_vec(),
+			results: vec![
+				(vec![(BenchmarkParameter::U, 1), (BenchmarkParameter::E, 3)], 1_000),
+				(vec![(BenchmarkParameter::U, 2), (BenchmarkParameter::E, 3)], 2_000),
+			],
+		};
+
+		let encoded = batch.encode();
+		assert_eq!(BenchmarkBatch::decode(&mut &encoded[..]).unwrap(), batch);
+	}
+
+	// A pallet instantiable like the ones declared with `decl_module!` and `decl_storage!`.
+	mod instanced {
+		use super::*;
+		use core::marker::PhantomData;
+
+		pub trait Instance: 'static {
+			const MAX: u32;
+		}
+		pub enum Instance1 {}
+		impl Instance for Instance1 { const MAX: u32 = 10; }
+		pub enum Instance2 {}
+		impl Instance for Instance2 { const MAX: u32 = 20; }
+
+		pub trait Trait<I: Instance>: 'static {
+			type AccountId;
+		}
+		pub struct Call<T, I>(pub u32, PhantomData<(T, I)>);
+		pub struct RawOrigin<AccountId>(PhantomData<AccountId>);
+
+		pub enum Test {}
+		impl Trait<Instance1> for Test { type AccountId = u64; }
+		impl Trait<Instance2> for Test { type AccountId = u64; }
+
+		pub struct Fill;
+		impl<T: Trait<I>, I: Instance> BenchmarkingSetup<T, Call<T, I>, RawOrigin<T::AccountId>> for Fill {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(BenchmarkParameter::N, 0, I::MAX)]
+			}
+
+			fn instance(&self, components: &[(BenchmarkParameter, u32)])
+				-> Result<(Call<T, I>, RawOrigin<T::AccountId>), &'static str>
+			{
+				Ok((Call(components[0].1, PhantomData), RawOrigin(PhantomData)))
+			}
+		}
+
+		selected_benchmark!(<I: Instance> Fill);
+
+		#[test]
+		fn instances_are_benchmarked_distinctly() {
+			let first = SelectedBenchmark::list::<Test, Instance1>();
+			let second = SelectedBenchmark::list::<Test, Instance2>();
+			assert_eq!(first[0].name, b"fill".to_vec());
+			assert_eq!(first[0].components, vec![(BenchmarkParameter::N, 0, 10)]);
+			assert_eq!(second[0].components, vec![(BenchmarkParameter::N, 0, 20)]);
+
+			let selected: SelectedBenchmark = "fill".parse().unwrap();
+			let (call, _) = <SelectedBenchmark as BenchmarkingSetup<
+				Test,
+				Call<Test, Instance2>,
+				RawOrigin<u64>,
+			>>::instance(&selected, &[(BenchmarkParameter::N, 15)]).unwrap();
+			assert_eq!(call.0, 15);
+		}
+
+		#[test]
+		fn components_are_looked_up_by_name() {
+			assert_eq!(
+				SelectedBenchmark::components_for::<Test, Instance2>("fill"),
+				Ok(vec![(BenchmarkParameter::N, 0, 20)]),
+			);
+			assert!(SelectedBenchmark::components_for::<Test, Instance2>("drain").is_err());
+		}
+
+		#[cfg(feature = "criterion")]
+		impl<T, I> Dispatchable for Call<T, I> {
+			type Origin = RawOrigin<u64>;
+			type Trait = ();
+			fn dispatch(self, _: Self::Origin) -> sp_runtime::DispatchResult {
+				Ok(())
+			}
+		}
+
+		#[test]
+		#[cfg(feature = "criterion")]
+		fn criterion_groups_are_named_after_the_benchmarks() {
+			let mut c = criterion::Criterion::default()
+				.sample_size(10)
+				.warm_up_time(core::time::Duration::from_millis(1))
+				.measurement_time(core::time::Duration::from_millis(10));
+			let groups = SelectedBenchmark::criterion::<Test, Instance1>(&mut c, &mut |f| f());
+			assert_eq!(groups, vec!["fill".to_string()]);
+		}
+	}
+
+	// A pallet with a call none of its benchmarks covers.
+	mod uncovered {
+		use super::*;
+		use core::marker::PhantomData;
+
+		pub trait Trait: 'static {
+			type AccountId;
+		}
+		pub enum Call<T> {
+			Transfer(PhantomData<T>),
+			SetBalance,
+			ForceTransfer,
+		}
+		impl<T> GetCallName for Call<T> {
+			fn get_call_names() -> &'static [&'static str] {
+				&["transfer", "set_balance", "force_transfer"]
+			}
+
+			fn get_call_name(&self) -> &'static str {
 				match self {
-					$( Self::$bench => <$bench as $crate::BenchmarkingSetup<
-						T,
-						Call<T>,
-						RawOrigin<T::AccountId>,
-					>>::instance(&$bench, components), )*
+					Call::Transfer(_) => "transfer",
+					Call::SetBalance => "set_balance",
+					Call::ForceTransfer => "force_transfer",
 				}
 			}
 		}
-	};
+		pub struct RawOrigin<AccountId>(PhantomData<AccountId>);
+
+		pub enum Test {}
+		impl Trait for Test { type AccountId = u64; }
+
+		pub struct Transfer;
+		impl<T: Trait> BenchmarkingSetup<T, Call<T>, RawOrigin<T::AccountId>> for Transfer {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				Vec::new()
+			}
+
+			fn instance(&self, _: &[(BenchmarkParameter, u32)])
+				-> Result<(Call<T>, RawOrigin<T::AccountId>), &'static str>
+			{
+				Ok((Call::Transfer(PhantomData), RawOrigin(PhantomData)))
+			}
+		}
+
+		pub struct SetBalance;
+		impl<T: Trait> BenchmarkingSetup<T, Call<T>, RawOrigin<T::AccountId>> for SetBalance {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				Vec::new()
+			}
+
+			fn instance(&self, _: &[(BenchmarkParameter, u32)])
+				-> Result<(Call<T>, RawOrigin<T::AccountId>), &'static str>
+			{
+				Ok((Call::SetBalance, RawOrigin(PhantomData)))
+			}
+		}
+
+		selected_benchmark!(Transfer, SetBalance);
+
+		#[test]
+		fn calls_without_a_benchmark_are_reported() {
+			assert_eq!(SelectedBenchmark::uncovered_calls::<Test>(), vec!["force_transfer"]);
+			assert_eq!(Call::<Test>::ForceTransfer.get_call_name(), "force_transfer");
+			assert_eq!(
+				uncovered_calls(&["transfer", "set_balance"], &SelectedBenchmark::list::<Test>()),
+				Vec::<&str>::new(),
+			);
+		}
+	}
 }
@@ -0,0 +1,254 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A mock of the benchmarking host functions, for unit testing the runners without an executor.
+//!
+//! With the `mock-host` feature the runners call the functions of this module instead of the
+//! [`benchmarking`](crate::benchmarking) host functions. The mock keeps its state per thread, so
+//! tests running in parallel don't see each other's calls:
+//!
+//! - the clock only moves when told to by [`advance_clock`], unless replaced with [`set_clock`],
+//! - commits and wipes of the DB, and the times the host is let settle, are counted rather
+//!   than done,
+//! - snapshots are recorded rather than written, and every snapshot loads.
+//!
+//! Switching the DB to read-only is still passed on to the externalities, if there are any.
+
+use std::cell::RefCell;
+
+/// What the runners did with the mock host on the current thread.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct HostState {
+	/// The number of times the DB was committed.
+	pub commits: u32,
+	/// The number of times the DB was wiped.
+	pub wipes: u32,
+	/// The number of times the host was let settle between two iterations.
+	pub settles: u32,
+	/// Whether the DB is read-only.
+	pub read_only: bool,
+	/// The paths of the snapshots loaded.
+	pub loaded_snapshots: Vec<Vec<u8>>,
+	/// The benchmark names and encoded component assignments of the snapshots taken.
+	pub snapshots: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+#[derive(Default)]
+struct MockHost {
+	/// The time of the manual clock, in nanoseconds.
+	now: u128,
+	/// The clock replacing the manual one.
+	clock: Option<fn() -> u128>,
+	state: HostState,
+}
+
+thread_local! {
+	static HOST: RefCell<MockHost> = RefCell::new(MockHost::default());
+}
+
+/// Reset the mock host of the current thread, including its clock.
+pub fn reset() {
+	HOST.with(|host| *host.borrow_mut() = MockHost::default());
+}
+
+/// Returns what the runners did with the mock host of the current thread.
+pub fn state() -> HostState {
+	HOST.with(|host| host.borrow().state.clone())
+}
+
+/// Move the manual clock of the current thread forward by `nanos`.
+///
+/// A dispatch calling this appears to take `nanos` to the runners.
+pub fn advance_clock(nanos: u128) {
+	HOST.with(|host| host.borrow_mut().now += nanos);
+}
+
+/// Read the time from `clock` instead of the manual clock on the current thread.
+pub fn set_clock(clock: fn() -> u128) {
+	HOST.with(|host| host.borrow_mut().clock = Some(clock));
+}
+
+pub(crate) fn current_time() -> u128 {
+	// The clock is called outside of the borrow, so it may use the mock itself.
+	match HOST.with(|host| host.borrow().clock) {
+		Some(clock) => clock(),
+		None => HOST.with(|host| host.borrow().now),
+	}
+}
+
+pub(crate) fn wipe_db() {
+	HOST.with(|host| host.borrow_mut().state.wipes += 1);
+}
+
+pub(crate) fn commit_db() {
+	HOST.with(|host| host.borrow_mut().state.commits += 1);
+}
+
+pub(crate) fn settle() {
+	HOST.with(|host| host.borrow_mut().state.settles += 1);
+}
+
+pub(crate) fn set_read_only_db(read_only: bool) {
+	HOST.with(|host| host.borrow_mut().state.read_only = read_only);
+	sp_externalities::with_externalities(|ext| {
+		sp_externalities::Externalities::set_read_only(ext, read_only)
+	});
+}
+
+pub(crate) fn load_snapshot(path: &[u8]) -> bool {
+	HOST.with(|host| host.borrow_mut().state.loaded_snapshots.push(path.to_vec()));
+	true
+}
+
+pub(crate) fn snapshot_db(name: &[u8], components: &[u8]) {
+	HOST.with(|host| {
+		host.borrow_mut().state.snapshots.push((name.to_vec(), components.to_vec()))
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use sp_runtime::{DispatchError, DispatchResult, traits::Dispatchable};
+	use crate::{BenchmarkParameter::{self, *}, BenchmarkRequest, BenchmarkingSetup, run_benchmark};
+
+	// Takes 10 ns per unit of `A`, and fails for `A` of 5 if asked to.
+	struct Spin { a: u32, fail_at_five: bool }
+	impl Dispatchable for Spin {
+		type Origin = ();
+		type Trait = ();
+		fn dispatch(self, _: ()) -> DispatchResult {
+			if self.fail_at_five && self.a == 5 {
+				return Err(DispatchError::Other("Spun out"));
+			}
+			advance_clock(10 * self.a as u128);
+			Ok(())
+		}
+	}
+
+	struct SpinSetup { fail_at_five: bool }
+	impl BenchmarkingSetup<(), Spin, ()> for SpinSetup {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			vec![(A, 0, 10)]
+		}
+
+		fn instance(&self, c: &[(BenchmarkParameter, u32)]) -> Result<(Spin, ()), &'static str> {
+			Ok((Spin { a: c[0].1, fail_at_five: self.fail_at_five }, ()))
+		}
+	}
+
+	fn request() -> BenchmarkRequest {
+		BenchmarkRequest { name: b"spin".to_vec(), steps: 2, repeat: 2, ..Default::default() }
+	}
+
+	#[test]
+	fn sweeps_are_timed_by_the_mock_clock() {
+		reset();
+		let results = run_benchmark(&SpinSetup { fail_at_five: false }, &request()).unwrap();
+		assert_eq!(results, vec![
+			(vec![(A, 0)], 0),
+			(vec![(A, 0)], 0),
+			(vec![(A, 5)], 50),
+			(vec![(A, 5)], 50),
+		]);
+		// Once to warm up the DB, and once per iteration.
+		let state = state();
+		assert_eq!((state.commits, state.wipes), (5, 5));
+		assert!(state.snapshots.is_empty());
+		assert_eq!(state.settles, 0);
+	}
+
+	#[test]
+	fn the_host_settles_after_every_iteration_if_asked() {
+		reset();
+		let request = BenchmarkRequest { settle: true, repeat: 3, ..request() };
+		assert_eq!(run_benchmark(&SpinSetup { fail_at_five: false }, &request).unwrap().len(), 6);
+		// Once per iteration, after it was wiped.
+		let state = state();
+		assert_eq!((state.wipes, state.settles), (7, 6));
+	}
+
+	#[test]
+	fn failed_dispatches_are_snapshotted() {
+		reset();
+		assert_eq!(run_benchmark(&SpinSetup { fail_at_five: true }, &request()), Err("Spun out"));
+		assert_eq!(state().snapshots, vec![(b"spin".to_vec(), vec![(A, 5)].encode())]);
+	}
+
+	#[test]
+	fn warm_snapshots_leave_the_db_alone() {
+		reset();
+		let request = BenchmarkRequest { warm_snapshot: Some(b"warm".to_vec()), ..request() };
+		assert_eq!(run_benchmark(&SpinSetup { fail_at_five: false }, &request).unwrap().len(), 4);
+		let state = state();
+		assert_eq!(state.loaded_snapshots, vec![b"warm".to_vec()]);
+		assert_eq!((state.commits, state.wipes), (0, 0));
+	}
+
+	#[test]
+	fn injected_clocks_are_calibrated() {
+		thread_local!(static TICKS: std::cell::Cell<u128> = std::cell::Cell::new(0));
+		// Every read takes 7 ns, so there is nothing left of the dispatch once that's subtracted.
+		fn ticking() -> u128 {
+			TICKS.with(|ticks| {
+				ticks.set(ticks.get() + 7);
+				ticks.get()
+			})
+		}
+
+		reset();
+		set_clock(ticking);
+		let results = run_benchmark(&SpinSetup { fail_at_five: false }, &request()).unwrap();
+		assert!(results.iter().all(|(_, elapsed)| *elapsed == 0), "{:?}", results);
+	}
+
+	#[test]
+	fn the_db_is_writable_again_after_a_panicking_read_only_timing() {
+		// Panics on finding its writes dropped.
+		struct Write;
+		impl Dispatchable for Write {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> DispatchResult {
+				assert!(!state().read_only, "write dropped");
+				Ok(())
+			}
+		}
+
+		struct WriteSetup;
+		impl BenchmarkingSetup<(), Write, ()> for WriteSetup {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 0, 10)]
+			}
+
+			fn read_only_timing(&self) -> bool {
+				true
+			}
+
+			fn instance(&self, _: &[(BenchmarkParameter, u32)])
+				-> Result<(Write, ()), &'static str>
+			{
+				Ok((Write, ()))
+			}
+		}
+
+		reset();
+		let run = || run_benchmark(&WriteSetup, &request());
+		assert!(std::panic::catch_unwind(run).is_err());
+		assert!(!state().read_only);
+	}
+}
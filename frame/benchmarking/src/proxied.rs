@@ -0,0 +1,123 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarking calls the way they are dispatched through a proxy.
+
+use crate::{BenchmarkParameter, Wrap, Wrapped};
+
+/// Wraps the instances of a bare setup into calls dispatching them through a proxy.
+pub struct Proxying<Proxy>(Proxy);
+
+impl<Call, RawOrigin, ProxyCall, ProxyOrigin, Proxy> Wrap<Call, RawOrigin, ProxyCall, ProxyOrigin>
+	for Proxying<Proxy>
+where
+	Proxy: Fn(Call, RawOrigin, &[(BenchmarkParameter, u32)])
+		-> Result<(ProxyCall, ProxyOrigin), &'static str>,
+{
+	fn wrap(&self, call: Call, caller: RawOrigin, components: &[(BenchmarkParameter, u32)])
+		-> Result<(ProxyCall, ProxyOrigin), &'static str>
+	{
+		(self.0)(call, caller, components)
+	}
+}
+
+/// Benchmarks the calls of the bare setup `Setup` dispatched through a proxy, which `Proxy`
+/// wraps every instance in, e.g. a call of a proxy pallet on behalf of the instance's origin.
+///
+/// The instances are prepared by `Setup` as usual, so the measured cost is that of the call
+/// plus the overhead of the delegation. Everything but the instances is the same as for
+/// `Setup`.
+///
+/// The proxy must already be authorized to dispatch on behalf of the origin, e.g. by one of
+/// the prerequisites of `Setup`.
+pub type Proxied<Setup, Proxy, Call, RawOrigin> = Wrapped<Setup, Proxying<Proxy>, Call, RawOrigin>;
+
+impl<Setup, Proxy, Call, RawOrigin> Proxied<Setup, Proxy, Call, RawOrigin> {
+	/// Benchmark the calls of `setup` dispatched through `proxy`.
+	pub fn new(setup: Setup, proxy: Proxy) -> Self {
+		Wrapped::with(setup, Proxying(proxy))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::{DispatchError, DispatchResult, traits::Dispatchable};
+	use std::sync::atomic::{AtomicU64, Ordering};
+	use crate::{BenchmarkingSetup, BenchmarkParameter::*};
+
+	// The origins the proxy and the call were dispatched by.
+	static PROXY: AtomicU64 = AtomicU64::new(0);
+	static REAL: AtomicU64 = AtomicU64::new(0);
+
+	struct Remark;
+	impl Dispatchable for Remark {
+		type Origin = Option<u64>;
+		type Trait = ();
+		fn dispatch(self, origin: Self::Origin) -> DispatchResult {
+			REAL.store(origin.ok_or(DispatchError::BadOrigin)?, Ordering::SeqCst);
+			Ok(())
+		}
+	}
+
+	// Stands in for a proxy pallet looking up the delegation of `real` to the proxy.
+	struct Proxy { real: u64, call: Remark }
+	impl Dispatchable for Proxy {
+		type Origin = Option<u64>;
+		type Trait = ();
+		fn dispatch(self, origin: Self::Origin) -> DispatchResult {
+			PROXY.store(origin.ok_or(DispatchError::BadOrigin)?, Ordering::SeqCst);
+			self.call.dispatch(Some(self.real))
+		}
+	}
+
+	struct RemarkSetup;
+	impl BenchmarkingSetup<(), Remark, Option<u64>> for RemarkSetup {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			vec![(L, 0, 10)]
+		}
+
+		fn instance(&self, _: &[(BenchmarkParameter, u32)])
+			-> Result<(Remark, Option<u64>), &'static str>
+		{
+			Ok((Remark, Some(1)))
+		}
+	}
+
+	#[test]
+	fn proxied_calls_include_the_delegation() {
+		type ProxiedSetup = dyn BenchmarkingSetup<(), Proxy, Option<u64>>;
+
+		let proxied = Proxied::<_, _, Remark, Option<u64>>::new(
+			RemarkSetup,
+			|call, caller: Option<u64>, _: &[(BenchmarkParameter, u32)]|
+				-> Result<(Proxy, Option<u64>), &'static str>
+			{
+				let real = caller.ok_or("Remarks are made by an account")?;
+				Ok((Proxy { real, call }, Some(2)))
+			},
+		);
+		let proxied: &ProxiedSetup = &proxied;
+		assert_eq!(proxied.components(), RemarkSetup.components());
+
+		// The proxy dispatches the call on behalf of the instance's origin.
+		let (proxy, proxy_caller) = proxied.instance(&[(L, 10)]).unwrap();
+		assert_eq!((proxy.real, proxy_caller), (1, Some(2)));
+		proxy.dispatch(proxy_caller).unwrap();
+		assert_eq!(PROXY.load(Ordering::SeqCst), 2);
+		assert_eq!(REAL.load(Ordering::SeqCst), 1);
+	}
+}
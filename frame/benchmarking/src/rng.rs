@@ -0,0 +1,180 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Deterministic randomness for instancers.
+//!
+//! Benchmarks are only reproducible if everything they do is derived from the request, so all
+//! randomness an instancer needs, e.g. to shuffle its inputs or pick keys, should come from
+//! [`rng`] rather than from the time or the operating system.
+
+use sp_std::prelude::*;
+use codec::Encode;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+#[cfg(feature = "std")]
+std::thread_local! {
+	/// The seed of the current run of this thread, and the first bytes of the hash of the name
+	/// of its benchmark.
+	static RUN: core::cell::Cell<(u32, u64)> = core::cell::Cell::new((0, 0));
+}
+
+/// Returns the seed and the name key of the current run.
+#[cfg(feature = "std")]
+fn current_run() -> (u32, u64) {
+	RUN.with(|run| run.get())
+}
+
+#[cfg(feature = "std")]
+fn set_current_run(key: (u32, u64)) {
+	RUN.with(|run| run.set(key));
+}
+
+// A runtime runs on a single thread, so its run can be global.
+#[cfg(not(feature = "std"))]
+static SEED: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(0);
+#[cfg(not(feature = "std"))]
+static NAME: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(0);
+
+#[cfg(not(feature = "std"))]
+fn current_run() -> (u32, u64) {
+	use core::sync::atomic::Ordering;
+	(SEED.load(Ordering::Relaxed), NAME.load(Ordering::Relaxed))
+}
+
+#[cfg(not(feature = "std"))]
+fn set_current_run((seed, name): (u32, u64)) {
+	use core::sync::atomic::Ordering;
+	SEED.store(seed, Ordering::Relaxed);
+	NAME.store(name, Ordering::Relaxed);
+}
+
+/// A deterministic stream of random numbers.
+pub struct BenchmarkRng {
+	key: (u32, u64),
+	counter: u64,
+}
+
+impl BenchmarkRng {
+	/// Create the stream of the benchmark `name` run with `seed`.
+	pub fn new(seed: u32, name: &[u8]) -> Self {
+		BenchmarkRng { key: (seed, name_key(name)), counter: 0 }
+	}
+
+	/// Returns the next random `u64`.
+	pub fn next_u64(&mut self) -> u64 {
+		let hash = BlakeTwo256::hash_of(&(self.key, self.counter));
+		self.counter += 1;
+		let mut bytes = [0u8; 8];
+		bytes.copy_from_slice(&hash.as_ref()[..8]);
+		u64::from_le_bytes(bytes)
+	}
+
+	/// Returns the next random `u32`.
+	pub fn next_u32(&mut self) -> u32 {
+		self.next_u64() as u32
+	}
+
+	/// Fill `dest` with random bytes.
+	pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+		for chunk in dest.chunks_mut(8) {
+			let bytes = self.next_u64().to_le_bytes();
+			chunk.copy_from_slice(&bytes[..chunk.len()]);
+		}
+	}
+
+	/// Shuffle `items` into a random order.
+	pub fn shuffle<T>(&mut self, items: &mut [T]) {
+		for i in (1..items.len()).rev() {
+			let j = (self.next_u64() % (i as u64 + 1)) as usize;
+			items.swap(i, j);
+		}
+	}
+}
+
+/// Returns the random stream of the current benchmark run.
+///
+/// The stream only depends on the seed of the request and the name of the benchmark, so every
+/// call starts it over, and instances prepared for the same request get the same numbers.
+pub fn rng() -> BenchmarkRng {
+	BenchmarkRng { key: current_run(), counter: 0 }
+}
+
+/// Make [`rng`] return the stream of the benchmark `name` run with `seed`.
+///
+/// The runners call this before preparing any instance. The run is that of the calling thread,
+/// so runs on other threads, e.g. of tests, don't change its stream.
+pub(crate) fn start_run(seed: u32, name: &[u8]) {
+	set_current_run((seed, name_key(name)));
+}
+
+fn name_key(name: &[u8]) -> u64 {
+	let hash = BlakeTwo256::hash(&name.encode());
+	let mut bytes = [0u8; 8];
+	bytes.copy_from_slice(&hash.as_ref()[..8]);
+	u64::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sample(rng: &mut BenchmarkRng) -> Vec<u64> {
+		(0..16).map(|_| rng.next_u64()).collect()
+	}
+
+	#[test]
+	fn same_seed_gives_the_same_numbers() {
+		start_run(42, b"transfer");
+		let first = sample(&mut rng());
+		start_run(42, b"transfer");
+		assert_eq!(sample(&mut rng()), first);
+		assert_eq!(sample(&mut BenchmarkRng::new(42, b"transfer")), first);
+
+		assert_ne!(sample(&mut BenchmarkRng::new(43, b"transfer")), first);
+		assert_ne!(sample(&mut BenchmarkRng::new(42, b"set_balance")), first);
+	}
+
+	#[test]
+	fn shuffles_are_reproducible() {
+		let shuffled = |seed| {
+			let mut items: Vec<u32> = (0..50).collect();
+			BenchmarkRng::new(seed, b"transfer").shuffle(&mut items);
+			items
+		};
+
+		assert_eq!(shuffled(7), shuffled(7));
+		assert_ne!(shuffled(7), (0..50).collect::<Vec<_>>());
+
+		let mut sorted = shuffled(7);
+		sorted.sort();
+		assert_eq!(sorted, (0..50).collect::<Vec<_>>());
+
+		let mut bytes = [0u8; 13];
+		BenchmarkRng::new(7, b"transfer").fill_bytes(&mut bytes);
+		assert!(bytes.iter().any(|b| *b != 0));
+	}
+
+	#[test]
+	fn runs_on_other_threads_keep_to_their_stream() {
+		start_run(42, b"transfer");
+		let first = sample(&mut rng());
+		std::thread::spawn(|| {
+			start_run(43, b"set_balance");
+			assert_eq!(sample(&mut rng()), sample(&mut BenchmarkRng::new(43, b"set_balance")));
+		}).join().unwrap();
+		assert_eq!(sample(&mut rng()), first);
+	}
+}
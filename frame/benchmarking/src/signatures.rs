@@ -0,0 +1,187 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Real signatures for components counting the signatures a dispatch verifies.
+//!
+//! The keys are generated in the keystore of the externalities the benchmark runs in, so one
+//! has to be registered.
+
+use sp_std::prelude::*;
+use sp_runtime::{KeyTypeId, MultiSignature, MultiSigner};
+use crate::BenchmarkParameter;
+
+/// The key type of the keys generated to sign with.
+pub const BENCHMARK_KEY_TYPE: KeyTypeId = KeyTypeId(*b"bnch");
+
+/// A signature scheme keys can be generated for.
+#[derive(codec::Encode, codec::Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SignatureScheme {
+	/// Ed25519 signatures.
+	Ed25519,
+	/// Schnorrkel signatures on Ristretto 255.
+	Sr25519,
+}
+
+/// Returns `count` distinct signers, each along with its signature of `message` in `scheme`.
+///
+/// The keys are derived from their index, so the same count yields the same signers.
+pub fn generate_signatures(scheme: SignatureScheme, count: u32, message: &[u8])
+	-> Result<Vec<(MultiSigner, MultiSignature)>, &'static str>
+{
+	(0..count).map(|index| {
+		let seed = Some(seed(index));
+		match scheme {
+			SignatureScheme::Ed25519 => {
+				let public = sp_io::crypto::ed25519_generate(BENCHMARK_KEY_TYPE, seed);
+				let signature = sp_io::crypto::ed25519_sign(BENCHMARK_KEY_TYPE, &public, message)
+					.ok_or("Generated key is missing from the keystore")?;
+				Ok((public.into(), signature.into()))
+			},
+			SignatureScheme::Sr25519 => {
+				let public = sp_io::crypto::sr25519_generate(BENCHMARK_KEY_TYPE, seed);
+				let signature = sp_io::crypto::sr25519_sign(BENCHMARK_KEY_TYPE, &public, message)
+					.ok_or("Generated key is missing from the keystore")?;
+				Ok((public.into(), signature.into()))
+			},
+		}
+	}).collect()
+}
+
+/// Returns as many signatures of `message` in `scheme` as `param` is assigned in `components`.
+///
+/// This is what an instancer calls to fill a component counting signatures.
+pub fn signatures_for(
+	components: &[(BenchmarkParameter, u32)],
+	param: BenchmarkParameter,
+	scheme: SignatureScheme,
+	message: &[u8],
+) -> Result<Vec<(MultiSigner, MultiSignature)>, &'static str> {
+	let count = components.iter()
+		.find(|(p, _)| *p == param)
+		.ok_or("Signature component is not assigned")?
+		.1;
+	generate_signatures(scheme, count, message)
+}
+
+/// The secret URI of the key with the given index, i.e. `//Benchmark//<index>`.
+fn seed(index: u32) -> Vec<u8> {
+	let mut digits = Vec::new();
+	let mut rest = index;
+	loop {
+		digits.push(b'0' + (rest % 10) as u8);
+		rest /= 10;
+		if rest == 0 {
+			break;
+		}
+	}
+	digits.reverse();
+
+	let mut seed = b"//Benchmark//".to_vec();
+	seed.extend(digits);
+	seed
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{testing::KeyStore, traits::KeystoreExt};
+	use sp_runtime::{DispatchError, DispatchResult, traits::{Dispatchable, IdentifyAccount, Verify}};
+	use std::cell::Cell;
+	use crate::{BenchmarkingSetup, BenchmarkParameter::*};
+
+	const MESSAGE: &[u8] = b"benchmark";
+
+	thread_local!(static VERIFIED: Cell<u32> = Cell::new(0));
+
+	// Costs one signature verification per signer, counted in `VERIFIED`.
+	struct VerifyAll(Vec<(MultiSigner, MultiSignature)>);
+	impl Dispatchable for VerifyAll {
+		type Origin = ();
+		type Trait = ();
+		fn dispatch(self, _: ()) -> DispatchResult {
+			for (signer, signature) in self.0 {
+				VERIFIED.with(|verified| verified.set(verified.get() + 1));
+				if !signature.verify(MESSAGE, &signer.into_account()) {
+					return Err(DispatchError::Other("Bad signature"));
+				}
+			}
+			Ok(())
+		}
+	}
+
+	struct VerifySetup(SignatureScheme);
+	impl BenchmarkingSetup<(), VerifyAll, ()> for VerifySetup {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			vec![(S, 1, 100)]
+		}
+
+		fn instance(&self, components: &[(BenchmarkParameter, u32)])
+			-> Result<(VerifyAll, ()), &'static str>
+		{
+			Ok((VerifyAll(signatures_for(components, S, self.0, MESSAGE)?), ()))
+		}
+	}
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		let mut ext = sp_io::TestExternalities::new_empty();
+		ext.register_extension(KeystoreExt(KeyStore::new()));
+		ext
+	}
+
+	#[test]
+	fn signatures_are_real_and_distinct() {
+		new_test_ext().execute_with(|| {
+			for scheme in vec![SignatureScheme::Ed25519, SignatureScheme::Sr25519] {
+				let signatures = generate_signatures(scheme, 3, MESSAGE).unwrap();
+				assert_eq!(signatures.len(), 3);
+				assert!(signatures[0].0 != signatures[1].0 && signatures[1].0 != signatures[2].0);
+				for (signer, signature) in signatures.clone() {
+					assert!(signature.verify(MESSAGE, &signer.into_account()));
+				}
+
+				let again = generate_signatures(scheme, 1, MESSAGE).unwrap();
+				assert_eq!(again[0].0, signatures[0].0);
+			}
+		});
+	}
+
+	#[test]
+	fn verification_cost_scales_with_the_signatures() {
+		new_test_ext().execute_with(|| {
+			for scheme in vec![SignatureScheme::Ed25519, SignatureScheme::Sr25519] {
+				let setup = VerifySetup(scheme);
+				for signatures in vec![1, 10, 100] {
+					let (call, ()) = setup.instance(&[(S, signatures)]).unwrap();
+					for (signer, signature) in &call.0 {
+						let account = signer.clone().into_account();
+						assert!(signature.verify(MESSAGE, &account));
+						assert!(!signature.verify(&b"another message"[..], &account));
+					}
+
+					VERIFIED.with(|verified| verified.set(0));
+					call.dispatch(()).unwrap();
+					assert_eq!(VERIFIED.with(|verified| verified.get()), signatures, "{:?}", scheme);
+				}
+			}
+		});
+	}
+
+	#[test]
+	fn seeds_are_numbered() {
+		assert_eq!(seed(0), b"//Benchmark//0".to_vec());
+		assert_eq!(seed(1024), b"//Benchmark//1024".to_vec());
+	}
+}
@@ -0,0 +1,173 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarking calls the way they are applied as part of a signed extrinsic.
+
+use sp_runtime::{DispatchError, DispatchResult, traits::{Dispatchable, SignedExtension}};
+use crate::{BenchmarkParameter, Wrap, Wrapped};
+
+/// A call dispatched the way a signed extrinsic is applied: prepared by the signed extensions
+/// `Extra` on behalf of the signer, dispatched with the signer as origin, and finished by the
+/// signed extensions again.
+///
+/// The measured cost thus includes the overhead of the signed extensions, e.g. checking the
+/// nonce or charging the fee.
+pub struct SignedCall<Call, Extra: SignedExtension> {
+	/// The call to dispatch.
+	pub call: Call,
+	/// The signer of the extrinsic.
+	pub who: Extra::AccountId,
+	/// The signed extensions of the extrinsic.
+	pub extra: Extra,
+	/// The dispatch info of the call.
+	pub info: Extra::DispatchInfo,
+	/// The length of the encoded extrinsic.
+	pub len: usize,
+}
+
+impl<Call, Extra, Origin> Dispatchable for SignedCall<Call, Extra>
+where
+	Call: Dispatchable<Origin = Origin>,
+	Extra: SignedExtension<Call = Call>,
+	Origin: From<Option<Extra::AccountId>>,
+{
+	/// The signer is part of the extrinsic, there is no separate origin.
+	type Origin = ();
+	type Trait = Call::Trait;
+
+	fn dispatch(self, _: ()) -> DispatchResult {
+		let pre = self.extra.pre_dispatch(&self.who, &self.call, self.info.clone(), self.len)
+			.map_err(|_| DispatchError::Other("Signed extrinsic is invalid"))?;
+		let result = self.call.dispatch(Origin::from(Some(self.who)));
+		Extra::post_dispatch(pre, self.info, self.len);
+		result
+	}
+}
+
+/// Signs the instances of a bare setup with a function building the signed extrinsic.
+pub struct Signing<Sign>(Sign);
+
+impl<Call, RawOrigin, Extra, Sign> Wrap<Call, RawOrigin, SignedCall<Call, Extra>, ()>
+	for Signing<Sign>
+where
+	Extra: SignedExtension,
+	Sign: Fn(Call, RawOrigin, &[(BenchmarkParameter, u32)])
+		-> Result<SignedCall<Call, Extra>, &'static str>,
+{
+	fn wrap(&self, call: Call, caller: RawOrigin, components: &[(BenchmarkParameter, u32)])
+		-> Result<(SignedCall<Call, Extra>, ()), &'static str>
+	{
+		Ok(((self.0)(call, caller, components)?, ()))
+	}
+}
+
+/// Benchmarks the calls of the bare setup `Setup` as part of a signed extrinsic, which `Sign`
+/// builds from the call and origin of every instance.
+///
+/// Everything but the instances is the same as for `Setup`.
+pub type Signed<Setup, Sign, Call, RawOrigin> = Wrapped<Setup, Signing<Sign>, Call, RawOrigin>;
+
+impl<Setup, Sign, Call, RawOrigin> Signed<Setup, Sign, Call, RawOrigin> {
+	/// Benchmark the calls of `setup` signed by `sign`.
+	pub fn new(setup: Setup, sign: Sign) -> Self {
+		Wrapped::with(setup, Signing(sign))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::{Encode, Decode};
+	use sp_runtime::transaction_validity::TransactionValidityError;
+	use std::sync::atomic::{AtomicU64, Ordering};
+	use crate::{BenchmarkingSetup, BenchmarkParameter::*};
+
+	// The signer checked by the signed extension, and the origin the call was dispatched by.
+	static CHECKED: AtomicU64 = AtomicU64::new(0);
+	static DISPATCHED: AtomicU64 = AtomicU64::new(0);
+
+	struct Remark;
+	impl Dispatchable for Remark {
+		type Origin = Option<u64>;
+		type Trait = ();
+		fn dispatch(self, origin: Self::Origin) -> DispatchResult {
+			DISPATCHED.store(origin.ok_or(DispatchError::BadOrigin)?, Ordering::SeqCst);
+			Ok(())
+		}
+	}
+
+	// Stands in for checking the nonce and charging the fee of the signer.
+	#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+	struct CheckSigner;
+	impl SignedExtension for CheckSigner {
+		const IDENTIFIER: &'static str = "CheckSigner";
+		type AccountId = u64;
+		type Call = Remark;
+		type AdditionalSigned = ();
+		type Pre = ();
+		type DispatchInfo = ();
+
+		fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+			Ok(())
+		}
+
+		fn pre_dispatch(self, who: &u64, _: &Remark, _: (), _: usize)
+			-> Result<(), TransactionValidityError>
+		{
+			CHECKED.store(*who, Ordering::SeqCst);
+			Ok(())
+		}
+	}
+
+	struct RemarkSetup;
+	impl BenchmarkingSetup<(), Remark, Option<u64>> for RemarkSetup {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			vec![(L, 0, 10)]
+		}
+
+		fn instance(&self, c: &[(BenchmarkParameter, u32)])
+			-> Result<(Remark, Option<u64>), &'static str>
+		{
+			Ok((Remark, Some(c[0].1 as u64).filter(|&who| who > 0)))
+		}
+	}
+
+	#[test]
+	fn signed_calls_include_the_signed_extensions() {
+		type SignedSetup = dyn BenchmarkingSetup<(), SignedCall<Remark, CheckSigner>, ()>;
+
+		let signed = Signed::<_, _, Remark, Option<u64>>::new(
+			RemarkSetup,
+			|call, caller: Option<u64>, _: &[(BenchmarkParameter, u32)]|
+				-> Result<SignedCall<Remark, CheckSigner>, &'static str>
+			{
+				let who = caller.ok_or("Remarks are signed")?;
+				Ok(SignedCall { call, who, extra: CheckSigner, info: (), len: 0 })
+			},
+		);
+		let signed: &SignedSetup = &signed;
+		assert_eq!(signed.components(), RemarkSetup.components());
+
+		// The signer is checked by the signed extension and dispatches the call.
+		let (call, ()) = signed.instance(&[(L, 10)]).unwrap();
+		assert_eq!(call.who, 10);
+		call.dispatch(()).unwrap();
+		assert_eq!(CHECKED.load(Ordering::SeqCst), 10);
+		assert_eq!(DISPATCHED.load(Ordering::SeqCst), 10);
+
+		assert_eq!(signed.instance(&[(L, 0)]).err(), Some("Remarks are signed"));
+	}
+}
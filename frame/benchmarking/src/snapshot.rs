@@ -0,0 +1,91 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Snapshots of the storage a benchmark dispatch failed with.
+
+use std::{fs, io, path::{Path, PathBuf}};
+use codec::{Encode, Decode};
+use crate::BenchmarkParameter;
+
+/// The key/value pairs of the storage at the time of a failure.
+pub type Snapshot = Vec<(Vec<u8>, Vec<u8>)>;
+
+sp_externalities::decl_extension! {
+	/// The directory snapshots of failed benchmark dispatches are written to.
+	///
+	/// No snapshots are taken unless this extension is registered.
+	pub struct SnapshotDir(PathBuf);
+}
+
+/// Returns the path in `dir` of the snapshot of benchmark `name` failing for `components`.
+pub fn snapshot_path(dir: &Path, name: &[u8], components: &[(BenchmarkParameter, u32)]) -> PathBuf {
+	let mut file_name = String::from_utf8_lossy(name).into_owned();
+	for (param, value) in components {
+		file_name.push_str(&format!("-{:?}{}", param, value));
+	}
+	file_name.push_str(".snapshot");
+	dir.join(file_name)
+}
+
+/// Write `snapshot` to `path`.
+pub fn write_snapshot(path: &Path, snapshot: &Snapshot) -> io::Result<()> {
+	fs::write(path, snapshot.encode())
+}
+
+/// Read the snapshot written to `path`.
+pub fn read_snapshot(path: &Path) -> io::Result<Snapshot> {
+	let encoded = fs::read(path)?;
+	Snapshot::decode(&mut &encoded[..])
+		.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{benchmarking, BenchmarkParameter::*};
+	use sp_io::TestExternalities;
+
+	#[test]
+	fn failure_snapshot_round_trips() {
+		let dir = tempfile::tempdir().unwrap();
+		let mut ext = TestExternalities::default();
+		ext.register_extension(SnapshotDir(dir.path().to_path_buf()));
+
+		ext.execute_with(|| {
+			sp_io::storage::set(b"alice", b"100");
+			sp_io::storage::set(b"bob", b"50");
+			benchmarking::snapshot_db(b"transfer", &vec![(U, 2), (E, 3)].encode());
+		});
+
+		let path = snapshot_path(dir.path(), b"transfer", &[(U, 2), (E, 3)]);
+		assert_eq!(path.file_name().unwrap(), "transfer-U2-E3.snapshot");
+
+		let snapshot = read_snapshot(&path).unwrap();
+		assert!(snapshot.contains(&(b"alice".to_vec(), b"100".to_vec())));
+		assert!(snapshot.contains(&(b"bob".to_vec(), b"50".to_vec())));
+
+		write_snapshot(&path, &snapshot).unwrap();
+		assert_eq!(read_snapshot(&path).unwrap(), snapshot);
+	}
+
+	#[test]
+	fn no_snapshot_without_a_directory() {
+		TestExternalities::default().execute_with(|| {
+			sp_io::storage::set(b"alice", b"100");
+			benchmarking::snapshot_db(b"transfer", &Vec::<(BenchmarkParameter, u32)>::new().encode());
+		});
+	}
+}
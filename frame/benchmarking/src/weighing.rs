@@ -0,0 +1,2120 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sweeping the components of a benchmark and measuring each assignment.
+
+use sp_std::{prelude::*, ops::Range};
+use core::time::Duration;
+use sp_runtime::traits::Dispatchable;
+use codec::Encode;
+use crate::{
+	BenchmarkParameter, BenchmarkRequest, BenchmarkResults, Benchmarking, BenchmarkingSetup,
+	AdaptiveRepeat, ComponentBound, ComponentStats, HOST_FUNCTIONS_UNAVAILABLE, OnTimeout,
+	RestPolicy, SKIP, host,
+};
+
+/// The error a benchmark is aborted with when an iteration takes longer than its timeout.
+const TIMED_OUT: &str = "Benchmark iteration timed out";
+
+/// The largest number of steps `run_benchmark_timed` sweeps a component in.
+const MAX_TIMED_STEPS: u32 = 100;
+
+/// Returns the component assignments measured by a sweep over `components`.
+///
+/// Each component is stepped from its low towards its high value in up to `steps` steps,
+/// while all other components are kept at the middle of their range.
+pub fn sweep_assignments(
+	components: &[(BenchmarkParameter, u32, u32)],
+	steps: u32,
+) -> Vec<Vec<(BenchmarkParameter, u32)>> {
+	let ranges: Vec<_> = components.iter()
+		.map(|(name, low, high)| (*name, ComponentBound::Value(*low), ComponentBound::Value(*high)))
+		.collect();
+	sweep_dependent_assignments(&ranges, steps).expect("constant ranges reference no component; qed")
+}
+
+/// Like [`sweep_assignments`], but the bounds of a range may reference the components declared
+/// before it, and are resolved against their current values for every assignment.
+///
+/// Returns an error if a range references a component which isn't declared before it.
+pub fn sweep_dependent_assignments(
+	ranges: &[(BenchmarkParameter, ComponentBound, ComponentBound)],
+	steps: u32,
+) -> Result<Vec<Vec<(BenchmarkParameter, u32)>>, &'static str> {
+	sweep_points_assignments(ranges, steps, &[])
+}
+
+/// Like [`sweep_dependent_assignments`], but the components in `points` are sampled exactly
+/// at the given values instead of being stepped through their range.
+///
+/// Returns an error if a point is outside the range of its component.
+pub fn sweep_points_assignments(
+	ranges: &[(BenchmarkParameter, ComponentBound, ComponentBound)],
+	steps: u32,
+	points: &[(BenchmarkParameter, Vec<u32>)],
+) -> Result<Vec<Vec<(BenchmarkParameter, u32)>>, &'static str> {
+	sweep_rest_assignments(ranges, steps, points, RestPolicy::Midpoint)
+}
+
+/// Like [`sweep_points_assignments`], but the components not being swept sit where `rest`
+/// says instead of at the middle of their range, e.g. at the worst case.
+pub fn sweep_rest_assignments(
+	ranges: &[(BenchmarkParameter, ComponentBound, ComponentBound)],
+	steps: u32,
+	points: &[(BenchmarkParameter, Vec<u32>)],
+	rest: RestPolicy,
+) -> Result<Vec<Vec<(BenchmarkParameter, u32)>>, &'static str> {
+	// Check all references up front, even those of ranges never swept.
+	assign_mid(ranges, &mut Vec::new())?;
+
+	let mut assignments = Vec::new();
+	// Select the component we will be benchmarking. Each component will be benchmarked.
+	for (i, (name, low, high)) in ranges.iter().enumerate() {
+		// Select the rest value for the components declared before it.
+		let mut before = Vec::new();
+		assign_rest(&ranges[..i], &mut before, rest)?;
+		let low = resolve(*low, &before)?;
+		let high = resolve(*high, &before)?;
+
+		let values = match points.iter().find(|p| p.0 == *name) {
+			Some((_, values)) => {
+				if values.iter().any(|v| *v < low || *v > high) {
+					return Err("Sample point is outside the range of its component");
+				}
+				values.clone()
+			},
+			None => {
+				// Create up to `STEPS` steps for that component between high and low.
+				let step_size = (high.saturating_sub(low) / steps).max(1);
+				let num_of_steps = high.saturating_sub(low) / step_size;
+				(0..num_of_steps).map(|s| low + step_size * s).collect()
+			},
+		};
+		for value in values {
+			// This is the value we will be testing for component `name`
+			let mut c = before.clone();
+			c.push((*name, value));
+
+			// Select the rest value for the components declared after it.
+			assign_rest(&ranges[i + 1..], &mut c, rest)?;
+			assignments.push(c);
+		}
+	}
+	Ok(assignments)
+}
+
+/// Returns the assignments of a sweep stepping both components of `pair` from their low
+/// towards their high value in lockstep, in up to `steps` steps.
+///
+/// All other components are kept at the middle of their range. Returns an error if a component
+/// of the pair isn't in `ranges`.
+pub fn diagonal_assignments(
+	ranges: &[(BenchmarkParameter, ComponentBound, ComponentBound)],
+	steps: u32,
+	pair: (BenchmarkParameter, BenchmarkParameter),
+) -> Result<Vec<Vec<(BenchmarkParameter, u32)>>, &'static str> {
+	if ranges.iter().filter(|r| r.0 == pair.0 || r.0 == pair.1).count() != 2 {
+		return Err("Paired components must be two distinct declared components");
+	}
+
+	let steps = steps.max(1);
+	let mut assignments: Vec<Vec<_>> = Vec::new();
+	for s in 0..steps {
+		let mut c = Vec::new();
+		for (name, low, high) in ranges {
+			let low = resolve(*low, &c)?;
+			let high = resolve(*high, &c)?;
+			let value = if *name == pair.0 || *name == pair.1 {
+				low + (high.saturating_sub(low) as u64 * s as u64 / steps as u64) as u32
+			} else {
+				high.saturating_sub(low) / 2 + low
+			};
+			c.push((*name, value));
+		}
+		// Short ranges repeat the same assignment for consecutive steps.
+		if assignments.last() != Some(&c) {
+			assignments.push(c);
+		}
+	}
+	Ok(assignments)
+}
+
+/// Resolve `bound` against the components already assigned in `assigned`.
+fn resolve(bound: ComponentBound, assigned: &[(BenchmarkParameter, u32)]) -> Result<u32, &'static str> {
+	match bound {
+		ComponentBound::Value(value) => Ok(value),
+		ComponentBound::Component(name) => assigned.iter()
+			.find(|c| c.0 == name)
+			.map(|c| c.1)
+			.ok_or("Component range references a component which isn't declared before it"),
+	}
+}
+
+/// Assign the mid value of their range to all components in `ranges`, after those in `assigned`.
+fn assign_mid(
+	ranges: &[(BenchmarkParameter, ComponentBound, ComponentBound)],
+	assigned: &mut Vec<(BenchmarkParameter, u32)>,
+) -> Result<(), &'static str> {
+	assign_rest(ranges, assigned, RestPolicy::Midpoint)
+}
+
+/// Assign the value of their range `rest` picks to all components in `ranges`, after those in
+/// `assigned`.
+fn assign_rest(
+	ranges: &[(BenchmarkParameter, ComponentBound, ComponentBound)],
+	assigned: &mut Vec<(BenchmarkParameter, u32)>,
+	rest: RestPolicy,
+) -> Result<(), &'static str> {
+	for (name, low, high) in ranges {
+		let low = resolve(*low, assigned)?;
+		let high = resolve(*high, assigned)?;
+		let value = match rest {
+			RestPolicy::Midpoint => high.saturating_sub(low) / 2 + low,
+			RestPolicy::Low => low,
+			RestPolicy::High => high,
+		};
+		assigned.push((*name, value));
+	}
+	Ok(())
+}
+
+/// Returns the assignment pinning every component in `ranges` at the high end of its range,
+/// which usually is the most expensive one to dispatch.
+pub fn worst_case_assignment(
+	ranges: &[(BenchmarkParameter, ComponentBound, ComponentBound)],
+) -> Result<Vec<(BenchmarkParameter, u32)>, &'static str> {
+	let mut c = Vec::new();
+	for (name, _, high) in ranges {
+		let high = resolve(*high, &c)?;
+		c.push((*name, high));
+	}
+	Ok(c)
+}
+
+/// Returns the range of the `len` assignments of a sweep handled by `worker` out of `workers`.
+///
+/// Every worker gets a contiguous chunk, so concatenating the results of all workers in
+/// worker order yields the same order as a serial sweep.
+pub fn worker_range(len: usize, worker: u32, workers: u32) -> Range<usize> {
+	let workers = workers.max(1) as usize;
+	let worker = worker as usize;
+	let start = (len * worker / workers).min(len);
+	let end = (len * (worker + 1) / workers).min(len);
+	start..end
+}
+
+/// Returns `ranges` with the components pinned by `fixed` narrowed to their fixed value.
+pub fn fix_components(
+	ranges: Vec<(BenchmarkParameter, ComponentBound, ComponentBound)>,
+	fixed: &[(BenchmarkParameter, u32)],
+) -> Vec<(BenchmarkParameter, ComponentBound, ComponentBound)> {
+	ranges.into_iter()
+		.map(|(name, low, high)| match fixed.iter().find(|f| f.0 == name) {
+			Some(&(_, value)) => (name, ComponentBound::Value(value), ComponentBound::Value(value)),
+			None => (name, low, high),
+		})
+		.collect()
+}
+
+/// Run the benchmark `setup` as described by `request`, measuring every assignment of its
+/// sweep `request.repeat` times.
+///
+/// Only the assignments of `request.worker` out of `request.workers` are measured, see
+/// [`worker_range`]. Components pinned by `request.fixed` are not swept, and if all of them
+/// are pinned the pinned assignment is measured on its own. Components given sample points by
+/// `request.points` are measured at exactly those values. While a component is swept, the
+/// others sit where `request.rest` says. The pairs of
+/// [`BenchmarkingSetup::paired_components`] are also swept along their diagonal.
+///
+/// An iteration taking longer than `request.timeout` is handled as `request.on_timeout` says.
+/// A dispatch can't be interrupted, so this only bounds the time spent on an assignment that
+/// turns out to be much slower than expected.
+///
+/// The instances are prepared with [`rng`] returning the stream of `request.seed`
+/// and `request.name`.
+pub fn run_benchmark<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	request: &BenchmarkRequest,
+) -> Result<Vec<BenchmarkResults>, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	collect_results(|sink| run_benchmark_streaming(setup, request, sink))
+}
+
+/// Like [`run_benchmark`], but hand every measurement to `sink` as soon as it is taken instead
+/// of collecting them.
+///
+/// This bounds the memory used by huge sweeps, e.g. when `sink` writes the results to disk.
+pub fn run_benchmark_streaming<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	request: &BenchmarkRequest,
+	sink: &mut dyn FnMut(BenchmarkResults),
+) -> Result<(), &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	ensure_host_functions()?;
+	crate::rng::start_run(request.seed, &request.name);
+	let db = prepare_db(setup, request)?;
+	let timer = Timer::HOST.calibrated();
+	frame_support::debug::native::debug!(
+		"Subtracting {} ns of timing overhead from every measurement of benchmark {}",
+		timer.overhead,
+		sp_std::str::from_utf8(&request.name).unwrap_or_default(),
+	);
+	measure_sweep(setup, request, db, timer, sink)
+}
+
+/// Returns the results `run` hands to its sink.
+fn collect_results<F>(run: F) -> Result<Vec<BenchmarkResults>, &'static str> where
+	F: FnOnce(&mut dyn FnMut(BenchmarkResults)) -> Result<(), &'static str>,
+{
+	let mut results: Vec<BenchmarkResults> = Vec::new();
+	run(&mut |result| results.push(result))?;
+	Ok(results)
+}
+
+/// Measure every assignment of the sweep of `setup` described by `request` against `db` with
+/// `timer`, handing the results to `sink`.
+fn measure_sweep<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	request: &BenchmarkRequest,
+	db: Db,
+	timer: Timer,
+	sink: &mut dyn FnMut(BenchmarkResults),
+) -> Result<(), &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	for c in sweep_plan(setup, request)? {
+		let timeout = (request.timeout, request.on_timeout);
+		let repeat = (request.repeat, request.adaptive);
+		measure(setup, (db, timer), &request.name, &c, repeat, timeout, sink)?;
+	}
+
+	Ok(())
+}
+
+/// Returns the assignments of the sweep of `setup` described by `request` this worker measures.
+fn sweep_plan<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	request: &BenchmarkRequest,
+) -> Result<Vec<Vec<(BenchmarkParameter, u32)>>, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+{
+	let ranges = fix_components(setup.component_ranges(), &request.fixed);
+	let mut assignments =
+		sweep_rest_assignments(&ranges, request.steps, &request.points, request.rest)?;
+	if assignments.is_empty() && !request.fixed.is_empty() {
+		let mut c = Vec::new();
+		assign_mid(&ranges, &mut c)?;
+		assignments.push(c);
+	}
+	for pair in setup.paired_components() {
+		assignments.extend(diagonal_assignments(&ranges, request.steps, pair)?);
+	}
+
+	let range = worker_range(assignments.len(), request.worker, request.workers);
+	Ok(assignments[range].to_vec())
+}
+
+/// Returns about how long the run `request` of `setup` takes, without running it.
+///
+/// The DB is warmed up as for the run and a single iteration of the first assignment of the
+/// sweep is timed, including its setup and every origin. The estimate is the warm-up plus that
+/// iteration for every assignment and repetition of the sweep, taking `request.steps` and
+/// `request.repeat` into account. With an adaptive repeat every assignment is assumed to need
+/// its `max_repeat` iterations, so the estimate is an upper bound.
+pub fn estimate_duration<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	request: &BenchmarkRequest,
+) -> Result<Duration, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	ensure_host_functions()?;
+	estimate_sweep(setup, request, Timer::HOST)
+}
+
+/// Like [`estimate_duration`], but timed by `timer`.
+fn estimate_sweep<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	request: &BenchmarkRequest,
+	timer: Timer,
+) -> Result<Duration, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	crate::rng::start_run(request.seed, &request.name);
+	let start = (timer.now)();
+	let db = prepare_db(setup, request)?;
+	let warm_up = (timer.now)().saturating_sub(start);
+
+	let plan = sweep_plan(setup, request)?;
+	let iteration = match plan.first() {
+		Some(c) => {
+			let timeout = (None, OnTimeout::Skip);
+			let start = (timer.now)();
+			measure(setup, (db, timer), &request.name, c, (1, None), timeout, &mut |_| ())?;
+			(timer.now)().saturating_sub(start)
+		},
+		None => 0,
+	};
+	let repeat = match request.adaptive {
+		None => request.repeat,
+		Some(adaptive) => request.repeat.max(2).max(adaptive.max_repeat),
+	};
+
+	let total = iteration
+		.saturating_mul(plan.len() as u128)
+		.saturating_mul(repeat as u128)
+		.saturating_add(warm_up);
+	Ok(Duration::from_nanos(total.min(u64::max_value() as u128) as u64))
+}
+
+/// Like [`run_benchmark`], but in debug builds first warn about the components `setup` appears
+/// to ignore, see [`unused_components`].
+///
+/// The sweep is also aborted before it starts if the high end of a component range can't be
+/// instanced, see [`unreachable_highs`], naming every such component in a warning.
+pub fn run_benchmark_checked<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	request: &BenchmarkRequest,
+) -> Result<Vec<BenchmarkResults>, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable + Encode,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	ensure_host_functions()?;
+
+	if cfg!(debug_assertions) {
+		let mut c = Vec::new();
+		assign_mid(&setup.component_ranges(), &mut c)?;
+		for name in unused_components(setup, &c, host::wipe_db)? {
+			frame_support::debug::native::warn!(
+				"Component {:?} of benchmark {} doesn't change the call, it appears to be unused.",
+				name,
+				sp_std::str::from_utf8(&request.name).unwrap_or_default(),
+			);
+		}
+	}
+
+	let unreachable = unreachable_highs(setup, host::wipe_db)?;
+	for (name, high, error) in &unreachable {
+		frame_support::debug::native::warn!(
+			"Component {:?} of benchmark {} can't be instanced at the high end {} of its range: {}",
+			name,
+			sp_std::str::from_utf8(&request.name).unwrap_or_default(),
+			high,
+			error,
+		);
+	}
+	if !unreachable.is_empty() {
+		return Err("High end of a component range can't be instanced");
+	}
+
+	run_benchmark(setup, request)
+}
+
+/// Returns the components `setup` fails to prepare an instance for at the high end of their
+/// range, along with that high end and the error of the instancer.
+///
+/// Every component is set to its high end in turn, the others being at the middle of their
+/// range. A sample skipped with [`SKIP`] counts as failed, as the worst case would silently be
+/// missing from the sweep. `reset` is called after every instance to undo its setup.
+pub fn unreachable_highs<T, Call, RawOrigin, Setup, Reset>(
+	setup: &Setup,
+	mut reset: Reset,
+) -> Result<Vec<(BenchmarkParameter, u32, &'static str)>, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Reset: FnMut(),
+{
+	let ranges = setup.component_ranges();
+	let mut unreachable = Vec::new();
+	for (i, (name, _, high)) in ranges.iter().enumerate() {
+		let mut c = Vec::new();
+		assign_mid(&ranges[..i], &mut c)?;
+		let high = resolve(*high, &c)?;
+		c.push((*name, high));
+		assign_mid(&ranges[i + 1..], &mut c)?;
+
+		let instance = setup.instance_with_origin(&c, 0);
+		reset();
+		if let Err(error) = instance {
+			unreachable.push((*name, high, error));
+		}
+	}
+	Ok(unreachable)
+}
+
+/// Returns the components the call `setup` prepares for the assignment `c` doesn't depend on.
+///
+/// Every component is set to the low and then to the high end of its range, and reported if
+/// the encoding of the call stays the same. Such a component is likely ignored by the
+/// instancer, which makes the benchmark flat in it. `reset` is called after every instance to
+/// undo its setup.
+pub fn unused_components<T, Call, RawOrigin, Setup, Reset>(
+	setup: &Setup,
+	c: &[(BenchmarkParameter, u32)],
+	mut reset: Reset,
+) -> Result<Vec<BenchmarkParameter>, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Encode,
+	Reset: FnMut(),
+{
+	let mut unused = Vec::new();
+	for (name, low, high) in setup.component_ranges() {
+		let low = resolve(low, c)?;
+		let high = resolve(high, c)?;
+		if low == high {
+			continue
+		}
+
+		let mut encode_with = |value| -> Result<Option<Vec<u8>>, &'static str> {
+			let perturbed: Vec<_> = c.iter()
+				.map(|&(n, v)| if n == name { (n, value) } else { (n, v) })
+				.collect();
+			let call = instance_or_skip(setup, &perturbed, 0)?.map(|(call, _)| call.encode());
+			reset();
+			Ok(call)
+		};
+		if let (Some(at_low), Some(at_high)) = (encode_with(low)?, encode_with(high)?) {
+			if at_low == at_high {
+				unused.push(name);
+			}
+		}
+	}
+	Ok(unused)
+}
+
+/// Like [`run_benchmark`], but also return the summary of the results of every assignment, as
+/// computed by [`summarize_results`] from the returned results.
+pub fn run_benchmark_with_stats<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	request: &BenchmarkRequest,
+) -> Result<(Vec<BenchmarkResults>, Vec<ComponentStats>), &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	let results = run_benchmark(setup, request)?;
+	let stats = summarize_results(&results);
+	Ok((results, stats))
+}
+
+/// Summarize the results of every assignment in `results`, in the order the assignments were
+/// first measured in.
+pub fn summarize_results(results: &[BenchmarkResults]) -> Vec<ComponentStats> {
+	let mut stats: Vec<(ComponentStats, u128)> = Vec::new();
+	for (components, time) in results {
+		match stats.iter_mut().find(|(s, _)| s.components == *components) {
+			Some((s, total)) => {
+				s.samples += 1;
+				s.min = s.min.min(*time);
+				s.max = s.max.max(*time);
+				*total = total.saturating_add(*time);
+			},
+			None => {
+				let s = ComponentStats {
+					components: components.clone(),
+					samples: 1,
+					min: *time,
+					max: *time,
+					mean: 0,
+				};
+				stats.push((s, *time));
+			},
+		}
+	}
+	stats.into_iter()
+		.map(|(s, total)| ComponentStats { mean: total / s.samples as u128, ..s })
+		.collect()
+}
+
+/// Combine the results of the shards of a benchmark into the results of the whole benchmark.
+///
+/// The results are sorted by their assignment, keeping the order in which each assignment was
+/// measured, so the order the shards are given in doesn't matter. Returns an error if the
+/// results don't all assign the same components, e.g. because the shards ran different
+/// versions of the benchmark. Pseudo-components, which only tag some results, are ignored.
+pub fn merge_results(shards: Vec<Vec<BenchmarkResults>>)
+	-> Result<Vec<BenchmarkResults>, &'static str>
+{
+	let mut merged: Vec<BenchmarkResults> = shards.into_iter().flatten().collect();
+
+	let params = |c: &[(BenchmarkParameter, u32)]| {
+		let mut params: Vec<_> = c.iter()
+			.map(|(param, _)| *param)
+			.filter(|param| !param.is_pseudo())
+			.collect();
+		params.sort();
+		params
+	};
+	if let Some((first, _)) = merged.first() {
+		let expected = params(first);
+		if merged.iter().any(|(c, _)| params(c) != expected) {
+			return Err("Shards of the benchmark assign different components");
+		}
+	}
+
+	merged.sort_by(|a, b| a.0.cmp(&b.0));
+	Ok(merged)
+}
+
+/// Run every benchmark of the pallet `B` as described by `request`, returning the results of
+/// each benchmark along with its name.
+///
+/// A benchmark named in `repeats` is measured the given number of times instead of
+/// `request.repeat`, which only changes the recorded runs and not the warm-up.
+pub fn run_all_benchmarks<T, B: Benchmarking<T>>(
+	request: &BenchmarkRequest,
+	repeats: &[(Vec<u8>, u32)],
+) -> Result<Vec<(Vec<u8>, Vec<T>)>, &'static str> {
+	B::benchmarks().into_iter().map(|benchmark| {
+		let repeat = repeats.iter()
+			.find(|(name, _)| *name == benchmark.name)
+			.map_or(request.repeat, |(_, repeat)| *repeat);
+		let request = BenchmarkRequest { name: benchmark.name.clone(), repeat, ..request.clone() };
+		Ok((benchmark.name, B::run_benchmark(&request)?))
+	}).collect()
+}
+
+/// Run the benchmark `setup`, picking `steps` and `repeat` so the whole run takes about `target`.
+///
+/// A single iteration per component is measured first to estimate the cost of an iteration.
+/// The high end of every component range is always measured, in addition to the sweep.
+pub fn run_benchmark_timed<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	target: Duration,
+) -> Result<Vec<BenchmarkResults>, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	ensure_host_functions()?;
+	let ranges = setup.component_ranges();
+
+	let start = host::current_time();
+	let probe = run_benchmark(setup, &BenchmarkRequest { steps: 1, repeat: 1, ..Default::default() })?;
+	let elapsed = host::current_time() - start;
+	let iteration_cost = elapsed / probe.len().max(1) as u128;
+
+	let (steps, repeat) = timed_parameters(ranges.len() as u32, iteration_cost, target);
+	let timer = Timer::HOST.calibrated();
+	let mut results = run_benchmark(setup, &BenchmarkRequest { steps, repeat, ..Default::default() })?;
+
+	// The sweep stops short of the high end of each range, so measure it explicitly.
+	for (i, (name, low, high)) in ranges.iter().enumerate() {
+		let mut c = Vec::new();
+		assign_mid(&ranges[..i], &mut c)?;
+		let low = resolve(*low, &c)?;
+		let high = resolve(*high, &c)?;
+		if low < high {
+			c.push((*name, high));
+			assign_mid(&ranges[i + 1..], &mut c)?;
+			let timeout = (None, OnTimeout::Skip);
+			let env = (Db::HOST, timer);
+			let repeat = (repeat, None);
+			measure(setup, env, &[], &c, repeat, timeout, &mut |result| results.push(result))?;
+		}
+	}
+
+	Ok(results)
+}
+
+/// Make sure the benchmarking host functions can be called.
+///
+/// Without the `runtime-benchmarks` feature they are never called, so a runtime built without
+/// it doesn't import them. The `mock-host` feature provides them in-crate.
+fn ensure_host_functions() -> Result<(), &'static str> {
+	if cfg!(any(feature = "runtime-benchmarks", feature = "mock-host")) {
+		Ok(())
+	} else {
+		Err(HOST_FUNCTIONS_UNAVAILABLE)
+	}
+}
+
+/// Returns the `(steps, repeat)` for a run of a benchmark with `components` components taking
+/// about `target`, when one iteration costs `iteration_cost` nanoseconds.
+///
+/// At least two steps and one repetition are always taken.
+pub fn timed_parameters(components: u32, iteration_cost: u128, target: Duration) -> (u32, u32) {
+	let components = components.max(1) as u128;
+	let iterations = target.as_nanos() / iteration_cost.max(1);
+	let steps = (iterations / components).max(2).min(MAX_TIMED_STEPS as u128);
+	let repeat = (iterations / (components * steps)).max(1).min(u32::max_value() as u128);
+	(steps as u32, repeat as u32)
+}
+
+/// Call `f` with every index in `0..count`.
+///
+/// This is the canonical way for a setup to pre-seed state whose size is given by a component,
+/// e.g. to insert `count` entries into a storage map.
+pub fn fill_storage_map<F: FnMut(u32)>(count: u32, mut f: F) {
+	for i in 0..count {
+		f(i);
+	}
+}
+
+/// Like [`fill_storage_map`], but commit the storage changes to the database after every
+/// `commit_every` calls of `f`, bounding the memory used by large fills.
+pub fn fill_storage_map_committing<F: FnMut(u32)>(count: u32, commit_every: u32, mut f: F) {
+	let commit_every = commit_every.max(1);
+	fill_storage_map(count, |i| {
+		f(i);
+		if (i + 1) % commit_every == 0 {
+			host::commit_db();
+		}
+	});
+}
+
+/// The calls keeping the DB in the state the iterations of a benchmark expect.
+#[derive(Clone, Copy)]
+struct Db {
+	/// Commit the storage changes to the DB, flushing its cache.
+	commit: fn(),
+	/// Reset the DB to the genesis state.
+	wipe: fn(),
+	/// Let the host settle after an iteration was wiped, before the next one is prepared.
+	settle: fn(),
+}
+
+impl Db {
+	/// The benchmarking DB of the host.
+	const HOST: Db = Db { commit: host::commit_db, wipe: host::wipe_db, settle: leave_db };
+	/// Leaves the DB alone, for benchmarks not accessing storage.
+	const UNUSED: Db = Db { commit: leave_db, wipe: leave_db, settle: leave_db };
+}
+
+fn leave_db() {}
+
+/// The number of times the overhead of reading the clock is measured, the fastest being taken.
+const TIMER_CALIBRATION_SAMPLES: u32 = 100;
+
+/// The clock the iterations of a benchmark are timed with.
+#[derive(Clone, Copy)]
+struct Timer {
+	/// Returns the current time in nanoseconds.
+	now: fn() -> u128,
+	/// The time taken by reading the clock twice, subtracted from every measurement.
+	overhead: u128,
+}
+
+impl Timer {
+	/// The clock of the host, with no overhead subtracted.
+	const HOST: Timer = Timer { now: host::current_time, overhead: 0 };
+
+	/// Returns this clock with the overhead of reading it twice measured and thus subtracted.
+	///
+	/// Otherwise every measurement includes the cost of the two reads, which biases the weights
+	/// of very fast dispatches upwards.
+	fn calibrated(self) -> Timer {
+		let overhead = (0..TIMER_CALIBRATION_SAMPLES)
+			.map(|_| {
+				let start = (self.now)();
+				(self.now)().saturating_sub(start)
+			})
+			.min()
+			.unwrap_or(0);
+		Timer { overhead, ..self }
+	}
+
+	/// Returns the time passed from `start` to `finish`, as read from this clock, without the
+	/// overhead of reading it.
+	fn elapsed(&self, start: u128, finish: u128) -> u128 {
+		finish.saturating_sub(start).saturating_sub(self.overhead)
+	}
+}
+
+/// Prepare the DB for the run `request` of `setup`, returning how the iterations treat it.
+///
+/// A warm snapshot is loaded into the storage and left alone by the iterations, otherwise the
+/// DB is warmed up. The host is let settle between the iterations if `request.settle` is set.
+fn prepare_db<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	request: &BenchmarkRequest,
+) -> Result<Db, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+{
+	let db = if let Some(path) = &request.warm_snapshot {
+		if !host::load_snapshot(path) {
+			return Err("Failed to load the warm state snapshot");
+		}
+		Db::UNUSED
+	} else {
+		// Warm up the DB
+		if setup.uses_storage() {
+			host::commit_db();
+			host::wipe_db();
+		}
+		Db::HOST
+	};
+	Ok(if request.settle { Db { settle: host::settle, ..db } } else { db })
+}
+
+/// Measure `setup` with the components set to `c`, `repeat` times, or as often as the
+/// adaptive repeat, if any, asks for, see [`repeat_again`].
+///
+/// Every iteration commits to and wipes `db`, unless `setup` doesn't use storage, and is timed
+/// by `timer`. After every iteration the host settles as `db` says. If a dispatch fails, a
+/// snapshot of the storage is taken under the benchmark `name`.
+///
+/// Every measurement is handed to `sink` as soon as it is taken.
+fn measure<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	(db, timer): (Db, Timer),
+	name: &[u8],
+	c: &[(BenchmarkParameter, u32)],
+	repeat: (u32, Option<AdaptiveRepeat>),
+	(timeout, on_timeout): (Option<u64>, OnTimeout),
+	sink: &mut dyn FnMut(BenchmarkResults),
+) -> Result<(), &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	let db = if setup.uses_storage() { db } else { Db { settle: db.settle, ..Db::UNUSED } };
+	for (tagged, origin) in origin_assignments::<T, Call, RawOrigin, _>(setup, c) {
+		// Run the benchmark `repeat` times, or until its mean is stable.
+		let mut times = Vec::new();
+		while repeat_again(repeat, &times) {
+			// Leave the DB clean for the next call even if the setup or dispatch panics.
+			#[cfg(feature = "std")]
+			let _wipe = WipeOnPanic(db.wipe);
+			// Set up the externalities environment for the setup we want to benchmark.
+			let (call, caller) = match prepare_instance(setup, c, origin)? {
+				Some(instance) => instance,
+				None => {
+					// Wipe what the setup did before bailing out.
+					(db.wipe)();
+					break
+				},
+			};
+			// Commit the externalities to the database, flushing the DB cache.
+			// This will enable worst case scenario for reading from the database.
+			(db.commit)();
+			// Run the benchmark.
+			let start = (timer.now)();
+			let outcome = call.dispatch(caller.into());
+			let finish = (timer.now)();
+			if let Err(e) = outcome {
+				// Keep the state the dispatch failed with, so the failure can be reproduced.
+				host::snapshot_db(name, &c.encode());
+				return Err(e.into());
+			}
+			let elapsed = timer.elapsed(start, finish);
+			let within_timeout = check_timeout(elapsed, timeout, on_timeout);
+			if within_timeout != Ok(true) {
+				// Leave the DB as it would be after a completed iteration.
+				(db.wipe)();
+				within_timeout?;
+				break
+			}
+			times.push(elapsed);
+			if setup.cache_timing() {
+				let mut cold = tagged.clone();
+				cold.push((BenchmarkParameter::Cache, 0));
+				sink((cold, elapsed));
+				if let Some(elapsed) = measure_warm(setup, (db, timer), c, origin)? {
+					let mut warm = tagged.clone();
+					warm.push((BenchmarkParameter::Cache, 1));
+					sink((warm, elapsed));
+				}
+			} else {
+				sink((tagged.clone(), elapsed));
+			}
+			// Wipe the DB back to the genesis state.
+			(db.wipe)();
+
+			if setup.read_only_timing() {
+				if let Some(elapsed) = measure_read_only(setup, (db, timer), c, origin)? {
+					let mut read_only = tagged.clone();
+					read_only.push((BenchmarkParameter::ReadOnly, 1));
+					sink((read_only, elapsed));
+				}
+			}
+			(db.settle)();
+		}
+	}
+	Ok(())
+}
+
+/// Returns whether to run another iteration of an assignment whose iterations so far took
+/// `times`, when it is measured `repeat` times.
+///
+/// An adaptive repeat measures the assignment at least `repeat` times, and at least twice, until
+/// the relative standard error of the mean is at most its threshold, or it was measured its
+/// `max_repeat` times.
+fn repeat_again((repeat, adaptive): (u32, Option<AdaptiveRepeat>), times: &[u128]) -> bool {
+	let done = times.len() as u32;
+	match adaptive {
+		None => done < repeat,
+		Some(adaptive) => done < repeat.max(2)
+			|| (done < adaptive.max_repeat && !mean_is_stable(times, adaptive.max_error_permille)),
+	}
+}
+
+/// Returns whether the relative standard error of the mean of `times` is at most
+/// `max_error_permille` parts per thousand.
+fn mean_is_stable(times: &[u128], max_error_permille: u32) -> bool {
+	let n = times.len() as u128;
+	if n < 2 {
+		return false;
+	}
+	let mean = times.iter().fold(0u128, |sum, t| sum.saturating_add(*t)) / n;
+	let variance = times.iter()
+		.map(|t| {
+			let deviation = if *t > mean { t - mean } else { mean - t };
+			deviation.saturating_mul(deviation)
+		})
+		.fold(0u128, |sum, d| sum.saturating_add(d)) / (n - 1);
+	// The standard error is `sqrt(variance / n)`, so compare the squares to avoid the root.
+	let max_error = max_error_permille as u128;
+	let bound = max_error.saturating_mul(max_error).saturating_mul(mean.saturating_mul(mean));
+	variance.saturating_mul(1_000_000) <= bound.saturating_mul(n)
+}
+
+/// Time the dispatch `setup` prepares for `c` and the `origin`-th origin without committing
+/// its setup first, so it runs with a warm DB cache.
+///
+/// `db` is wiped first, so the dispatch doesn't see the changes of the one just timed. Returns
+/// `None` if the setup asked for the sample to be skipped with [`SKIP`], or if the dispatch
+/// failed.
+fn measure_warm<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	(db, timer): (Db, Timer),
+	c: &[(BenchmarkParameter, u32)],
+	origin: u32,
+) -> Result<Option<u128>, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	(db.wipe)();
+	let (call, caller) = match prepare_instance(setup, c, origin) {
+		Ok(Some(instance)) => instance,
+		Ok(None) => return Ok(None),
+		Err(e) => {
+			(db.wipe)();
+			return Err(e)
+		},
+	};
+	let start = (timer.now)();
+	let outcome = call.dispatch(caller.into());
+	let finish = (timer.now)();
+	Ok(outcome.ok().map(|_| timer.elapsed(start, finish)))
+}
+
+/// Time the dispatch `setup` prepares for `c` and the `origin`-th origin with its storage
+/// changes dropped.
+///
+/// Returns `None` if the setup asked for the sample to be skipped with [`SKIP`].
+fn measure_read_only<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	(db, timer): (Db, Timer),
+	c: &[(BenchmarkParameter, u32)],
+	origin: u32,
+) -> Result<Option<u128>, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	let (call, caller) = match prepare_instance(setup, c, origin)? {
+		Some(instance) => instance,
+		None => {
+			(db.wipe)();
+			return Ok(None)
+		},
+	};
+	(db.commit)();
+	let read_only = ReadOnlyDb::set();
+	let start = (timer.now)();
+	// The dispatch may fail on reading back what it couldn't write, so its outcome is ignored.
+	let _ = call.dispatch(caller.into());
+	let finish = (timer.now)();
+	drop(read_only);
+	(db.wipe)();
+	Ok(Some(timer.elapsed(start, finish)))
+}
+
+/// Keeps the DB read-only until dropped, even when unwinding from a panic.
+struct ReadOnlyDb;
+
+impl ReadOnlyDb {
+	fn set() -> Self {
+		host::set_read_only_db(true);
+		ReadOnlyDb
+	}
+}
+
+impl Drop for ReadOnlyDb {
+	fn drop(&mut self) {
+		host::set_read_only_db(false);
+	}
+}
+
+/// Calls the wipe function it holds when dropped while unwinding from a panic.
+///
+/// Without `std` a panic aborts, so there is nothing left to clean up.
+#[cfg(feature = "std")]
+struct WipeOnPanic<F: FnMut()>(F);
+
+#[cfg(feature = "std")]
+impl<F: FnMut()> Drop for WipeOnPanic<F> {
+	fn drop(&mut self) {
+		if std::thread::panicking() {
+			(self.0)();
+		}
+	}
+}
+
+/// Returns whether an iteration taking `elapsed` nanoseconds completed within `timeout`, or an
+/// error if it didn't and `on_timeout` says to abort.
+fn check_timeout(
+	elapsed: u128,
+	timeout: Option<u64>,
+	on_timeout: OnTimeout,
+) -> Result<bool, &'static str> {
+	match timeout {
+		Some(timeout) if elapsed > timeout as u128 => match on_timeout {
+			OnTimeout::Skip => Ok(false),
+			OnTimeout::Abort => Err(TIMED_OUT),
+		},
+		_ => Ok(true),
+	}
+}
+
+/// Set up `setup` for the assignment `c` and the `origin`-th origin.
+///
+/// Returns `None` if the setup asked for the sample to be skipped with [`SKIP`].
+pub fn instance_or_skip<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	c: &[(BenchmarkParameter, u32)],
+	origin: u32,
+) -> Result<Option<(Call, RawOrigin)>, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+{
+	match setup.instance_with_origin(c, origin) {
+		Ok(instance) => Ok(Some(instance)),
+		Err(e) if e == SKIP => Ok(None),
+		Err(e) => Err(e),
+	}
+}
+
+/// Like [`instance_or_skip`], but also dispatch the prerequisites of the instance, see
+/// [`BenchmarkingSetup::prerequisites`].
+fn prepare_instance<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	c: &[(BenchmarkParameter, u32)],
+	origin: u32,
+) -> Result<Option<(Call, RawOrigin)>, &'static str>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	Call: Dispatchable,
+	RawOrigin: Into<<Call as Dispatchable>::Origin>,
+{
+	let instance = match instance_or_skip(setup, c, origin)? {
+		Some(instance) => instance,
+		None => return Ok(None),
+	};
+	for (call, caller) in setup.prerequisites(c)? {
+		call.dispatch(caller.into()).map_err(|_| "Prerequisite call of the benchmark failed")?;
+	}
+	Ok(Some(instance))
+}
+
+/// Returns the assignments the results of measuring `c` are recorded under, each along with
+/// the index of the origin to dispatch with.
+///
+/// When `setup` declares more than one origin, every origin is measured separately and its
+/// results are tagged with the `BenchmarkParameter::Origin` pseudo-component.
+pub fn origin_assignments<T, Call, RawOrigin, Setup>(
+	setup: &Setup,
+	c: &[(BenchmarkParameter, u32)],
+) -> Vec<(Vec<(BenchmarkParameter, u32)>, u32)>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+{
+	let origins = setup.origins();
+	if origins <= 1 {
+		return vec![(c.to_vec(), 0)];
+	}
+
+	(0..origins).map(|origin| {
+		let mut tagged = c.to_vec();
+		tagged.push((BenchmarkParameter::Origin, origin));
+		(tagged, origin)
+	}).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{BenchmarkParameter::*, ComponentBound::*};
+
+	// Does nothing, for tests of what is measured rather than of the measurements.
+	struct Noop;
+	impl Dispatchable for Noop {
+		type Origin = ();
+		type Trait = ();
+		fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+			Ok(())
+		}
+	}
+
+	// Sets up a `Noop` with the given components.
+	struct NoopSetup(Vec<(BenchmarkParameter, u32, u32)>);
+	impl BenchmarkingSetup<(), Noop, ()> for NoopSetup {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			self.0.clone()
+		}
+
+		fn instance(&self, _: &[(BenchmarkParameter, u32)]) -> Result<(Noop, ()), &'static str> {
+			Ok((Noop, ()))
+		}
+	}
+
+	thread_local! {
+		// The time of the fake clock in nanoseconds, and how long reading it takes.
+		static CLOCK: core::cell::Cell<(u128, u128)> = core::cell::Cell::new((0, 0));
+	}
+
+	// Returns a timer reading the fake clock, reset to zero, every reading taking `read_cost`.
+	fn fake_timer(read_cost: u128) -> Timer {
+		CLOCK.with(|clock| clock.set((0, read_cost)));
+		Timer { now: read_clock, overhead: 0 }
+	}
+
+	fn read_clock() -> u128 {
+		CLOCK.with(|clock| {
+			let (now, read_cost) = clock.get();
+			clock.set((now + read_cost, read_cost));
+			now + read_cost
+		})
+	}
+
+	fn advance_clock(by: u128) {
+		CLOCK.with(|clock| {
+			let (now, read_cost) = clock.get();
+			clock.set((now + by, read_cost));
+		})
+	}
+
+	// Takes 100 ns on the fake clock.
+	struct Tick;
+	impl Dispatchable for Tick {
+		type Origin = ();
+		type Trait = ();
+		fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+			advance_clock(100);
+			Ok(())
+		}
+	}
+
+	// Sets up a `Tick` in the given number of nanoseconds on the fake clock, without storage.
+	struct TickSetup(u128);
+	impl BenchmarkingSetup<(), Tick, ()> for TickSetup {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			vec![(A, 0, 10)]
+		}
+
+		fn uses_storage(&self) -> bool {
+			false
+		}
+
+		fn instance(&self, _: &[(BenchmarkParameter, u32)]) -> Result<(Tick, ()), &'static str> {
+			advance_clock(self.0);
+			Ok((Tick, ()))
+		}
+	}
+
+	#[test]
+	fn workers_cover_the_serial_sweep() {
+		let components = vec![(A, 0, 100), (B, 1, 17), (C, 5, 5)];
+		let serial = sweep_assignments(&components, 10);
+		assert!(!serial.is_empty());
+
+		for workers in 1..8 {
+			let parallel: Vec<_> = (0..workers)
+				.flat_map(|w| serial[worker_range(serial.len(), w, workers)].to_vec())
+				.collect();
+			assert_eq!(parallel, serial, "workers = {}", workers);
+		}
+	}
+
+	#[test]
+	fn fixed_components_are_not_swept() {
+		let ranges = vec![(A, Value(0), Value(100)), (B, Value(0), Value(10))];
+		let ranges = fix_components(ranges, &[(B, 7)]);
+		assert_eq!(ranges, vec![(A, Value(0), Value(100)), (B, Value(7), Value(7))]);
+
+		let assignments = sweep_dependent_assignments(&ranges, 10).unwrap();
+		assert_eq!(assignments.len(), 10);
+		assert!(assignments.iter().all(|c| c[1] == (B, 7)));
+	}
+
+	#[test]
+	fn dependent_ranges_follow_the_referenced_component() {
+		// `b in 0 .. a`
+		let ranges = [(A, Value(0), Value(100)), (B, Value(0), Component(A))];
+		let assignments = sweep_dependent_assignments(&ranges, 10).unwrap();
+
+		assert!(assignments.iter().any(|c| c[0].0 == A && c[1].1 > 0));
+		for c in &assignments {
+			assert_eq!((c[0].0, c[1].0), (A, B));
+			assert!(c[1].1 <= c[0].1, "{:?}", c);
+		}
+	}
+
+	#[test]
+	fn explicit_points_replace_the_sweep_of_their_component() {
+		let ranges = [(A, Value(0), Value(100)), (B, Value(0), Value(10))];
+		let points = [(A, vec![1, 2, 4, 64])];
+		let assignments = sweep_points_assignments(&ranges, 5, &points).unwrap();
+
+		let values_where = |fixed, swept: usize| -> Vec<u32> {
+			assignments.iter().filter(|c| c[1 - swept] == fixed).map(|c| c[swept].1).collect()
+		};
+		assert_eq!(values_where((B, 5), 0), vec![1, 2, 4, 64]);
+		assert_eq!(values_where((A, 50), 1), vec![0, 2, 4, 6, 8]);
+		assert_eq!(assignments.len(), 9);
+
+		assert!(sweep_points_assignments(&ranges, 5, &[(B, vec![3, 11])]).is_err());
+	}
+
+	#[test]
+	fn paired_components_vary_together() {
+		let ranges = [(A, Value(0), Value(100)), (B, Value(10), Value(20)), (C, Value(0), Value(8))];
+		let assignments = diagonal_assignments(&ranges, 5, (A, B)).unwrap();
+
+		assert_eq!(assignments, vec![
+			vec![(A, 0), (B, 10), (C, 4)],
+			vec![(A, 20), (B, 12), (C, 4)],
+			vec![(A, 40), (B, 14), (C, 4)],
+			vec![(A, 60), (B, 16), (C, 4)],
+			vec![(A, 80), (B, 18), (C, 4)],
+		]);
+		assert!(assignments.windows(2).all(|w| w[0][0].1 < w[1][0].1 && w[0][1].1 < w[1][1].1));
+
+		assert!(diagonal_assignments(&ranges, 5, (A, A)).is_err());
+		assert!(diagonal_assignments(&ranges, 5, (A, D)).is_err());
+	}
+
+	#[test]
+	fn ignored_components_are_reported() {
+		#[derive(Encode)]
+		struct Fill(u32);
+
+		// Uses `A` but forgets about `B`.
+		struct ForgetfulFill;
+		impl BenchmarkingSetup<(), Fill, ()> for ForgetfulFill {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 0, 10), (B, 0, 10), (C, 5, 5)]
+			}
+
+			fn instance(&self, c: &[(BenchmarkParameter, u32)]) -> Result<(Fill, ()), &'static str> {
+				Ok((Fill(c[0].1), ()))
+			}
+		}
+
+		let mut resets = 0;
+		let c = [(A, 5), (B, 5), (C, 5)];
+		let unused = unused_components::<(), Fill, (), _, _>(&ForgetfulFill, &c, || resets += 1);
+		assert_eq!(unused, Ok(vec![B]));
+		assert_eq!(resets, 4);
+	}
+
+	#[test]
+	fn summaries_match_the_raw_results() {
+		let results: Vec<BenchmarkResults> = vec![
+			(vec![(A, 1)], 100),
+			(vec![(A, 2)], 210),
+			(vec![(A, 1)], 120),
+			(vec![(A, 2)], 190),
+			(vec![(A, 1)], 111),
+		];
+		let stats = summarize_results(&results);
+
+		assert_eq!(stats, vec![
+			ComponentStats { components: vec![(A, 1)], samples: 3, min: 100, max: 120, mean: 110 },
+			ComponentStats { components: vec![(A, 2)], samples: 2, min: 190, max: 210, mean: 200 },
+		]);
+		for s in &stats {
+			let times: Vec<u128> = results.iter()
+				.filter(|r| r.0 == s.components)
+				.map(|r| r.1)
+				.collect();
+			assert_eq!(s.samples as usize, times.len());
+			assert_eq!(Some(&s.min), times.iter().min());
+			assert_eq!(Some(&s.max), times.iter().max());
+		}
+	}
+
+	#[test]
+	fn worst_case_pins_every_component_at_its_high() {
+		let ranges = [(A, Value(0), Value(100)), (B, Value(1), Component(A)), (C, Value(5), Value(5))];
+		assert_eq!(worst_case_assignment(&ranges).unwrap(), vec![(A, 100), (B, 100), (C, 5)]);
+	}
+
+	#[test]
+	fn references_to_later_components_are_an_error() {
+		let ranges = [(B, Value(0), Component(A)), (A, Value(0), Value(100))];
+		assert!(sweep_dependent_assignments(&ranges, 10).is_err());
+	}
+
+	struct RecordRepeat;
+	impl Benchmarking<u32> for RecordRepeat {
+		fn run_benchmark(request: &BenchmarkRequest) -> Result<Vec<u32>, &'static str> {
+			Ok(vec![request.repeat])
+		}
+
+		fn benchmarks() -> Vec<crate::BenchmarkMetadata> {
+			vec![b"cheap".to_vec(), b"noisy".to_vec()].into_iter()
+				.map(|name| crate::BenchmarkMetadata { name, components: vec![], units: vec![] })
+				.collect()
+		}
+	}
+
+	#[test]
+	fn repeat_overrides_only_apply_to_their_benchmark() {
+		let request = BenchmarkRequest { steps: 10, repeat: 5, ..Default::default() };
+		let results = run_all_benchmarks::<u32, RecordRepeat>(&request, &[(b"noisy".to_vec(), 50)]);
+
+		assert_eq!(results.unwrap(), vec![
+			(b"cheap".to_vec(), vec![5]),
+			(b"noisy".to_vec(), vec![50]),
+		]);
+	}
+
+	#[test]
+	fn slow_iterations_hit_the_timeout() {
+		use core::sync::atomic::{AtomicU32, Ordering};
+
+		// A clock advancing by 10ms on every reading, around a dispatch taking `A` times 10ms.
+		static WIPES: AtomicU32 = AtomicU32::new(0);
+		fn wipe() {
+			WIPES.fetch_add(1, Ordering::SeqCst);
+		}
+
+		struct Work { a: u32 }
+		impl Dispatchable for Work {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+				advance_clock(self.a as u128 * 10_000_000);
+				Ok(())
+			}
+		}
+
+		struct WorkSetup;
+		impl BenchmarkingSetup<(), Work, ()> for WorkSetup {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 0, 10)]
+			}
+
+			fn instance(&self, c: &[(BenchmarkParameter, u32)]) -> Result<(Work, ()), &'static str> {
+				Ok((Work { a: c[0].1 }, ()))
+			}
+		}
+
+		let measured = |a, timeout| {
+			let mut results = Vec::new();
+			let sink: &mut dyn FnMut(BenchmarkResults) = &mut |r| results.push(r);
+			let env = (Db { commit: leave_db, wipe, settle: leave_db }, fake_timer(10_000_000));
+			measure::<(), Work, (), _>(
+				&WorkSetup, env, b"work", &[(A, a)], (2, None), timeout, sink,
+			).map(|()| results.into_iter().map(|(_, elapsed)| elapsed).collect::<Vec<_>>())
+		};
+
+		// Fast iterations are all measured, and wiped after.
+		assert_eq!(measured(1, (Some(20_000_000), OnTimeout::Skip)), Ok(vec![20_000_000; 2]));
+		assert_eq!(WIPES.load(Ordering::SeqCst), 2);
+		assert_eq!(measured(5, (None, OnTimeout::Abort)), Ok(vec![60_000_000; 2]));
+		assert_eq!(WIPES.load(Ordering::SeqCst), 4);
+
+		// A slow iteration is dropped along with the rest of the assignment, but still wiped.
+		assert_eq!(measured(5, (Some(20_000_000), OnTimeout::Skip)), Ok(vec![]));
+		assert_eq!(WIPES.load(Ordering::SeqCst), 5);
+		assert_eq!(measured(5, (Some(20_000_000), OnTimeout::Abort)), Err(TIMED_OUT));
+		assert_eq!(WIPES.load(Ordering::SeqCst), 6);
+	}
+
+	struct SingleOrigin;
+	impl BenchmarkingSetup<(), (), &'static str> for SingleOrigin {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			vec![(A, 0, 10)]
+		}
+
+		fn instance(&self, _: &[(BenchmarkParameter, u32)]) -> Result<((), &'static str), &'static str> {
+			Ok(((), "signed"))
+		}
+	}
+
+	struct TwoOrigins;
+	impl BenchmarkingSetup<(), (), &'static str> for TwoOrigins {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			vec![(A, 0, 10)]
+		}
+
+		fn instance(&self, c: &[(BenchmarkParameter, u32)]) -> Result<((), &'static str), &'static str> {
+			self.instance_with_origin(c, 0)
+		}
+
+		fn origins(&self) -> u32 {
+			2
+		}
+
+		fn instance_with_origin(&self, _: &[(BenchmarkParameter, u32)], origin: u32)
+			-> Result<((), &'static str), &'static str>
+		{
+			Ok(((), ["root", "signed"][origin as usize]))
+		}
+	}
+
+	#[test]
+	fn single_origin_results_are_not_tagged() {
+		let c = [(A, 3)];
+		assert_eq!(
+			origin_assignments::<(), (), &'static str, _>(&SingleOrigin, &c),
+			vec![(c.to_vec(), 0)],
+		);
+	}
+
+	#[test]
+	fn every_declared_origin_is_measured() {
+		let c = [(A, 3)];
+		let assignments = origin_assignments::<(), (), &'static str, _>(&TwoOrigins, &c);
+		assert_eq!(assignments, vec![
+			(vec![(A, 3), (Origin, 0)], 0),
+			(vec![(A, 3), (Origin, 1)], 1),
+		]);
+
+		let origins: Vec<_> = assignments.iter()
+			.map(|(_, origin)| TwoOrigins.instance_with_origin(&c, *origin).unwrap().1)
+			.collect();
+		assert_eq!(origins, vec!["root", "signed"]);
+	}
+
+	// Can only reach values of `A` up to 5, and fails for 8.
+	struct Bounded;
+	impl BenchmarkingSetup<(), (), ()> for Bounded {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			vec![(A, 0, 10)]
+		}
+
+		fn instance(&self, c: &[(BenchmarkParameter, u32)]) -> Result<((), ()), &'static str> {
+			match c[0].1 {
+				8 => Err("Setup failed"),
+				a if a > 5 => Err(SKIP),
+				_ => Ok(((), ())),
+			}
+		}
+	}
+
+	#[test]
+	fn skipped_samples_are_omitted() {
+		let instanced: Vec<_> = sweep_assignments(&Bounded.components(), 10).into_iter()
+			.filter(|c| c[0].1 != 8)
+			.filter_map(|c| {
+				instance_or_skip::<(), (), (), _>(&Bounded, &c, 0).unwrap().map(|_| c[0].1)
+			})
+			.collect();
+		assert_eq!(instanced, vec![0, 1, 2, 3, 4, 5]);
+
+		assert_eq!(instance_or_skip::<(), (), (), _>(&Bounded, &[(A, 8)], 0), Err("Setup failed"));
+	}
+
+	#[test]
+	fn fill_storage_map_calls_once_per_index() {
+		let mut indices = Vec::new();
+		fill_storage_map(5, |i| indices.push(i));
+		assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+
+		let mut calls = 0;
+		fill_storage_map(0, |_| calls += 1);
+		assert_eq!(calls, 0);
+	}
+
+	#[test]
+	fn timed_parameters_scale_with_target_and_cost() {
+		let second = Duration::from_secs(1);
+
+		// 1µs per iteration, one component: a million iterations fit in a second.
+		assert_eq!(timed_parameters(1, 1_000, second), (100, 10_000));
+		// Doubling the target doubles the repetitions.
+		assert_eq!(timed_parameters(1, 1_000, 2 * second), (100, 20_000));
+		// Doubling the cost halves them.
+		assert_eq!(timed_parameters(1, 2_000, second), (100, 5_000));
+		// The budget is shared between components.
+		assert_eq!(timed_parameters(4, 1_000, second), (100, 2_500));
+		// Small budgets spend fewer steps before repeating.
+		assert_eq!(timed_parameters(1, 1_000, Duration::from_micros(10)), (10, 1));
+		// Both ends of the range are still covered when the budget is exhausted.
+		assert_eq!(timed_parameters(1, 1_000, Duration::from_nanos(1)), (2, 1));
+	}
+
+	#[test]
+	#[cfg(not(any(feature = "runtime-benchmarks", feature = "mock-host")))]
+	fn runners_fail_without_host_functions() {
+		let setup = NoopSetup(vec![(A, 0, 10)]);
+		let request = BenchmarkRequest { steps: 1, repeat: 1, ..Default::default() };
+		assert_eq!(run_benchmark(&setup, &request), Err(HOST_FUNCTIONS_UNAVAILABLE));
+		assert_eq!(
+			run_benchmark_timed(&setup, Duration::from_secs(1)),
+			Err(HOST_FUNCTIONS_UNAVAILABLE),
+		);
+	}
+
+	#[test]
+	fn panicking_iterations_still_wipe_the_db() {
+		struct Panic;
+		impl Dispatchable for Panic {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+				panic!("dispatch panicked")
+			}
+		}
+
+		let wipes = std::cell::Cell::new(0);
+		let iteration = || {
+			let _wipe = WipeOnPanic(|| wipes.set(wipes.get() + 1));
+			Panic.dispatch(())
+		};
+		assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(iteration)).is_err());
+		assert_eq!(wipes.get(), 1);
+
+		// Iterations completing normally wipe the DB themselves.
+		{
+			let _wipe = WipeOnPanic(|| wipes.set(wipes.get() + 1));
+		}
+		assert_eq!(wipes.get(), 1);
+	}
+
+	#[test]
+	fn compute_only_benchmarks_leave_the_db_alone() {
+		use core::sync::atomic::{AtomicU32, Ordering};
+
+		static COMMITS: AtomicU32 = AtomicU32::new(0);
+		static WIPES: AtomicU32 = AtomicU32::new(0);
+		fn commit() {
+			COMMITS.fetch_add(1, Ordering::SeqCst);
+		}
+		fn wipe() {
+			WIPES.fetch_add(1, Ordering::SeqCst);
+		}
+
+		struct Hash;
+		impl Dispatchable for Hash {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+				Ok(())
+			}
+		}
+
+		struct HashSetup(bool);
+		impl BenchmarkingSetup<(), Hash, ()> for HashSetup {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 0, 10)]
+			}
+
+			fn uses_storage(&self) -> bool {
+				self.0
+			}
+
+			fn instance(&self, _: &[(BenchmarkParameter, u32)]) -> Result<(Hash, ()), &'static str> {
+				Ok((Hash, ()))
+			}
+		}
+
+		let mut results = Vec::new();
+		let mut run = |setup: HashSetup| {
+			let timeout = (None, OnTimeout::Skip);
+			let db = Db { commit, wipe, settle: leave_db };
+			let sink: &mut dyn FnMut(BenchmarkResults) = &mut |r| results.push(r);
+			measure::<(), Hash, (), _>(
+				&setup,
+				(db, Timer::HOST),
+				b"hash",
+				&[(A, 1)],
+				(3, None),
+				timeout,
+				sink,
+			).unwrap();
+		};
+
+		run(HashSetup(false));
+		assert_eq!((COMMITS.load(Ordering::SeqCst), WIPES.load(Ordering::SeqCst)), (0, 0));
+
+		run(HashSetup(true));
+		assert_eq!(results.len(), 6);
+		assert_eq!((COMMITS.load(Ordering::SeqCst), WIPES.load(Ordering::SeqCst)), (3, 3));
+	}
+
+	#[test]
+	fn warm_snapshots_are_measured_against() {
+		struct Read;
+		impl Dispatchable for Read {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+				match sp_io::storage::get(b"alice") {
+					Some(ref balance) if balance == b"100" => Ok(()),
+					_ => Err(sp_runtime::DispatchError::Other("not preloaded")),
+				}
+			}
+		}
+
+		struct ReadSetup;
+		impl BenchmarkingSetup<(), Read, ()> for ReadSetup {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 0, 10)]
+			}
+
+			fn instance(&self, _: &[(BenchmarkParameter, u32)]) -> Result<(Read, ()), &'static str> {
+				Ok((Read, ()))
+			}
+		}
+
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("state.snapshot");
+		crate::write_snapshot(&path, &vec![(b"alice".to_vec(), b"100".to_vec())]).unwrap();
+		let request = |path: &std::path::Path| BenchmarkRequest {
+			warm_snapshot: Some(path.to_str().unwrap().as_bytes().to_vec()),
+			..Default::default()
+		};
+
+		sp_io::TestExternalities::default().execute_with(|| {
+			assert_eq!(sp_io::storage::get(b"alice"), None);
+			let db = prepare_db::<(), Read, (), _>(&ReadSetup, &request(&path)).unwrap();
+
+			let mut results = Vec::new();
+			let timeout = (None, OnTimeout::Skip);
+			let sink: &mut dyn FnMut(BenchmarkResults) = &mut |r| results.push(r);
+			measure::<(), Read, (), _>(
+				&ReadSetup,
+				(db, Timer::HOST),
+				b"read",
+				&[(A, 1)],
+				(3, None),
+				timeout,
+				sink,
+			).unwrap();
+			assert_eq!(results.len(), 3);
+		});
+
+		sp_io::TestExternalities::default().execute_with(|| {
+			let missing = dir.path().join("missing.snapshot");
+			assert!(prepare_db::<(), Read, (), _>(&ReadSetup, &request(&missing)).is_err());
+		});
+	}
+
+	#[test]
+	fn read_only_timings_drop_the_writes() {
+		struct Increment;
+		impl Dispatchable for Increment {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+				let count = sp_io::storage::get(b"count").map_or(0, |c| c[0]);
+				sp_io::storage::set(b"count", &[count + 1]);
+				Ok(())
+			}
+		}
+
+		struct IncrementSetup;
+		impl BenchmarkingSetup<(), Increment, ()> for IncrementSetup {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 0, 10)]
+			}
+
+			fn read_only_timing(&self) -> bool {
+				true
+			}
+
+			fn instance(&self, _: &[(BenchmarkParameter, u32)])
+				-> Result<(Increment, ()), &'static str>
+			{
+				Ok((Increment, ()))
+			}
+		}
+
+		sp_io::TestExternalities::new_empty().execute_with(|| {
+			let mut results = Vec::new();
+			let db = Db { commit: leave_db, wipe: leave_db, settle: leave_db };
+			let timeout = (None, OnTimeout::Skip);
+			let c = [(A, 1)];
+			let sink: &mut dyn FnMut(BenchmarkResults) = &mut |r| results.push(r);
+			measure::<(), Increment, (), _>(
+				&IncrementSetup,
+				(db, Timer::HOST),
+				b"increment",
+				&c,
+				(2, None),
+				timeout,
+				sink,
+			).unwrap();
+
+			let tags: Vec<_> = results.iter().map(|(c, _)| c.clone()).collect();
+			assert_eq!(tags, vec![
+				vec![(A, 1)],
+				vec![(A, 1), (ReadOnly, 1)],
+				vec![(A, 1)],
+				vec![(A, 1), (ReadOnly, 1)],
+			]);
+			// Only the full iterations incremented the count.
+			assert_eq!(sp_io::storage::get(b"count"), Some(vec![2]));
+		});
+	}
+
+	#[test]
+	fn sinks_receive_every_measurement() {
+		let setup = NoopSetup(vec![(A, 0, 10), (B, 0, 4)]);
+		let request = BenchmarkRequest { steps: 5, repeat: 2, ..Default::default() };
+		let expected: Vec<_> = sweep_assignments(&setup.components(), 5).into_iter()
+			.flat_map(|c| vec![c.clone(), c])
+			.collect();
+
+		sp_io::TestExternalities::new_empty().execute_with(|| {
+			let mut streamed = Vec::new();
+			let sink: &mut dyn FnMut(BenchmarkResults) = &mut |(c, _)| streamed.push(c);
+			measure_sweep::<(), Noop, (), _>(&setup, &request, Db::UNUSED, Timer::HOST, sink)
+				.unwrap();
+			assert_eq!(streamed, expected);
+
+			let collected = collect_results(|sink| {
+				measure_sweep::<(), Noop, (), _>(&setup, &request, Db::UNUSED, Timer::HOST, sink)
+			}).unwrap();
+			assert_eq!(collected.into_iter().map(|(c, _)| c).collect::<Vec<_>>(), expected);
+		});
+	}
+
+	#[test]
+	fn prerequisites_run_before_every_timed_call() {
+		use core::sync::atomic::{AtomicU32, Ordering};
+
+		static WIPES: AtomicU32 = AtomicU32::new(0);
+		fn wipe() {
+			WIPES.fetch_add(1, Ordering::SeqCst);
+			sp_io::storage::clear(b"bonded");
+		}
+
+		enum Staking {
+			Bond,
+			Claim,
+		}
+		impl Dispatchable for Staking {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+				match self {
+					Staking::Bond => sp_io::storage::set(b"bonded", b"yes"),
+					Staking::Claim => {
+						if sp_io::storage::get(b"bonded").is_none() {
+							return Err(sp_runtime::DispatchError::Other("Nothing bonded"));
+						}
+						sp_io::storage::clear(b"bonded");
+					},
+				}
+				Ok(())
+			}
+		}
+
+		struct ClaimSetup;
+		impl BenchmarkingSetup<(), Staking, ()> for ClaimSetup {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 0, 10)]
+			}
+
+			fn instance(&self, _: &[(BenchmarkParameter, u32)]) -> Result<(Staking, ()), &'static str> {
+				Ok((Staking::Claim, ()))
+			}
+
+			fn prerequisites(&self, _: &[(BenchmarkParameter, u32)])
+				-> Result<Vec<(Staking, ())>, &'static str>
+			{
+				Ok(vec![(Staking::Bond, ())])
+			}
+		}
+
+		sp_io::TestExternalities::new_empty().execute_with(|| {
+			let mut results = Vec::new();
+			let db = Db { commit: leave_db, wipe, settle: leave_db };
+			let timeout = (None, OnTimeout::Skip);
+			let sink: &mut dyn FnMut(BenchmarkResults) = &mut |r| results.push(r);
+			// Every claim consumes the bond, so it only succeeds if the bond is dispatched again.
+			measure::<(), Staking, (), _>(
+				&ClaimSetup,
+				(db, Timer::HOST),
+				b"claim",
+				&[(A, 1)],
+				(3, None),
+				timeout,
+				sink,
+			).unwrap();
+			assert_eq!(results.len(), 3);
+			assert_eq!(WIPES.load(Ordering::SeqCst), 3);
+		});
+	}
+
+	#[test]
+	fn shards_are_merged_in_assignment_order() {
+		let shard = |values: Vec<(u32, u128)>| -> Vec<BenchmarkResults> {
+			values.into_iter().map(|(a, time)| (vec![(A, a), (B, 5)], time)).collect()
+		};
+
+		let merged = merge_results(vec![
+			shard(vec![(6, 60), (6, 61), (8, 80)]),
+			shard(vec![(0, 1), (0, 2), (2, 20), (4, 40)]),
+		]).unwrap();
+		let expected = shard(vec![(0, 1), (0, 2), (2, 20), (4, 40), (6, 60), (6, 61), (8, 80)]);
+		assert_eq!(merged, expected);
+
+		// Results tagged with the origin still belong to the same benchmark.
+		let tagged = vec![(vec![(A, 0), (B, 5), (Origin, 1)], 3)];
+		assert_eq!(merge_results(vec![shard(vec![(0, 1)]), tagged]).unwrap().len(), 2);
+
+		let other_components = vec![(vec![(A, 0), (C, 5)], 1)];
+		assert_eq!(
+			merge_results(vec![shard(vec![(0, 1)]), other_components]),
+			Err("Shards of the benchmark assign different components"),
+		);
+		assert_eq!(merge_results(vec![]), Ok(vec![]));
+	}
+
+	#[test]
+	fn unreachable_highs_are_flagged() {
+		struct Transfer;
+		impl Dispatchable for Transfer {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+				Ok(())
+			}
+		}
+
+		// Only 50 accounts can be funded, but up to 100 recipients are swept.
+		struct TransferSetup;
+		impl BenchmarkingSetup<(), Transfer, ()> for TransferSetup {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 1, 10), (R, 1, 100)]
+			}
+
+			fn instance(&self, c: &[(BenchmarkParameter, u32)]) -> Result<(Transfer, ()), &'static str> {
+				match c.iter().find(|(p, _)| *p == R) {
+					Some((_, r)) if *r > 50 => Err("Not enough funded accounts"),
+					_ => Ok((Transfer, ())),
+				}
+			}
+		}
+
+		let mut resets = 0;
+		let unreachable = unreachable_highs(&TransferSetup, || resets += 1).unwrap();
+		assert_eq!(unreachable, vec![(R, 100, "Not enough funded accounts")]);
+		assert_eq!(resets, 2);
+	}
+
+	#[test]
+	fn timing_overhead_is_subtracted() {
+		// Every read of the clock takes 10 ns and the dispatch takes 100 ns.
+		let elapsed = |timer: Timer| {
+			let mut results = Vec::new();
+			let sink: &mut dyn FnMut(BenchmarkResults) = &mut |r| results.push(r);
+			let timeout = (None, OnTimeout::Skip);
+			measure::<(), Tick, (), _>(
+				&TickSetup(0), (Db::UNUSED, timer), b"tick", &[(A, 1)], (2, None), timeout, sink,
+			).unwrap();
+			results.into_iter().map(|(_, elapsed)| elapsed).collect::<Vec<_>>()
+		};
+
+		let uncalibrated = fake_timer(10);
+		let calibrated = uncalibrated.calibrated();
+		assert_eq!(calibrated.overhead, 10);
+		assert_eq!(elapsed(uncalibrated), vec![110, 110]);
+		assert_eq!(elapsed(calibrated), vec![100, 100]);
+		// An overhead larger than the measurement clamps it at zero.
+		assert_eq!(elapsed(Timer { overhead: 1_000, ..uncalibrated }), vec![0, 0]);
+	}
+
+	#[test]
+	fn cache_timings_are_tagged_cold_and_warm() {
+		use core::sync::atomic::{AtomicBool, Ordering};
+
+		// Reading the storage takes 100 ns from the DB and 10 ns from the cache, which holds what
+		// the setup wrote until it is committed.
+		static CACHED: AtomicBool = AtomicBool::new(false);
+		fn flush_cache() {
+			CACHED.store(false, Ordering::SeqCst);
+		}
+
+		struct Read;
+		impl Dispatchable for Read {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+				advance_clock(if CACHED.load(Ordering::SeqCst) { 10 } else { 100 });
+				Ok(())
+			}
+		}
+
+		struct ReadSetup;
+		impl BenchmarkingSetup<(), Read, ()> for ReadSetup {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 0, 10)]
+			}
+
+			fn cache_timing(&self) -> bool {
+				true
+			}
+
+			fn instance(&self, _: &[(BenchmarkParameter, u32)])
+				-> Result<(Read, ()), &'static str>
+			{
+				CACHED.store(true, Ordering::SeqCst);
+				Ok((Read, ()))
+			}
+		}
+
+		let mut results = Vec::new();
+		let sink: &mut dyn FnMut(BenchmarkResults) = &mut |r| results.push(r);
+		let db = Db { commit: flush_cache, wipe: flush_cache, settle: leave_db };
+		let timer = fake_timer(0);
+		measure::<(), Read, (), _>(
+			&ReadSetup, (db, timer), b"read", &[(A, 1)], (2, None), (None, OnTimeout::Skip), sink,
+		).unwrap();
+
+		assert_eq!(results, vec![
+			(vec![(A, 1), (Cache, 0)], 100),
+			(vec![(A, 1), (Cache, 1)], 10),
+			(vec![(A, 1), (Cache, 0)], 100),
+			(vec![(A, 1), (Cache, 1)], 10),
+		]);
+		for pair in results.chunks(2) {
+			assert!(pair[0].1 >= pair[1].1, "cold {} below warm {}", pair[0].1, pair[1].1);
+		}
+	}
+
+	#[test]
+	fn components_not_swept_rest_where_the_policy_says() {
+		let ranges = [
+			(A, Value(0), Value(10)),
+			(B, Value(0), Value(100)),
+			(C, Value(2), Value(4)),
+		];
+		let rest = |policy| sweep_rest_assignments(&ranges, 2, &[], policy).unwrap();
+
+		assert_eq!(rest(RestPolicy::Midpoint), sweep_points_assignments(&ranges, 2, &[]).unwrap());
+		assert_eq!(rest(RestPolicy::High), vec![
+			vec![(A, 0), (B, 100), (C, 4)],
+			vec![(A, 5), (B, 100), (C, 4)],
+			vec![(A, 10), (B, 0), (C, 4)],
+			vec![(A, 10), (B, 50), (C, 4)],
+			vec![(A, 10), (B, 100), (C, 2)],
+			vec![(A, 10), (B, 100), (C, 3)],
+		]);
+		assert_eq!(rest(RestPolicy::Low)[0], vec![(A, 0), (B, 0), (C, 2)]);
+
+		let setup = NoopSetup(vec![(A, 0, 10), (B, 0, 100), (C, 2, 4)]);
+		let request = BenchmarkRequest {
+			steps: 2,
+			repeat: 1,
+			rest: RestPolicy::High,
+			..Default::default()
+		};
+		sp_io::TestExternalities::new_empty().execute_with(|| {
+			let mut measured = Vec::new();
+			let sink: &mut dyn FnMut(BenchmarkResults) = &mut |(c, _)| measured.push(c);
+			measure_sweep::<(), Noop, (), _>(&setup, &request, Db::UNUSED, Timer::HOST, sink)
+				.unwrap();
+			assert_eq!(measured, rest(RestPolicy::High));
+		});
+	}
+
+	#[test]
+	fn adaptive_repeats_stop_once_the_mean_is_stable() {
+		use core::cell::Cell;
+
+		struct Wait(u64);
+		impl Dispatchable for Wait {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+				advance_clock(self.0 as u128);
+				Ok(())
+			}
+		}
+
+		// Waits about 1000 ns, or alternately 100 and 10000 ns if noisy.
+		struct WaitSetup { noisy: bool, calls: Cell<u64> }
+		impl BenchmarkingSetup<(), Wait, ()> for WaitSetup {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 0, 10)]
+			}
+
+			fn instance(&self, _: &[(BenchmarkParameter, u32)])
+				-> Result<(Wait, ()), &'static str>
+			{
+				let call = self.calls.get();
+				self.calls.set(call + 1);
+				let wait = match (self.noisy, call % 2) {
+					(false, parity) => 1_000 + parity,
+					(true, 0) => 100,
+					(true, _) => 10_000,
+				};
+				Ok((Wait(wait), ()))
+			}
+		}
+
+		let measured = |noisy| {
+			let mut results = Vec::new();
+			let sink: &mut dyn FnMut(BenchmarkResults) = &mut |r| results.push(r);
+			let setup = WaitSetup { noisy, calls: Cell::new(0) };
+			let adaptive = AdaptiveRepeat { max_error_permille: 10, max_repeat: 20 };
+			measure::<(), Wait, (), _>(
+				&setup, (Db::UNUSED, fake_timer(0)), b"wait", &[(A, 1)], (1, Some(adaptive)),
+				(None, OnTimeout::Skip), sink,
+			).unwrap();
+			results.into_iter().map(|(_, elapsed)| elapsed).collect::<Vec<_>>()
+		};
+
+		// The least number of iterations giving a standard error at all is enough.
+		assert_eq!(measured(false), vec![1_000, 1_001]);
+		// The mean never gets within 1%, so the iterations stop at the cap.
+		let noisy = measured(true);
+		assert_eq!(noisy.len(), 20);
+		assert!(!mean_is_stable(&noisy, 10));
+	}
+
+	#[test]
+	fn estimates_scale_with_steps_and_repeat() {
+		// Setting up an instance takes 50 ns and dispatching it 100 ns.
+		let estimate = |steps, repeat, adaptive| {
+			let request = BenchmarkRequest { steps, repeat, adaptive, ..Default::default() };
+			estimate_sweep::<(), Tick, (), _>(&TickSetup(50), &request, fake_timer(0))
+				.unwrap()
+				.as_nanos()
+		};
+
+		// The range of `A` is swept in `steps` assignments.
+		assert_eq!(estimate(2, 1, None), 2 * 150);
+		assert_eq!(estimate(5, 1, None), 5 * 150);
+		assert_eq!(estimate(2, 3, None), 2 * 3 * 150);
+		assert_eq!(estimate(5, 3, None), 5 * 3 * 150);
+
+		let adaptive = AdaptiveRepeat { max_error_permille: 10, max_repeat: 20 };
+		assert_eq!(estimate(2, 3, Some(adaptive)), 2 * 20 * 150);
+	}
+
+	#[test]
+	fn warm_timings_do_not_see_the_cold_dispatch() {
+		use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+		// Stands in for an account which can only be registered once.
+		static REGISTERED: AtomicBool = AtomicBool::new(false);
+		static COMMITTED: AtomicBool = AtomicBool::new(false);
+		static WIPES: AtomicU32 = AtomicU32::new(0);
+		fn commit() {
+			COMMITTED.store(true, Ordering::SeqCst);
+		}
+		fn wipe() {
+			REGISTERED.store(false, Ordering::SeqCst);
+			COMMITTED.store(false, Ordering::SeqCst);
+			WIPES.fetch_add(1, Ordering::SeqCst);
+		}
+
+		// Fails for `A` of 1 unless the setup was committed, as only the warm dispatch does.
+		struct Register { a: u32 }
+		impl Dispatchable for Register {
+			type Origin = ();
+			type Trait = ();
+			fn dispatch(self, _: ()) -> sp_runtime::DispatchResult {
+				if self.a == 1 && !COMMITTED.load(Ordering::SeqCst) {
+					return Err("Setup not committed".into());
+				}
+				if REGISTERED.swap(true, Ordering::SeqCst) {
+					return Err("Already registered".into());
+				}
+				Ok(())
+			}
+		}
+
+		struct RegisterSetup;
+		impl BenchmarkingSetup<(), Register, ()> for RegisterSetup {
+			fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+				vec![(A, 0, 10)]
+			}
+
+			fn cache_timing(&self) -> bool {
+				true
+			}
+
+			fn instance(&self, c: &[(BenchmarkParameter, u32)])
+				-> Result<(Register, ()), &'static str>
+			{
+				Ok((Register { a: c[0].1 }, ()))
+			}
+		}
+
+		let measured = |a| {
+			let mut results = Vec::new();
+			let sink: &mut dyn FnMut(BenchmarkResults) = &mut |r| results.push(r);
+			let env = (Db { commit, wipe, settle: leave_db }, fake_timer(0));
+			let timeout = (None, OnTimeout::Skip);
+			measure::<(), Register, (), _>(
+				&RegisterSetup, env, b"register", &[(A, a)], (2, None), timeout, sink,
+			).unwrap();
+			results.into_iter().map(|(c, _)| c).collect::<Vec<_>>()
+		};
+
+		// On top of the cold dispatch the warm one would register the account again.
+		assert_eq!(measured(0), vec![
+			vec![(A, 0), (Cache, 0)],
+			vec![(A, 0), (Cache, 1)],
+			vec![(A, 0), (Cache, 0)],
+			vec![(A, 0), (Cache, 1)],
+		]);
+		// Before and after every warm dispatch.
+		assert_eq!(WIPES.load(Ordering::SeqCst), 4);
+
+		// A failing warm dispatch only drops its sample.
+		assert_eq!(measured(1), vec![vec![(A, 1), (Cache, 0)], vec![(A, 1), (Cache, 0)]]);
+		assert_eq!(WIPES.load(Ordering::SeqCst), 8);
+		assert!(!REGISTERED.load(Ordering::SeqCst));
+	}
+}
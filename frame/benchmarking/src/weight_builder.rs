@@ -0,0 +1,104 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Building the weight of a dispatch from the costs fitted to its benchmark.
+
+use sp_std::prelude::*;
+use frame_support::weights::Weight;
+use crate::BenchmarkParameter;
+
+/// Composes a weight from a base cost, a cost per unit of every component and the cost of the
+/// storage reads and writes, the way hand-written weight functions do.
+///
+/// Every operation saturates, so an overly large component yields the largest weight rather
+/// than wrapping around.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct WeightBuilder {
+	base: Weight,
+	slopes: Vec<(BenchmarkParameter, Weight)>,
+	reads: (Weight, Weight),
+	writes: (Weight, Weight),
+}
+
+impl WeightBuilder {
+	/// Start from the cost of the dispatch with all components at zero.
+	pub fn new(base: Weight) -> Self {
+		WeightBuilder { base, ..Default::default() }
+	}
+
+	/// Add `per_unit` for every unit of `param`.
+	pub fn slope(mut self, param: BenchmarkParameter, per_unit: Weight) -> Self {
+		self.slopes.push((param, per_unit));
+		self
+	}
+
+	/// Add `per_read` for each of `count` storage reads.
+	pub fn reads(mut self, count: Weight, per_read: Weight) -> Self {
+		self.reads = (count, per_read);
+		self
+	}
+
+	/// Add `per_write` for each of `count` storage writes.
+	pub fn writes(mut self, count: Weight, per_write: Weight) -> Self {
+		self.writes = (count, per_write);
+		self
+	}
+
+	/// Returns the weight of the dispatch with the components assigned `components`.
+	///
+	/// Components with a slope but no value are taken to be zero.
+	pub fn build(&self, components: &[(BenchmarkParameter, u32)]) -> Weight {
+		let value = |param: BenchmarkParameter| components.iter()
+			.find(|(p, _)| *p == param)
+			.map_or(0, |(_, v)| *v);
+
+		self.slopes.iter()
+			.fold(self.base, |weight, (param, per_unit)| {
+				weight.saturating_add(per_unit.saturating_mul(value(*param)))
+			})
+			.saturating_add(self.reads.0.saturating_mul(self.reads.1))
+			.saturating_add(self.writes.0.saturating_mul(self.writes.1))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::BenchmarkParameter::*;
+
+	#[test]
+	fn weight_is_the_saturating_composition() {
+		let builder = WeightBuilder::new(10_000)
+			.slope(U, 300)
+			.slope(E, 20)
+			.reads(2, 1_000)
+			.writes(1, 5_000);
+
+		assert_eq!(builder.build(&[(U, 4), (E, 7)]), 10_000 + 300 * 4 + 20 * 7 + 2 * 1_000 + 5_000);
+		// Unassigned components add nothing.
+		assert_eq!(builder.build(&[(U, 4)]), 10_000 + 300 * 4 + 2 * 1_000 + 5_000);
+		assert_eq!(WeightBuilder::new(10_000).build(&[(U, 4)]), 10_000);
+	}
+
+	#[test]
+	fn weight_saturates() {
+		let builder = WeightBuilder::new(10_000).slope(U, 300);
+		assert_eq!(builder.build(&[(U, u32::max_value())]), Weight::max_value());
+
+		let builder = WeightBuilder::new(Weight::max_value() - 1).writes(2, 1);
+		assert_eq!(builder.build(&[]), Weight::max_value());
+	}
+}
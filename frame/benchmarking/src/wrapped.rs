@@ -0,0 +1,192 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarking the calls of a setup wrapped into other calls.
+
+use sp_std::{prelude::*, marker::PhantomData};
+use crate::{BenchmarkParameter, BenchmarkingSetup, ComponentBound};
+
+/// Turns the instances of a bare setup into the calls and origins actually benchmarked.
+pub trait Wrap<Call, RawOrigin, OuterCall, OuterOrigin> {
+	/// Wrap the call and its origin, prepared for `components`.
+	fn wrap(&self, call: Call, caller: RawOrigin, components: &[(BenchmarkParameter, u32)])
+		-> Result<(OuterCall, OuterOrigin), &'static str>;
+
+	/// Wrap the instances prepared for `components` by `instance`.
+	///
+	/// Defaults to wrapping a single instance, wrappers of several calls prepare more.
+	fn wrap_instance(
+		&self,
+		instance: &mut dyn FnMut() -> Result<(Call, RawOrigin), &'static str>,
+		components: &[(BenchmarkParameter, u32)],
+	) -> Result<(OuterCall, OuterOrigin), &'static str> {
+		let (call, caller) = instance()?;
+		self.wrap(call, caller, components)
+	}
+}
+
+/// Benchmarks the calls of the bare setup `Setup`, wrapped by `W`.
+///
+/// The instances are prepared by `Setup` as usual. Everything but the instances is the same as
+/// for `Setup`, and every prerequisite is wrapped like the timed call.
+pub struct Wrapped<Setup, W, Call, RawOrigin> {
+	setup: Setup,
+	wrap: W,
+	_inner: PhantomData<(Call, RawOrigin)>,
+}
+
+impl<Setup, W, Call, RawOrigin> Wrapped<Setup, W, Call, RawOrigin> {
+	/// Benchmark the calls of `setup` wrapped by `wrap`.
+	pub fn with(setup: Setup, wrap: W) -> Self {
+		Wrapped { setup, wrap, _inner: PhantomData }
+	}
+}
+
+impl<T, Call, RawOrigin, OuterCall, OuterOrigin, Setup, W>
+	BenchmarkingSetup<T, OuterCall, OuterOrigin> for Wrapped<Setup, W, Call, RawOrigin>
+where
+	Setup: BenchmarkingSetup<T, Call, RawOrigin>,
+	W: Wrap<Call, RawOrigin, OuterCall, OuterOrigin>,
+{
+	fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+		self.setup.components()
+	}
+
+	fn component_ranges(&self) -> Vec<(BenchmarkParameter, ComponentBound, ComponentBound)> {
+		self.setup.component_ranges()
+	}
+
+	fn component_units(&self) -> Vec<(BenchmarkParameter, &'static str)> {
+		self.setup.component_units()
+	}
+
+	fn paired_components(&self) -> Vec<(BenchmarkParameter, BenchmarkParameter)> {
+		self.setup.paired_components()
+	}
+
+	fn uses_storage(&self) -> bool {
+		self.setup.uses_storage()
+	}
+
+	fn read_only_timing(&self) -> bool {
+		self.setup.read_only_timing()
+	}
+
+	fn cache_timing(&self) -> bool {
+		self.setup.cache_timing()
+	}
+
+	fn worst_case_assignment(&self) -> Result<Vec<(BenchmarkParameter, u32)>, &'static str> {
+		self.setup.worst_case_assignment()
+	}
+
+	fn instance(&self, components: &[(BenchmarkParameter, u32)])
+		-> Result<(OuterCall, OuterOrigin), &'static str>
+	{
+		self.instance_with_origin(components, 0)
+	}
+
+	fn origins(&self) -> u32 {
+		self.setup.origins()
+	}
+
+	fn instance_with_origin(&self, components: &[(BenchmarkParameter, u32)], origin: u32)
+		-> Result<(OuterCall, OuterOrigin), &'static str>
+	{
+		let mut instance = || self.setup.instance_with_origin(components, origin);
+		self.wrap.wrap_instance(&mut instance, components)
+	}
+
+	fn prerequisites(&self, components: &[(BenchmarkParameter, u32)])
+		-> Result<Vec<(OuterCall, OuterOrigin)>, &'static str>
+	{
+		self.setup.prerequisites(components)?
+			.into_iter()
+			.map(|(call, caller)| self.wrap.wrap(call, caller, components))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::BenchmarkParameter::*;
+
+	struct Tagged;
+	impl Wrap<u32, &'static str, (u32, u32), String> for Tagged {
+		fn wrap(&self, call: u32, caller: &'static str, components: &[(BenchmarkParameter, u32)])
+			-> Result<((u32, u32), String), &'static str>
+		{
+			Ok(((call, components[0].1), format!("{} via proxy", caller)))
+		}
+	}
+
+	struct Numbers;
+	impl BenchmarkingSetup<(), u32, &'static str> for Numbers {
+		fn components(&self) -> Vec<(BenchmarkParameter, u32, u32)> {
+			vec![(N, 0, 10)]
+		}
+
+		fn component_units(&self) -> Vec<(BenchmarkParameter, &'static str)> {
+			vec![(N, "items")]
+		}
+
+		fn cache_timing(&self) -> bool {
+			true
+		}
+
+		fn instance(&self, c: &[(BenchmarkParameter, u32)])
+			-> Result<(u32, &'static str), &'static str>
+		{
+			self.instance_with_origin(c, 0)
+		}
+
+		fn origins(&self) -> u32 {
+			2
+		}
+
+		fn instance_with_origin(&self, _: &[(BenchmarkParameter, u32)], origin: u32)
+			-> Result<(u32, &'static str), &'static str>
+		{
+			Ok((7, ["root", "signed"][origin as usize]))
+		}
+
+		fn prerequisites(&self, _: &[(BenchmarkParameter, u32)])
+			-> Result<Vec<(u32, &'static str)>, &'static str>
+		{
+			Ok(vec![(1, "root")])
+		}
+	}
+
+	#[test]
+	fn wrapped_setups_forward_to_the_bare_setup() {
+		type WrappedSetup = dyn BenchmarkingSetup<(), (u32, u32), String>;
+		let wrapped = Wrapped::<_, _, u32, &'static str>::with(Numbers, Tagged);
+		let wrapped: &WrappedSetup = &wrapped;
+
+		assert_eq!(wrapped.components(), Numbers.components());
+		assert_eq!(wrapped.component_units(), Numbers.component_units());
+		assert!(wrapped.cache_timing());
+		assert_eq!(wrapped.origins(), 2);
+
+		// Every instance is wrapped for the same components and origin.
+		let c = [(N, 3)];
+		let via = |caller: &str| format!("{} via proxy", caller);
+		assert_eq!(wrapped.instance(&c).unwrap(), ((7, 3), via("root")));
+		assert_eq!(wrapped.instance_with_origin(&c, 1).unwrap(), ((7, 3), via("signed")));
+		assert_eq!(wrapped.prerequisites(&c).unwrap(), vec![((1, 3), via("root"))]);
+	}
+}
@@ -0,0 +1,23 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+use frame_benchmarking::{component_ranges, BenchmarkParameter, ComponentBound};
+
+fn component_ranges() -> Vec<(BenchmarkParameter, ComponentBound, ComponentBound)> {
+	component_ranges![B in 0 .. C, C in 0 .. 10]
+}
+
+fn main() {}
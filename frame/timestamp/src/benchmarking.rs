@@ -22,10 +22,9 @@ use sp_std::prelude::*;
 
 use frame_system::RawOrigin;
 use frame_benchmarking::{
-	BenchmarkResults, BenchmarkParameter, selected_benchmark, benchmarking,
+	BenchmarkResults, BenchmarkRequest, BenchmarkParameter, BenchmarkMetadata, selected_benchmark,
 	Benchmarking, BenchmarkingSetup,
 };
-use sp_runtime::traits::Dispatchable;
 
 /// Benchmark `set` extrinsic.
 struct Set;
@@ -37,6 +36,10 @@ impl<T: Trait> BenchmarkingSetup<T, Call<T>, RawOrigin<T::AccountId>> for Set {
 		]
 	}
 
+	fn component_units(&self) -> Vec<(BenchmarkParameter, &'static str)> {
+		vec![(BenchmarkParameter::N, "milliseconds")]
+	}
+
 	fn instance(&self, components: &[(BenchmarkParameter, u32)])
 		-> Result<(Call<T>, RawOrigin<T::AccountId>), &'static str>
 	{
@@ -51,58 +54,41 @@ impl<T: Trait> BenchmarkingSetup<T, Call<T>, RawOrigin<T::AccountId>> for Set {
 selected_benchmark!(Set);
 
 impl<T: Trait> Benchmarking<BenchmarkResults> for Module<T> {
-	fn run_benchmark(extrinsic: Vec<u8>, steps: u32, repeat: u32) -> Result<Vec<BenchmarkResults>, &'static str> {
+	fn run_benchmark(request: &BenchmarkRequest) -> Result<Vec<BenchmarkResults>, &'static str> {
 		// Map the input to the selected benchmark.
-		let selected_benchmark = match extrinsic.as_slice() {
-			b"set" => SelectedBenchmark::Set,
-			_ => return Err("Could not find extrinsic."),
-		};
-
-		// Warm up the DB
-		benchmarking::commit_db();
-		benchmarking::wipe_db();
-
-		let components = <SelectedBenchmark as BenchmarkingSetup<T, crate::Call<T>, RawOrigin<T::AccountId>>>::components(&selected_benchmark);
-		let mut results: Vec<BenchmarkResults> = Vec::new();
-
-		// Select the component we will be benchmarking. Each component will be benchmarked.
-		for (name, low, high) in components.iter() {
-			// Create up to `STEPS` steps for that component between high and low.
-			let step_size = ((high - low) / steps).max(1);
-			let num_of_steps = (high - low) / step_size;
-			for s in 0..num_of_steps {
-				// This is the value we will be testing for component `name`
-				let component_value = low + step_size * s;
-
-				// Select the mid value for all the other components.
-				let c: Vec<(BenchmarkParameter, u32)> = components.iter()
-					.map(|(n, l, h)|
-						(*n, if n == name { component_value } else { (h - l) / 2 + l })
-					).collect();
-
-				// Run the benchmark `repeat` times.
-				for _ in 0..repeat {
-					// Set up the externalities environment for the setup we want to benchmark.
-					let (call, caller) = <SelectedBenchmark as BenchmarkingSetup<
-						T,
-						Call<T>,
-						RawOrigin<T::AccountId>,
-					>>::instance(&selected_benchmark, &c)?;
-					// Commit the externalities to the database, flushing the DB cache.
-					// This will enable worst case scenario for reading from the database.
-					benchmarking::commit_db();
-					// Run the benchmark.
-					let start = benchmarking::current_time();
-					call.dispatch(caller.into())?;
-					let finish = benchmarking::current_time();
-					let elapsed = finish - start;
-					results.push((c.clone(), elapsed));
-					// Wipe the DB back to the genesis state.
-					benchmarking::wipe_db();
-				}
-			}
-		}
-
-		return Ok(results);
+		let selected_benchmark: SelectedBenchmark = sp_std::str::from_utf8(&request.name)
+			.map_err(|_| "Could not find extrinsic.")?
+			.parse()?;
+
+		frame_benchmarking::run_benchmark_checked::<T, crate::Call<T>, RawOrigin<T::AccountId>, _>(
+			&selected_benchmark,
+			request,
+		)
+	}
+
+	fn benchmarks() -> Vec<BenchmarkMetadata> {
+		SelectedBenchmark::list::<T>()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn benchmarks_are_parsed_from_their_name() {
+		assert!(match "set".parse::<SelectedBenchmark>() {
+			Ok(SelectedBenchmark::Set) => true,
+			_ => false,
+		});
+		assert_eq!("get".parse::<SelectedBenchmark>().err(), Some("Could not find extrinsic."));
+	}
+
+	#[test]
+	fn worst_case_is_the_latest_time() {
+		assert_eq!(
+			SelectedBenchmark::worst_case_assignment::<crate::tests::Test>("set"),
+			Ok(vec![(BenchmarkParameter::N, 100)]),
+		);
 	}
 }
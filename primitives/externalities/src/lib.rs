@@ -210,6 +210,12 @@ pub trait Externalities: ExtensionStore {
 	fn commit(&mut self) {
 		unimplemented!()
 	}
+
+	/// Drop all storage changes while `read_only` is set, instead of applying them.
+	///
+	/// Reads still see the storage as it was before the dropped changes. Externalities which
+	/// don't support this keep applying the changes.
+	fn set_read_only(&mut self, _read_only: bool) {}
 }
 
 /// Extension for the [`Externalities`] trait.
@@ -86,6 +86,8 @@ pub struct Ext<'a, H, N, B>
 	_phantom: std::marker::PhantomData<N>,
 	/// Extensions registered with this instance.
 	extensions: Option<&'a mut Extensions>,
+	/// Whether storage changes are dropped, see `set_read_only`.
+	read_only: bool,
 }
 
 impl<'a, H, N, B> Ext<'a, H, N, B>
@@ -112,6 +114,7 @@ where
 			id: rand::random(),
 			_phantom: Default::default(),
 			extensions,
+			read_only: false,
 		}
 	}
 
@@ -385,6 +388,9 @@ where
 			value.as_ref().map(HexDisplay::from)
 		);
 		let _guard = sp_panic_handler::AbortGuard::force_abort();
+		if self.read_only {
+			return;
+		}
 		if is_child_storage_key(&key) {
 			warn!(target: "trie", "Refuse to directly set child storage key");
 			return;
@@ -408,6 +414,9 @@ where
 			value.as_ref().map(HexDisplay::from)
 		);
 		let _guard = sp_panic_handler::AbortGuard::force_abort();
+		if self.read_only {
+			return;
+		}
 
 		self.mark_dirty();
 		self.overlay.set_child_storage(storage_key.into_owned(), child_info, key, value);
@@ -423,6 +432,9 @@ where
 			HexDisplay::from(&storage_key.as_ref()),
 		);
 		let _guard = sp_panic_handler::AbortGuard::force_abort();
+		if self.read_only {
+			return;
+		}
 
 		self.mark_dirty();
 		self.overlay.clear_child_storage(storage_key.as_ref(), child_info);
@@ -437,6 +449,9 @@ where
 			HexDisplay::from(&prefix),
 		);
 		let _guard = sp_panic_handler::AbortGuard::force_abort();
+		if self.read_only {
+			return;
+		}
 		if is_child_storage_key(prefix) {
 			warn!(target: "trie", "Refuse to directly clear prefix that is part of child storage key");
 			return;
@@ -461,6 +476,9 @@ where
 			HexDisplay::from(&prefix),
 		);
 		let _guard = sp_panic_handler::AbortGuard::force_abort();
+		if self.read_only {
+			return;
+		}
 
 		self.mark_dirty();
 		self.overlay.clear_child_prefix(storage_key.as_ref(), child_info, prefix);
@@ -602,6 +620,10 @@ where
 		).expect(EXT_NOT_ALLOWED_TO_FAIL);
 		self.storage_transaction_cache.reset();
 	}
+
+	fn set_read_only(&mut self, read_only: bool) {
+		self.read_only = read_only;
+	}
 }
 
 impl<'a, H, B, N> sp_externalities::ExtensionStore for Ext<'a, H, N, B>
@@ -841,4 +863,45 @@ mod tests {
 		);
 
 	}
+
+	#[test]
+	fn read_only_ext_drops_storage_changes() {
+		let mut cache = StorageTransactionCache::default();
+		let child = || ChildStorageKey::from_slice(CHILD_KEY_1).unwrap();
+		let mut overlay = OverlayedChanges::default();
+		overlay.set_storage(vec![30], Some(vec![31]));
+		let backend = Storage {
+			top: map![
+				vec![10] => vec![10],
+				vec![20] => vec![20]
+			],
+			children: map![
+				child().as_ref().to_vec() => StorageChild {
+					data: map![vec![10] => vec![10]],
+					child_info: CHILD_INFO_1.to_owned(),
+				}
+			],
+		}.into();
+
+		let mut ext = TestExt::new(&mut overlay, &mut cache, &backend, None, None);
+		ext.set_read_only(true);
+		ext.set_storage(vec![10], vec![11]);
+		ext.clear_storage(&[30]);
+		ext.clear_prefix(&[20]);
+		ext.set_child_storage(child(), CHILD_INFO_1, vec![20], vec![21]);
+		ext.kill_child_storage(child(), CHILD_INFO_1);
+
+		// Reads see the storage as it was before the dropped changes.
+		assert_eq!(ext.storage(&[10]), Some(vec![10]));
+		assert_eq!(ext.storage(&[20]), Some(vec![20]));
+		assert_eq!(ext.storage(&[30]), Some(vec![31]));
+		assert_eq!(ext.child_storage(child(), CHILD_INFO_1, &[10]), Some(vec![10]));
+		assert_eq!(ext.child_storage(child(), CHILD_INFO_1, &[20]), None);
+
+		ext.set_read_only(false);
+		ext.set_storage(vec![10], vec![11]);
+		ext.clear_storage(&[30]);
+		assert_eq!(ext.storage(&[10]), Some(vec![11]));
+		assert_eq!(ext.storage(&[30]), None);
+	}
 }
@@ -0,0 +1,615 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Analysis of benchmark results.
+
+use frame_benchmarking::{BenchmarkParameter, BenchmarkResults};
+
+/// A linear fit leaving less than this share of the variance unexplained is considered good
+/// enough, whatever a quadratic fit achieves.
+const LINEAR_FIT_TOLERANCE: f64 = 0.01;
+
+/// The share of the variance left unexplained by a linear fit which a quadratic fit has to
+/// explain for the results to be considered nonlinear.
+const QUADRATIC_IMPROVEMENT: f64 = 0.5;
+
+/// Components swept across fewer values than this are not checked for a flat slope.
+const FLAT_SLOPE_MIN_RANGE: f64 = 10.0;
+
+/// A slope less than this many standard errors away from zero is indistinguishable from zero.
+const FLAT_SLOPE_T_VALUE: f64 = 2.0;
+
+/// The two-sided 95% critical values of Student's t-distribution, by degrees of freedom.
+const T_95: [f64; 30] = [
+	12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+	2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+	2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+/// The least squares line through the cost of a benchmark in one of its components, along
+/// with the uncertainty of its coefficients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+	/// The component the results were fitted against.
+	pub param: BenchmarkParameter,
+	/// The cost of every unit of `param`.
+	pub slope: f64,
+	/// The cost with `param` at zero.
+	pub intercept: f64,
+	/// The standard error of `slope`.
+	pub slope_std_error: f64,
+	/// The standard error of `intercept`.
+	pub intercept_std_error: f64,
+	/// The 95% confidence interval of `slope`.
+	pub slope_interval: (f64, f64),
+	/// The 95% confidence interval of `intercept`.
+	pub intercept_interval: (f64, f64),
+}
+
+/// Returns the `results` of a benchmark measured the regular way, i.e. with a cold DB cache and
+/// the storage changes applied, with their pseudo-components stripped.
+///
+/// The results of every origin are kept, as samples of the same assignment.
+pub fn baseline_results(results: &[BenchmarkResults]) -> Vec<BenchmarkResults> {
+	results.iter()
+		.filter(|(components, _)| components.iter().all(|(param, value)| {
+			*param == BenchmarkParameter::Origin || !param.is_pseudo() || *value == 0
+		}))
+		.map(|(components, time)| {
+			(components.iter().filter(|(param, _)| !param.is_pseudo()).cloned().collect(), *time)
+		})
+		.collect()
+}
+
+/// Returns the components the cost of the `results` of a benchmark can scale with.
+pub fn real_components(results: &[BenchmarkResults]) -> Vec<BenchmarkParameter> {
+	results.first()
+		.map(|(components, _)| components.iter()
+			.map(|(param, _)| *param)
+			.filter(|param| !param.is_pseudo())
+			.collect())
+		.unwrap_or_default()
+}
+
+/// Fit a line through the `results` of a benchmark against the values of `param`.
+///
+/// The samples are picked like for [`fit_quality`]. Returns `None` if there are fewer than
+/// three samples or `param` never changes, as the uncertainty can't be estimated then.
+///
+/// Setting a weight from the upper bound of the intervals makes it conservative.
+pub fn linear_regression(
+	results: &[BenchmarkResults],
+	param: BenchmarkParameter,
+) -> Option<Regression> {
+	regression(param, &sweep_samples(results, param))
+}
+
+/// How well the cost of a benchmark is explained by a polynomial in one of its components.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitReport {
+	/// The component the results were fitted against.
+	pub param: BenchmarkParameter,
+	/// The coefficient of determination of a linear fit.
+	pub linear_r_squared: f64,
+	/// The coefficient of determination of a quadratic fit.
+	pub quadratic_r_squared: f64,
+	/// Whether a quadratic term explains the results materially better than a line.
+	pub nonlinear: bool,
+	/// The degree of the polynomial suggested to model the cost in `param`.
+	pub suggested_degree: u32,
+}
+
+/// Fit the `results` of a benchmark against the values of `param`.
+///
+/// Only the samples taken while sweeping `param` are used, i.e. those in which all other
+/// components have the values they were fixed at during that sweep, out of the
+/// [`baseline_results`].
+pub fn fit_quality(results: &[BenchmarkResults], param: BenchmarkParameter) -> FitReport {
+	let samples = sweep_samples(results, param);
+	let mean = mean(samples.iter().map(|s| s.1));
+	let total: f64 = samples.iter().map(|s| (s.1 - mean).powi(2)).sum();
+
+	if total == 0.0 {
+		// A constant cost is perfectly explained by any fit.
+		return FitReport {
+			param,
+			linear_r_squared: 1.0,
+			quadratic_r_squared: 1.0,
+			nonlinear: false,
+			suggested_degree: 0,
+		};
+	}
+
+	let (slope, intercept) = linear_fit(&samples);
+	let linear_residual = residual(&samples, |x| intercept + slope * x);
+	let quadratic_residual = quadratic_fit(&samples)
+		.map(|[a, b, c]| residual(&samples, |x| a + b * x + c * x * x))
+		.unwrap_or(linear_residual)
+		.min(linear_residual);
+
+	let nonlinear = linear_residual / total > LINEAR_FIT_TOLERANCE &&
+		linear_residual - quadratic_residual > QUADRATIC_IMPROVEMENT * linear_residual;
+
+	FitReport {
+		param,
+		linear_r_squared: 1.0 - linear_residual / total,
+		quadratic_r_squared: 1.0 - quadratic_residual / total,
+		nonlinear,
+		suggested_degree: if nonlinear { 2 } else { 1 },
+	}
+}
+
+/// Returns a warning for every component of `benchmark` whose cost doesn't measurably change
+/// across a wide range of values.
+///
+/// This usually points at a setup whose dispatch doesn't actually depend on the component.
+pub fn flat_slope_warnings(benchmark: &str, results: &[BenchmarkResults]) -> Vec<String> {
+	real_components(results).into_iter()
+		.filter_map(|param| {
+			let samples = sweep_samples(results, param);
+			let low = samples.iter().map(|s| s.0).fold(f64::INFINITY, f64::min);
+			let high = samples.iter().map(|s| s.0).fold(f64::NEG_INFINITY, f64::max);
+			if samples.len() < 3 || high - low < FLAT_SLOPE_MIN_RANGE {
+				return None;
+			}
+
+			let Regression { slope, slope_std_error, .. } = regression(param, &samples)?;
+
+			if slope == 0.0 || slope.abs() < FLAT_SLOPE_T_VALUE * slope_std_error {
+				Some(format!(
+					"Benchmark {} doesn't scale with component {:?} across {}..={}, \
+					check that its setup is used by the dispatch.",
+					benchmark,
+					param,
+					low,
+					high,
+				))
+			} else {
+				None
+			}
+		})
+		.collect()
+}
+
+/// How the time measured for one assignment of the components changed between two runs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentDelta {
+	/// The assignment was measured in both runs.
+	Changed {
+		/// The assignment of the components.
+		components: Vec<(BenchmarkParameter, u32)>,
+		/// The mean time of the baseline, in nanoseconds.
+		baseline: u128,
+		/// The mean time of the current run, in nanoseconds.
+		current: u128,
+		/// The current time minus the baseline time, in nanoseconds.
+		absolute: i128,
+		/// The absolute change in percent of the baseline time, `None` if the baseline time is
+		/// zero.
+		percent: Option<f64>,
+	},
+	/// The assignment was only measured in the current run.
+	Added {
+		/// The assignment of the components.
+		components: Vec<(BenchmarkParameter, u32)>,
+		/// The mean time of the current run, in nanoseconds.
+		current: u128,
+	},
+	/// The assignment was only measured in the baseline.
+	Removed {
+		/// The assignment of the components.
+		components: Vec<(BenchmarkParameter, u32)>,
+		/// The mean time of the baseline, in nanoseconds.
+		baseline: u128,
+	},
+}
+
+/// Compare the `current` results of a benchmark to the `baseline` results.
+///
+/// Results are matched by their assignment of the components, whatever the order of the
+/// components in it, and compared by their mean time if an assignment was measured repeatedly.
+/// The deltas of the assignments in `baseline` come first, in the order of `baseline`, followed
+/// by those only measured in `current`.
+///
+/// The results don't record database reads or writes, so only the time is compared.
+pub fn compare_results(
+	baseline: &[BenchmarkResults],
+	current: &[BenchmarkResults],
+) -> Vec<ComponentDelta> {
+	let baseline = mean_times(baseline);
+	let current = mean_times(current);
+
+	let mut deltas: Vec<ComponentDelta> = baseline.iter().map(|(components, base)| {
+		match current.iter().find(|(c, _)| c == components) {
+			Some((_, now)) => {
+				let absolute = *now as i128 - *base as i128;
+				ComponentDelta::Changed {
+					components: components.clone(),
+					baseline: *base,
+					current: *now,
+					absolute,
+					percent: match *base {
+						0 => None,
+						base => Some(absolute as f64 * 100.0 / base as f64),
+					},
+				}
+			},
+			None => ComponentDelta::Removed { components: components.clone(), baseline: *base },
+		}
+	}).collect();
+
+	deltas.extend(current.into_iter()
+		.filter(|(components, _)| !baseline.iter().any(|(b, _)| b == components))
+		.map(|(components, current)| ComponentDelta::Added { components, current })
+	);
+	deltas
+}
+
+/// Count the times of the `results` with `param` at `value` in `buckets` buckets of equal width,
+/// spanning the fastest to the slowest of them.
+///
+/// Every bucket is returned as its lowest time along with its count, fastest first. A mean hides
+/// whether the times cluster, e.g. around a cache hit and a cache miss, the histogram doesn't.
+/// Returns no buckets if no result has `param` at `value`.
+pub fn histogram(
+	results: &[BenchmarkResults],
+	param: BenchmarkParameter,
+	value: u32,
+	buckets: usize,
+) -> Vec<(u128, u32)> {
+	let times: Vec<u128> = results.iter()
+		.filter(|(components, _)| components.iter().any(|c| *c == (param, value)))
+		.map(|(_, elapsed)| *elapsed)
+		.collect();
+	let (min, max) = match (times.iter().min(), times.iter().max()) {
+		(Some(min), Some(max)) if buckets > 0 => (*min, *max),
+		_ => return Vec::new(),
+	};
+
+	let buckets = buckets as u128;
+	let width = ((max - min) / buckets + 1).max(1);
+	let mut counts: Vec<(u128, u32)> = (0..buckets).map(|i| (min + i * width, 0)).collect();
+	for time in times {
+		let index = ((time - min) / width).min(buckets - 1);
+		counts[index as usize].1 += 1;
+	}
+	counts
+}
+
+/// Returns the mean time of every assignment in `results`, with the components of each sorted,
+/// in the order the assignments were first measured in.
+fn mean_times(results: &[BenchmarkResults]) -> Vec<(Vec<(BenchmarkParameter, u32)>, u128)> {
+	let mut times: Vec<(Vec<(BenchmarkParameter, u32)>, u128, u128)> = Vec::new();
+	for (components, time) in results {
+		let mut components = components.clone();
+		components.sort();
+		match times.iter_mut().find(|(c, _, _)| *c == components) {
+			Some((_, total, samples)) => {
+				*total += time;
+				*samples += 1;
+			},
+			None => times.push((components, *time, 1)),
+		}
+	}
+	times.into_iter().map(|(components, total, samples)| (components, total / samples)).collect()
+}
+
+/// Returns the `(value of param, elapsed)` samples of the sweep over `param`.
+///
+/// The results are grouped by the values of all other components, and the group covering the
+/// most distinct values of `param` is taken to be its sweep.
+fn sweep_samples(results: &[BenchmarkResults], param: BenchmarkParameter) -> Vec<(f64, f64)> {
+	sweep(results, param).map(|sweep| sweep.1).unwrap_or_default()
+}
+
+/// Returns the values all other components were fixed at during the sweep over `param`, see
+/// [`sweep_samples`].
+pub fn sweep_rest_values(
+	results: &[BenchmarkResults],
+	param: BenchmarkParameter,
+) -> Vec<(BenchmarkParameter, u32)> {
+	sweep(results, param).map(|sweep| sweep.0).unwrap_or_default()
+}
+
+/// Returns the values of the other components and the samples of the sweep over `param`.
+fn sweep(
+	results: &[BenchmarkResults],
+	param: BenchmarkParameter,
+) -> Option<(Vec<(BenchmarkParameter, u32)>, Vec<(f64, f64)>)> {
+	let mut groups: Vec<(Vec<(BenchmarkParameter, u32)>, Vec<(f64, f64)>)> = Vec::new();
+
+	for (components, elapsed) in &baseline_results(results) {
+		let value = match components.iter().find(|c| c.0 == param) {
+			Some(c) => c.1,
+			None => continue,
+		};
+		let others: Vec<_> = components.iter().filter(|c| c.0 != param).cloned().collect();
+		let sample = (value as f64, *elapsed as f64);
+
+		match groups.iter_mut().find(|g| g.0 == others) {
+			Some(group) => group.1.push(sample),
+			None => groups.push((others, vec![sample])),
+		}
+	}
+
+	groups.into_iter().max_by_key(|g| distinct_values(&g.1))
+}
+
+fn distinct_values(samples: &[(f64, f64)]) -> usize {
+	let mut values: Vec<u64> = samples.iter().map(|s| s.0 as u64).collect();
+	values.sort();
+	values.dedup();
+	values.len()
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+	let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+	if count == 0 { 0.0 } else { sum / count as f64 }
+}
+
+/// The sum of squared differences between the samples and `model`.
+fn residual(samples: &[(f64, f64)], model: impl Fn(f64) -> f64) -> f64 {
+	samples.iter().map(|(x, y)| (y - model(*x)).powi(2)).sum()
+}
+
+/// Returns the `(slope, intercept)` of the least squares line through `samples`.
+fn linear_fit(samples: &[(f64, f64)]) -> (f64, f64) {
+	let mean_x = mean(samples.iter().map(|s| s.0));
+	let mean_y = mean(samples.iter().map(|s| s.1));
+	let sxx: f64 = samples.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+	let sxy: f64 = samples.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+
+	let slope = if sxx == 0.0 { 0.0 } else { sxy / sxx };
+	(slope, mean_y - slope * mean_x)
+}
+
+fn regression(param: BenchmarkParameter, samples: &[(f64, f64)]) -> Option<Regression> {
+	let n = samples.len();
+	let mean_x = mean(samples.iter().map(|s| s.0));
+	let sxx: f64 = samples.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+	if n < 3 || sxx == 0.0 {
+		return None;
+	}
+
+	let (slope, intercept) = linear_fit(samples);
+	let variance = residual(samples, |x| intercept + slope * x) / (n - 2) as f64;
+	let slope_std_error = (variance / sxx).sqrt();
+	let intercept_std_error = (variance * (1.0 / n as f64 + mean_x * mean_x / sxx)).sqrt();
+
+	// Beyond the table the t-distribution is close enough to the normal distribution.
+	let t = T_95.get(n - 3).cloned().unwrap_or(1.96);
+	Some(Regression {
+		param,
+		slope,
+		intercept,
+		slope_std_error,
+		intercept_std_error,
+		slope_interval: (slope - t * slope_std_error, slope + t * slope_std_error),
+		intercept_interval: (intercept - t * intercept_std_error, intercept + t * intercept_std_error),
+	})
+}
+
+/// Returns the coefficients `[a, b, c]` of the least squares parabola `a + b x + c x²`
+/// through `samples`, or `None` if there are fewer than three distinct values of `x`.
+fn quadratic_fit(samples: &[(f64, f64)]) -> Option<[f64; 3]> {
+	if distinct_values(samples) < 3 {
+		return None;
+	}
+
+	// Solve the normal equations with Gaussian elimination.
+	let mut m = [[0f64; 4]; 3];
+	for (x, y) in samples {
+		let powers = [1.0, *x, x * x];
+		for (row, p) in m.iter_mut().zip(powers.iter()) {
+			for (cell, q) in row.iter_mut().zip(powers.iter()) {
+				*cell += p * q;
+			}
+			row[3] += p * y;
+		}
+	}
+
+	for col in 0..3 {
+		let pivot = (col..3)
+			.max_by(|a, b| m[*a][col].abs().partial_cmp(&m[*b][col].abs()).expect("no NaN; qed"))
+			.expect("range is not empty; qed");
+		if m[pivot][col] == 0.0 {
+			return None;
+		}
+		m.swap(col, pivot);
+		let pivot_row = m[col];
+		for (_, row) in m.iter_mut().enumerate().filter(|(i, _)| *i != col) {
+			let factor = row[col] / pivot_row[col];
+			for (cell, p) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+				*cell -= factor * p;
+			}
+		}
+	}
+
+	Some([m[0][3] / m[0][0], m[1][3] / m[1][1], m[2][3] / m[2][2]])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_benchmarking::BenchmarkParameter::*;
+
+	// Sweep `A` over `0..100` with `B` at its middle, and `B` over `0..10` with `A` at its middle.
+	fn results(cost: impl Fn(u32, u32) -> u128) -> Vec<BenchmarkResults> {
+		let a = (0..100).step_by(10).map(|a| (a, 5));
+		let b = (0..10).map(|b| (50, b));
+		a.chain(b).map(|(a, b)| (vec![(A, a), (B, b)], cost(a, b))).collect()
+	}
+
+	#[test]
+	fn linear_results_are_classified_as_linear() {
+		let report = fit_quality(&results(|a, b| 1_000 + 25 * a as u128 + b as u128), A);
+
+		assert_eq!(report.param, A);
+		assert!(report.linear_r_squared > 0.999, "{:?}", report);
+		assert!(!report.nonlinear);
+		assert_eq!(report.suggested_degree, 1);
+	}
+
+	#[test]
+	fn quadratic_results_are_classified_as_nonlinear() {
+		let report = fit_quality(&results(|a, _| 1_000 + 3 * (a * a) as u128), A);
+
+		assert!(report.linear_r_squared < report.quadratic_r_squared);
+		assert!(report.quadratic_r_squared > 0.999, "{:?}", report);
+		assert!(report.nonlinear);
+		assert_eq!(report.suggested_degree, 2);
+	}
+
+	#[test]
+	fn flat_wide_component_is_warned_about() {
+		// `A` doesn't affect the cost beyond some noise, while `B` does.
+		let noise = [3, 0, 4, 1, 5, 2, 0, 4, 1, 3];
+		let results = results(|a, b| 1_000 + noise[(a / 10) as usize] + 50 * b as u128);
+
+		let warnings = flat_slope_warnings("balances::transfer", &results);
+		assert_eq!(warnings.len(), 1, "{:?}", warnings);
+		assert!(warnings[0].contains("balances::transfer"));
+		assert!(warnings[0].contains("component A"));
+	}
+
+	#[test]
+	fn scaling_components_are_not_warned_about() {
+		let results = results(|a, b| 1_000 + 25 * a as u128 + 50 * b as u128);
+		assert!(flat_slope_warnings("balances::transfer", &results).is_empty());
+	}
+
+	#[test]
+	fn constant_results_suggest_degree_zero() {
+		let report = fit_quality(&results(|_, _| 1_000), B);
+
+		assert!(!report.nonlinear);
+		assert_eq!(report.suggested_degree, 0);
+	}
+
+	#[test]
+	fn confidence_intervals_contain_the_true_coefficients() {
+		// Deterministic noise in `-50..50`.
+		let mut seed = 42u64;
+		let mut noise = move || {
+			seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+			(seed >> 33) % 100
+		};
+		let results: Vec<BenchmarkResults> = (0..50)
+			.map(|a| (vec![(A, a * 10)], 10_000 + 25 * (a * 10) as u128 + noise() as u128 - 50))
+			.collect();
+
+		let regression = linear_regression(&results, A).unwrap();
+		let (low, high) = regression.slope_interval;
+		assert!(low < 25.0 && 25.0 < high, "{:?}", regression);
+		assert!(regression.slope_std_error > 0.0);
+
+		let (low, high) = regression.intercept_interval;
+		assert!(low < 10_000.0 && 10_000.0 < high, "{:?}", regression);
+	}
+
+	#[test]
+	fn no_regression_without_enough_samples() {
+		assert!(linear_regression(&[(vec![(A, 1)], 10), (vec![(A, 2)], 20)], A).is_none());
+		assert!(linear_regression(&results(|_, _| 1_000), C).is_none());
+	}
+
+	#[test]
+	fn results_are_compared_by_assignment() {
+		let baseline = vec![
+			(vec![(A, 1), (B, 1)], 1_000),
+			(vec![(A, 2), (B, 1)], 2_000),
+			(vec![(A, 2), (B, 1)], 2_200),
+			(vec![(A, 3), (B, 1)], 3_000),
+			(vec![(A, 0), (B, 0)], 0),
+		];
+		let current = vec![
+			(vec![(B, 1), (A, 1)], 1_100),
+			(vec![(A, 2), (B, 1)], 1_050),
+			(vec![(A, 4), (B, 1)], 4_000),
+			(vec![(A, 0), (B, 0)], 30),
+		];
+
+		assert_eq!(compare_results(&baseline, &current), vec![
+			ComponentDelta::Changed {
+				components: vec![(A, 1), (B, 1)],
+				baseline: 1_000,
+				current: 1_100,
+				absolute: 100,
+				percent: Some(10.0),
+			},
+			ComponentDelta::Changed {
+				components: vec![(A, 2), (B, 1)],
+				baseline: 2_100,
+				current: 1_050,
+				absolute: -1_050,
+				percent: Some(-50.0),
+			},
+			ComponentDelta::Removed { components: vec![(A, 3), (B, 1)], baseline: 3_000 },
+			// A change from a baseline of zero has no percentage.
+			ComponentDelta::Changed {
+				components: vec![(A, 0), (B, 0)],
+				baseline: 0,
+				current: 30,
+				absolute: 30,
+				percent: None,
+			},
+			ComponentDelta::Added { components: vec![(A, 4), (B, 1)], current: 4_000 },
+		]);
+	}
+
+	#[test]
+	fn bimodal_times_fill_two_clusters() {
+		// Cache hits take about 100, misses about 1000.
+		let results: Vec<BenchmarkResults> = (0..20u128)
+			.map(|i| (vec![(N, 5)], if i % 2 == 0 { 100 + i } else { 1_000 + i }))
+			.chain(vec![(vec![(N, 6)], 500)])
+			.collect();
+
+		let buckets = histogram(&results, N, 5, 10);
+		assert_eq!(buckets.len(), 10);
+		assert_eq!(buckets[0], (100, 10));
+		assert_eq!(buckets.iter().map(|(_, count)| count).sum::<u32>(), 20);
+
+		let populated: Vec<bool> = buckets.iter().map(|(_, count)| *count > 0).collect();
+		let clusters = populated.windows(2).filter(|w| !w[0] && w[1]).count()
+			+ if populated[0] { 1 } else { 0 };
+		assert_eq!(clusters, 2);
+
+		assert!(histogram(&results, N, 7, 10).is_empty());
+		assert_eq!(histogram(&results, N, 6, 3), vec![(500, 1), (501, 0), (502, 0)]);
+	}
+
+	#[test]
+	fn pseudo_components_are_not_fitted() {
+		let tagged: Vec<BenchmarkResults> = results(|a, _| 1_000 + 25 * a as u128).into_iter()
+			.flat_map(|(c, time)| {
+				let tag = |param, value| {
+					let mut c = c.clone();
+					c.push((param, value));
+					c
+				};
+				// The warm and read-only timings are much cheaper and don't scale.
+				vec![(tag(Cache, 0), time), (tag(Cache, 1), 10), (tag(ReadOnly, 1), 10)]
+			})
+			.collect();
+
+		let baseline = baseline_results(&tagged);
+		assert_eq!(baseline, results(|a, _| 1_000 + 25 * a as u128));
+		assert_eq!(real_components(&tagged), vec![A, B]);
+		assert_eq!(linear_regression(&tagged, A), linear_regression(&baseline, A));
+		assert!(flat_slope_warnings("transfer", &tagged).iter().all(|w| !w.contains("Cache")));
+	}
+}
@@ -0,0 +1,220 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistence of benchmark results across runs.
+//!
+//! Every run is appended as one JSON record per line to a log file, which
+//! allows tracking how the cost of a benchmark drifts over time.
+//!
+//! Every record says which version of the format it is in, and records of older versions are
+//! still read, with the fields they lack set to their defaults.
+
+use std::{fs, io::{self, BufRead, Write}, path::Path, time::{SystemTime, UNIX_EPOCH}};
+use serde::{Serialize, Deserialize};
+use frame_benchmarking::{BenchmarkParameter, BenchmarkResults};
+
+/// The version of the records written by `append_results_to_log`.
+///
+/// Version 1 stored every result as a tuple of its components and time, version 2 as a
+/// [`LoggedResult`].
+pub const RESULTS_LOG_VERSION: u32 = 2;
+
+/// A single benchmark run stored in a results log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggedRun {
+	/// Version of the record format.
+	pub version: u32,
+	/// Seconds since the UNIX epoch at which the run was logged.
+	pub timestamp: u64,
+	/// Name of the benchmark, e.g. `balances::transfer`.
+	pub name: String,
+	/// The results of the run.
+	pub results: Vec<LoggedResult>,
+}
+
+/// A single result of a benchmark run stored in a results log.
+///
+/// Fields added in later versions of the format must have a default, which records of earlier
+/// versions are read with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggedResult {
+	/// The components the call was measured with.
+	pub components: Vec<(BenchmarkParameter, u32)>,
+	/// The time the call took, in nanoseconds.
+	pub time: u128,
+	/// The number of storage reads of the call, if measured.
+	#[serde(default)]
+	pub reads: Option<u32>,
+	/// The number of storage writes of the call, if measured.
+	#[serde(default)]
+	pub writes: Option<u32>,
+}
+
+impl From<BenchmarkResults> for LoggedResult {
+	fn from((components, time): BenchmarkResults) -> Self {
+		LoggedResult { components, time, reads: None, writes: None }
+	}
+}
+
+/// A run as stored by version 1 of the format.
+#[derive(Deserialize)]
+struct LoggedRunV1 {
+	timestamp: u64,
+	name: String,
+	results: Vec<BenchmarkResults>,
+}
+
+/// Just the version of a record, to tell how to read the rest of it.
+#[derive(Deserialize)]
+struct RecordVersion {
+	version: u32,
+}
+
+/// Append `results` of the benchmark `name` to the log at `path`.
+///
+/// The file is created if it doesn't exist yet.
+pub fn append_results_to_log(
+	path: &Path,
+	name: &str,
+	results: &[BenchmarkResults],
+) -> io::Result<()> {
+	let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)
+		.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+		.as_secs();
+	let run = LoggedRun {
+		version: RESULTS_LOG_VERSION,
+		timestamp,
+		name: name.to_owned(),
+		results: results.iter().cloned().map(LoggedResult::from).collect(),
+	};
+
+	let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+	let mut line = serde_json::to_vec(&run)?;
+	line.push(b'\n');
+	file.write_all(&line)
+}
+
+/// Read all runs stored in the log at `path`, oldest first.
+pub fn read_results_log(path: &Path) -> io::Result<Vec<LoggedRun>> {
+	let file = fs::File::open(path)?;
+
+	io::BufReader::new(file)
+		.lines()
+		.filter(|line| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+		.map(|line| -> io::Result<LoggedRun> { Ok(decode_run(&line?)?) })
+		.collect()
+}
+
+/// Decode the record `line` of any version up to [`RESULTS_LOG_VERSION`] into the current one.
+fn decode_run(line: &str) -> serde_json::Result<LoggedRun> {
+	match serde_json::from_str::<RecordVersion>(line)?.version {
+		1 => {
+			let run: LoggedRunV1 = serde_json::from_str(line)?;
+			Ok(LoggedRun {
+				version: RESULTS_LOG_VERSION,
+				timestamp: run.timestamp,
+				name: run.name,
+				results: run.results.into_iter().map(LoggedResult::from).collect(),
+			})
+		},
+		RESULTS_LOG_VERSION => {
+			let run: LoggedRun = serde_json::from_str(line)?;
+			Ok(run)
+		},
+		version => Err(serde::de::Error::custom(format!(
+			"Unsupported results log version {}, expected at most {}",
+			version,
+			RESULTS_LOG_VERSION,
+		))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn runs_are_read_back_in_order() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("results.log");
+
+		let first = vec![
+			(vec![(BenchmarkParameter::N, 1)], 100),
+			(vec![(BenchmarkParameter::N, 2)], 200),
+		];
+		let second = vec![(vec![(BenchmarkParameter::N, 1)], 110)];
+
+		append_results_to_log(&path, "timestamp::set", &first).unwrap();
+		append_results_to_log(&path, "timestamp::set", &second).unwrap();
+
+		let runs = read_results_log(&path).unwrap();
+		assert_eq!(runs.len(), 2);
+		assert!(runs.iter().all(|r| r.version == RESULTS_LOG_VERSION));
+		assert!(runs.iter().all(|r| r.name == "timestamp::set"));
+		assert!(runs[0].timestamp <= runs[1].timestamp);
+		let logged = |results: Vec<BenchmarkResults>| -> Vec<LoggedResult> {
+			results.into_iter().map(LoggedResult::from).collect()
+		};
+		assert_eq!(runs[0].results, logged(first));
+		assert_eq!(runs[1].results, logged(second));
+	}
+
+	#[test]
+	fn version_1_records_are_read_with_defaults() {
+		let v1 = concat!(
+			r#"{"version":1,"timestamp":7,"name":"timestamp::set","#,
+			r#""results":[[[["N",1]],100]]}"#,
+		);
+
+		let run = decode_run(v1).unwrap();
+		assert_eq!(run, LoggedRun {
+			version: RESULTS_LOG_VERSION,
+			timestamp: 7,
+			name: "timestamp::set".into(),
+			results: vec![LoggedResult {
+				components: vec![(BenchmarkParameter::N, 1)],
+				time: 100,
+				reads: None,
+				writes: None,
+			}],
+		});
+	}
+
+	#[test]
+	fn current_records_round_trip() {
+		let run = LoggedRun {
+			version: RESULTS_LOG_VERSION,
+			timestamp: 7,
+			name: "balances::transfer".into(),
+			results: vec![LoggedResult {
+				components: vec![(BenchmarkParameter::U, 10), (BenchmarkParameter::E, 2)],
+				time: 1_500,
+				reads: Some(3),
+				writes: Some(2),
+			}],
+		};
+
+		let line = serde_json::to_string(&run).unwrap();
+		assert_eq!(decode_run(&line).unwrap(), run);
+
+		let newer = line.replacen(
+			&format!("\"version\":{}", RESULTS_LOG_VERSION),
+			&format!("\"version\":{}", RESULTS_LOG_VERSION + 1),
+			1,
+		);
+		assert!(decode_run(&newer).is_err());
+	}
+}
@@ -14,7 +14,23 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-use sp_runtime::{BuildStorage, traits::{Block as BlockT, Header as HeaderT, NumberFor}};
+mod analysis;
+mod history;
+mod weights;
+
+pub use analysis::{
+	baseline_results, compare_results, fit_quality, flat_slope_warnings, histogram,
+	linear_regression, real_components, ComponentDelta, FitReport, Regression,
+};
+pub use history::{
+	append_results_to_log, read_results_log, LoggedResult, LoggedRun, RESULTS_LOG_VERSION,
+};
+pub use weights::{
+	fit_weights, weight_files, weight_functions, weight_overflow_warnings, write_weight_files,
+	WeightFormula, WeightUnit, MAX_WEIGHT,
+};
+
+use sp_runtime::{BuildStorage, Storage, traits::{Block as BlockT, Header as HeaderT, NumberFor}};
 use sc_client::StateMachine;
 use sc_cli::{ExecutionStrategy, WasmExecutionMethod};
 use sc_client_db::BenchmarkingState;
@@ -22,18 +38,27 @@ use sc_service::{RuntimeGenesis, ChainSpecExtension};
 use sc_executor::{NativeExecutor, NativeExecutionDispatch};
 use std::fmt::Debug;
 use codec::{Encode, Decode};
-use frame_benchmarking::BenchmarkResults;
+use frame_benchmarking::{
+	AdaptiveRepeat, BenchmarkBatch, BenchmarkMetadata, BenchmarkRequest, BenchmarkResults,
+	OnTimeout, PalletResults, RestPolicy, SnapshotDir, group_by_pallet,
+};
+use sp_externalities::Extensions;
+use sp_core::traits::KeystoreExt;
 
 /// The `benchmark` command used to benchmark FRAME Pallets.
 #[derive(Debug, structopt::StructOpt, Clone)]
 pub struct BenchmarkCmd {
 	/// Select a FRAME Pallet to benchmark.
-	#[structopt(short, long)]
-	pub pallet: String,
+	#[structopt(short, long, required_unless = "list")]
+	pub pallet: Option<String>,
 
 	/// Select an extrinsic to benchmark.
-	#[structopt(short, long)]
-	pub extrinsic: String,
+	#[structopt(short, long, required_unless = "list")]
+	pub extrinsic: Option<String>,
+
+	/// List the available benchmarks and their components instead of running one.
+	#[structopt(long)]
+	pub list: bool,
 
 	/// Select how many samples we should take across the variable components.
 	#[structopt(short, long, default_value = "1")]
@@ -43,6 +68,50 @@ pub struct BenchmarkCmd {
 	#[structopt(short, long, default_value = "1")]
 	pub repeat: u32,
 
+	/// Repeat every assignment until the mean of its times is stable, at most this many times.
+	///
+	/// `--repeat` is then the least number of times.
+	#[structopt(long = "adaptive-repeat", value_name = "MAX")]
+	pub adaptive_repeat: Option<u32>,
+
+	/// The relative standard error of the mean `--adaptive-repeat` stops at, per thousand.
+	#[structopt(long = "max-error", value_name = "PERMILLE", default_value = "10")]
+	pub max_error: u32,
+
+	/// Seed the randomness of the instancers with this value.
+	///
+	/// Runs with the same seed prepare the same instances.
+	#[structopt(long, default_value = "0")]
+	pub seed: u32,
+
+	/// Split the component sweep across this many threads.
+	///
+	/// Each thread benchmarks against its own copy of the genesis state.
+	#[structopt(long, default_value = "1")]
+	pub workers: u32,
+
+	/// Give up on an assignment whose iterations take longer than this many milliseconds.
+	#[structopt(long = "iteration-timeout", value_name = "MS")]
+	pub iteration_timeout: Option<u64>,
+
+	/// Abort the benchmark instead of skipping assignments hitting `--iteration-timeout`.
+	#[structopt(long = "abort-on-timeout", requires = "iteration-timeout")]
+	pub abort_on_timeout: bool,
+
+	/// Where the components not being swept sit in their range while another one is.
+	#[structopt(
+		long,
+		value_name = "POLICY",
+		possible_values = &["midpoint", "low", "high"],
+		default_value = "midpoint",
+		parse(try_from_str = parse_rest_policy),
+	)]
+	pub rest: RestPolicy,
+
+	/// Let the host settle between two iterations, so background work doesn't bleed into them.
+	#[structopt(long)]
+	pub settle: bool,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub shared_params: sc_cli::SharedParams,
@@ -65,6 +134,62 @@ pub struct BenchmarkCmd {
 		default_value = "Interpreted"
 	)]
 	pub wasm_method: WasmExecutionMethod,
+
+	/// Warn about components the measured cost doesn't scale with.
+	#[structopt(long = "check-slopes")]
+	pub check_slopes: bool,
+
+	/// Write a snapshot of the storage to this directory whenever a dispatch fails.
+	#[structopt(long = "snapshot-dir", value_name = "PATH", parse(from_os_str))]
+	pub snapshot_dir: Option<std::path::PathBuf>,
+
+	/// Append the results to this file, one JSON record per run.
+	///
+	/// The file is created if it doesn't exist.
+	#[structopt(long = "results-log", value_name = "PATH", parse(from_os_str))]
+	pub results_log: Option<std::path::PathBuf>,
+
+	/// Write the results to this file as JSON, nested by pallet and benchmark.
+	#[structopt(long = "json-output", value_name = "PATH", parse(from_os_str))]
+	pub json_output: Option<std::path::PathBuf>,
+
+	/// Measure against the state in this snapshot file and a warm cache.
+	///
+	/// The iterations don't reset the storage to the genesis state, so they see the changes of
+	/// the earlier ones.
+	#[structopt(long = "warm-snapshot", value_name = "PATH", parse(from_os_str))]
+	pub warm_snapshot: Option<std::path::PathBuf>,
+
+	/// Print the weight formula fitted to the results as a Rust function.
+	#[structopt(long = "weights")]
+	pub weights: bool,
+
+	/// Scale the fitted weights to a reference machine by this factor.
+	///
+	/// The factor is the time the reference machine takes per nanosecond this machine takes,
+	/// e.g. `2.0` for a reference machine twice as slow. Needs `--weights` or `--output`.
+	#[structopt(long = "reference-factor", value_name = "FACTOR")]
+	pub reference_factor: Option<f64>,
+
+	/// The units of the fitted weights, separated by commas.
+	///
+	/// Weights used to be in nanoseconds. Give both units to emit a function per unit from the
+	/// same fit while moving over, each named after its unit, e.g. `transfer_picoseconds`.
+	#[structopt(
+		long = "weight-unit",
+		value_name = "UNIT",
+		possible_values = &["nanoseconds", "picoseconds"],
+		default_value = "nanoseconds",
+		use_delimiter = true,
+		parse(try_from_str = parse_weight_unit),
+	)]
+	pub weight_units: Vec<WeightUnit>,
+
+	/// Write the fitted weight functions to this directory, one file per pallet.
+	///
+	/// Files are named after their pallet, e.g. `balances.rs`, and replace existing ones.
+	#[structopt(long = "output", value_name = "DIR", parse(from_os_str))]
+	pub output: Option<std::path::PathBuf>,
 }
 
 impl BenchmarkCmd {
@@ -74,13 +199,28 @@ impl BenchmarkCmd {
 		config: &mut sc_service::config::Configuration<G, E>,
 		spec_factory: impl FnOnce(&str) -> Result<Option<sc_service::ChainSpec<G, E>>, String>,
 		version: &sc_cli::VersionInfo,
-	) -> sc_cli::error::Result<()> where
+	) -> sc_cli::error::Result<sc_cli::Initialized> where
 		G: sc_service::RuntimeGenesis,
 		E: sc_service::ChainSpecExtension,
 	{
-		sc_cli::init_config(config, &self.shared_params, version, spec_factory)?;
+		self.validate()?;
+		let initialized = sc_cli::init_config(config, &self.shared_params, version, spec_factory)?;
+		if initialized != sc_cli::Initialized::Run {
+			return Ok(initialized);
+		}
 		// make sure to configure keystore
-		sc_cli::fill_config_keystore_in_memory(config).map_err(Into::into)
+		sc_cli::fill_config_keystore_in_memory(config)?;
+		Ok(sc_cli::Initialized::Run)
+	}
+
+	/// Check for flags that have no effect without some other flag.
+	pub fn validate(&self) -> Result<(), String> {
+		if self.reference_factor.is_some() && !self.weights && self.output.is_none() {
+			return Err("`--reference-factor` scales the fitted weights, \
+				so it needs `--weights` or `--output`".into());
+		}
+
+		Ok(())
 	}
 
 	/// Runs the command and benchmarks the chain.
@@ -101,46 +241,145 @@ impl BenchmarkCmd {
 		let strategy = self.execution.unwrap_or(ExecutionStrategy::Native);
 
 		let genesis_storage = spec.build_storage()?;
-		let mut changes = Default::default();
-		let state = BenchmarkingState::<BB>::new(genesis_storage)?;
-		let executor = NativeExecutor::<ExecDispatch>::new(
-			wasm_method,
-			None, // heap pages
-		);
-		let result = StateMachine::<_, _, NumberFor<BB>, _>::new(
-			&state,
-			None,
-			&mut changes,
-			&executor,
-			"Benchmark_dispatch_benchmark",
-			&(&self.pallet, &self.extrinsic, self.steps, self.repeat).encode(),
-			Default::default(),
-		)
-		.execute(strategy.into())
-		.map_err(|e| format!("Error executing runtime benchmark: {:?}", e))?;
-		let results = <Option<Vec<BenchmarkResults>> as Decode>::decode(&mut &result[..])
-			.unwrap_or(None);
-
-		if let Some(results) = results {
+
+		if self.list {
+			let result = execute_runtime_call::<BB, ExecDispatch>(
+				genesis_storage,
+				wasm_method,
+				strategy,
+				"Benchmark_list_benchmarks",
+				&[],
+				None,
+			)?;
+			let list = <Vec<(Vec<u8>, Vec<BenchmarkMetadata>)> as Decode>::decode(&mut &result[..])
+				.map_err(|e| format!("Failed to decode the list of benchmarks: {:?}", e))?;
+			print!("{}", format_benchmark_list(&list));
+			return Ok(());
+		}
+
+		let pallet = self.pallet.clone().unwrap_or_default();
+		let extrinsic = self.extrinsic.clone().unwrap_or_default();
+		let workers = self.workers.max(1);
+		let handles = (0..workers).map(|worker| {
+			let genesis_storage = genesis_storage.clone();
+			let request = BenchmarkRequest {
+				name: extrinsic.as_bytes().to_vec(),
+				steps: self.steps,
+				repeat: self.repeat,
+				seed: self.seed,
+				fixed: Vec::new(),
+				points: Vec::new(),
+				worker,
+				workers,
+				timeout: self.iteration_timeout.map(|ms| ms.saturating_mul(1_000_000)),
+				on_timeout: if self.abort_on_timeout { OnTimeout::Abort } else { OnTimeout::Skip },
+				warm_snapshot: self.warm_snapshot.as_ref()
+					.map(|path| path.to_string_lossy().into_owned().into_bytes()),
+				rest: self.rest,
+				settle: self.settle,
+				adaptive: self.adaptive_repeat.map(|max_repeat| AdaptiveRepeat {
+					max_error_permille: self.max_error,
+					max_repeat,
+				}),
+			};
+			let call_data = (&pallet, request).encode();
+			let snapshot_dir = self.snapshot_dir.clone();
+			std::thread::spawn(move || execute_benchmark::<BB, ExecDispatch>(
+				genesis_storage,
+				wasm_method,
+				strategy,
+				&call_data,
+				snapshot_dir,
+			))
+		}).collect::<Vec<_>>();
+
+		// Join in worker order, so the results are in the same order as for a serial sweep.
+		let mut results = Some(Vec::new());
+		for handle in handles {
+			let batch = handle.join()
+				.map_err(|_| "Benchmark worker panicked".to_string())??;
+			results = match (results, batch) {
+				(Some(mut results), Some(batch)) => {
+					results.extend(batch.results);
+					Some(results)
+				},
+				_ => None,
+			};
+		}
+
+		// Every sample may have been skipped or timed out.
+		let results = results.filter(|results| !results.is_empty());
+		if let Some(results) = results.map(|results| sorted_results(&results)) {
 			// Print benchmark metadata
 			println!(
 				"Pallet: {:?}, Extrinsic: {:?}, Steps: {:?}, Repeat: {:?}",
-				self.pallet,
-				self.extrinsic,
+				pallet,
+				extrinsic,
 				self.steps,
 				self.repeat,
 			);
 
-			// Print the table header
-			results[0].0.iter().for_each(|param| print!("{:?},", param.0));
+			print!("{}", format_results(&results));
+
+			for param in real_components(&results) {
+				let report = fit_quality(&results, param);
+				if report.nonlinear {
+					eprintln!(
+						"Warning: the cost of {:?} looks non-linear (linear R² {:.3}, quadratic R² {:.3}), \
+						consider modelling it with a polynomial of degree {}.",
+						param,
+						report.linear_r_squared,
+						report.quadratic_r_squared,
+						report.suggested_degree,
+					);
+				}
+			}
+
+			let name = format!("{}::{}", pallet, extrinsic);
+			if self.check_slopes {
+				for warning in flat_slope_warnings(&name, &results) {
+					eprintln!("Warning: {}", warning);
+				}
+			}
+
+			if let Some(path) = &self.results_log {
+				append_results_to_log(path, &name, &results)?;
+			}
+
+			if self.weights || self.output.is_some() {
+				let (factor, units) = (self.reference_factor, &self.weight_units);
+				for warning in weight_overflow_warnings(&results, factor, units, &extrinsic) {
+					eprintln!("Warning: {}", warning);
+				}
+			}
+
+			if self.weights {
+				print!("{}", weight_functions(
+					&results,
+					self.reference_factor,
+					&self.weight_units,
+					&extrinsic,
+				));
+			}
 
-			print!("time\n");
-			// Print the values
-			results.iter().for_each(|result| {
-				let parameters = &result.0;
-				parameters.iter().for_each(|param| print!("{:?},", param.1));
-				print!("{:?}\n", result.1);
-			});
+			let grouped = group_by_pallet(vec![BenchmarkBatch {
+				pallet: pallet.as_bytes().to_vec(),
+				benchmark: extrinsic.as_bytes().to_vec(),
+				results: results.clone(),
+			}]);
+
+			if let Some(dir) = &self.output {
+				let (factor, units) = (self.reference_factor, &self.weight_units);
+				for path in write_weight_files(dir, &grouped, factor, units)? {
+					eprintln!("Wrote weights to {}", path.display());
+				}
+			}
+
+			if let Some(path) = &self.json_output {
+				let json = serde_json::to_string_pretty(&results_to_json(&grouped))
+					.map_err(|e| format!("Failed to encode the results: {}", e))?;
+				std::fs::write(path, json)?;
+			}
 
 			eprintln!("Done.");
 		} else {
@@ -150,3 +389,314 @@ impl BenchmarkCmd {
 		Ok(())
 	}
 }
+
+/// Execute `Benchmark_dispatch_benchmark` with the encoded `call_data` on a fresh
+/// benchmarking state built from `genesis_storage`.
+fn execute_benchmark<BB, ExecDispatch>(
+	genesis_storage: Storage,
+	wasm_method: sc_executor::WasmExecutionMethod,
+	strategy: ExecutionStrategy,
+	call_data: &[u8],
+	snapshot_dir: Option<std::path::PathBuf>,
+) -> Result<Option<BenchmarkBatch>, String>
+where
+	BB: BlockT,
+	ExecDispatch: NativeExecutionDispatch + 'static,
+{
+	let result = execute_runtime_call::<BB, ExecDispatch>(
+		genesis_storage,
+		wasm_method,
+		strategy,
+		"Benchmark_dispatch_benchmark",
+		call_data,
+		snapshot_dir,
+	)?;
+
+	Ok(<Option<BenchmarkBatch> as Decode>::decode(&mut &result[..]).unwrap_or(None))
+}
+
+/// Call the runtime `method` with the encoded `call_data` on a fresh benchmarking state built
+/// from `genesis_storage`, returning the encoded result.
+fn execute_runtime_call<BB, ExecDispatch>(
+	genesis_storage: Storage,
+	wasm_method: sc_executor::WasmExecutionMethod,
+	strategy: ExecutionStrategy,
+	method: &str,
+	call_data: &[u8],
+	snapshot_dir: Option<std::path::PathBuf>,
+) -> Result<Vec<u8>, String>
+where
+	BB: BlockT,
+	ExecDispatch: NativeExecutionDispatch + 'static,
+{
+	let mut changes = Default::default();
+	let mut extensions = Extensions::new();
+	// Benchmarks generate the keys they sign with, see `frame_benchmarking::generate_signatures`.
+	extensions.register(KeystoreExt(sc_keystore::Store::new_in_memory()));
+	if let Some(dir) = snapshot_dir {
+		extensions.register(SnapshotDir(dir));
+	}
+	let state = BenchmarkingState::<BB>::new(genesis_storage)?;
+	let executor = NativeExecutor::<ExecDispatch>::new(
+		wasm_method,
+		None, // heap pages
+	);
+	StateMachine::<_, _, NumberFor<BB>, _>::new(
+		&state,
+		None,
+		&mut changes,
+		&executor,
+		method,
+		call_data,
+		extensions,
+	)
+	.execute(strategy.into())
+	.map_err(|e| format!("Error executing runtime benchmark: {:?}", e))
+}
+
+/// Returns `results` in the order they are printed and logged in.
+///
+/// The components of every result are sorted by `BenchmarkParameter`, and the results by their
+/// assignment and then by their time. The output is thus the same for the same results, whatever
+/// order the components were declared and the assignments measured in.
+pub fn sorted_results(results: &[BenchmarkResults]) -> Vec<BenchmarkResults> {
+	let mut sorted: Vec<BenchmarkResults> = results.iter()
+		.map(|(components, time)| {
+			let mut components = components.clone();
+			components.sort();
+			(components, *time)
+		})
+		.collect();
+	sorted.sort();
+	sorted
+}
+
+/// Returns the results of every pallet as a JSON object, mapping the names of the pallets to
+/// objects mapping the names of their benchmarks to their results.
+///
+/// Pallets and benchmarks are ordered by name, and the results as by [`sorted_results`].
+pub fn results_to_json(pallets: &[PalletResults]) -> serde_json::Value {
+	let pallets = pallets.iter().map(|p| {
+		let benchmarks = p.benchmarks.iter().map(|(name, results)| {
+			let results = serde_json::to_value(sorted_results(results))
+				.expect("benchmark results are always serializable; qed");
+			(String::from_utf8_lossy(name).into_owned(), results)
+		}).collect();
+		(String::from_utf8_lossy(&p.pallet).into_owned(), serde_json::Value::Object(benchmarks))
+	}).collect();
+	serde_json::Value::Object(pallets)
+}
+
+/// Format `results` as a table with a column per component, followed by the time.
+///
+/// The pseudo-components tagging only some results get a column of their own after the
+/// components, which is `0` for the results they don't tag.
+fn format_results(results: &[BenchmarkResults]) -> String {
+	let mut params = real_components(results);
+	let mut pseudo: Vec<_> = results.iter()
+		.flat_map(|(components, _)| components.iter().map(|(param, _)| *param))
+		.filter(|param| param.is_pseudo())
+		.collect();
+	pseudo.sort();
+	pseudo.dedup();
+	params.extend(pseudo);
+
+	let mut table = String::new();
+	params.iter().for_each(|param| table.push_str(&format!("{:?},", param)));
+	table.push_str("time\n");
+	for (components, time) in results {
+		for param in &params {
+			match components.iter().find(|(p, _)| p == param) {
+				Some((_, value)) => table.push_str(&format!("{:?},", value)),
+				None if param.is_pseudo() => table.push_str("0,"),
+				None => table.push(','),
+			}
+		}
+		table.push_str(&format!("{:?}\n", time));
+	}
+	table
+}
+
+/// Format the benchmarks of every pallet in `list` as a table of their components.
+///
+/// Every row holds a pallet, a benchmark, and one of its components along with its range and
+/// what it counts, if the benchmark says.
+fn format_benchmark_list(list: &[(Vec<u8>, Vec<BenchmarkMetadata>)]) -> String {
+	let mut table = String::from("pallet,benchmark,component,low,high,unit\n");
+	for (pallet, benchmarks) in list {
+		let pallet = String::from_utf8_lossy(pallet);
+		for benchmark in benchmarks {
+			let name = String::from_utf8_lossy(&benchmark.name);
+			if benchmark.components.is_empty() {
+				table.push_str(&format!("{},{},,,,\n", pallet, name));
+			}
+			for (component, low, high) in &benchmark.components {
+				let unit = benchmark.units.iter()
+					.find(|(param, _)| param == component)
+					.map(|(_, unit)| String::from_utf8_lossy(unit))
+					.unwrap_or_default();
+				table.push_str(&format!(
+					"{},{},{:?},{},{},{}\n",
+					pallet,
+					name,
+					component,
+					low,
+					high,
+					unit,
+				));
+			}
+		}
+	}
+	table
+}
+
+/// Parse the policy given by `--rest`.
+fn parse_rest_policy(policy: &str) -> Result<RestPolicy, String> {
+	match policy {
+		"midpoint" => Ok(RestPolicy::Midpoint),
+		"low" => Ok(RestPolicy::Low),
+		"high" => Ok(RestPolicy::High),
+		_ => Err(format!("Unknown rest policy '{}'", policy)),
+	}
+}
+
+/// Parse the unit given by `--weight-unit`.
+fn parse_weight_unit(unit: &str) -> Result<WeightUnit, String> {
+	match unit {
+		"nanoseconds" => Ok(WeightUnit::Nanoseconds),
+		"picoseconds" => Ok(WeightUnit::Picoseconds),
+		_ => Err(format!("Unknown weight unit '{}'", unit)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_benchmarking::BenchmarkParameter::*;
+	use structopt::StructOpt;
+
+	#[test]
+	fn benchmark_list_is_printed_as_a_table() {
+		let list = vec![
+			(b"balances".to_vec(), vec![
+				BenchmarkMetadata {
+					name: b"transfer".to_vec(),
+					components: vec![(U, 1, 1000), (E, 2, 1000)],
+					units: vec![(U, b"users".to_vec())],
+				},
+				BenchmarkMetadata { name: b"set_balance".to_vec(), components: vec![], units: vec![] },
+			]),
+			(b"timestamp".to_vec(), vec![
+				BenchmarkMetadata {
+					name: b"set".to_vec(),
+					components: vec![(N, 1, 100)],
+					units: vec![(N, b"milliseconds".to_vec())],
+				},
+			]),
+		];
+
+		assert_eq!(format_benchmark_list(&list), "\
+			pallet,benchmark,component,low,high,unit\n\
+			balances,transfer,U,1,1000,users\n\
+			balances,transfer,E,2,1000,\n\
+			balances,set_balance,,,,\n\
+			timestamp,set,N,1,100,milliseconds\n\
+		");
+	}
+
+	#[test]
+	fn json_results_are_nested_by_pallet() {
+		let grouped = group_by_pallet(vec![
+			BenchmarkBatch {
+				pallet: b"timestamp".to_vec(),
+				benchmark: b"set".to_vec(),
+				results: vec![(vec![(N, 1)], 10)],
+			},
+			BenchmarkBatch {
+				pallet: b"balances".to_vec(),
+				benchmark: b"transfer".to_vec(),
+				results: vec![(vec![(U, 2)], 30), (vec![(U, 1)], 20)],
+			},
+			BenchmarkBatch {
+				pallet: b"balances".to_vec(),
+				benchmark: b"set_balance".to_vec(),
+				results: vec![(vec![], 40)],
+			},
+		]);
+
+		assert_eq!(
+			results_to_json(&grouped).to_string(),
+			r#"{"balances":{"set_balance":[[[],40]],"transfer":[[[["U",1]],20],[[["U",2]],30]]},"#.to_owned()
+				+ r#""timestamp":{"set":[[[["N",1]],10]]}}"#,
+		);
+	}
+
+	#[test]
+	fn results_are_exported_in_a_stable_order() {
+		let results = vec![
+			(vec![(U, 2), (E, 3)], 2_000),
+			(vec![(U, 1), (E, 3)], 1_100),
+			(vec![(U, 1), (E, 3)], 1_000),
+			(vec![(U, 1), (E, 5)], 1_500),
+		];
+		let mut shuffled: Vec<BenchmarkResults> = results.iter().rev()
+			.map(|(components, time)| (components.iter().rev().cloned().collect(), *time))
+			.collect();
+		shuffled.swap(0, 2);
+
+		let csv = format_results(&sorted_results(&results));
+		assert_eq!(csv, format_results(&sorted_results(&results)));
+		assert_eq!(csv, format_results(&sorted_results(&shuffled)));
+		assert_eq!(csv, "E,U,time\n3,1,1000\n3,1,1100\n3,2,2000\n5,1,1500\n");
+
+		let json = serde_json::to_string(&sorted_results(&results)).unwrap();
+		assert_eq!(json, serde_json::to_string(&sorted_results(&shuffled)).unwrap());
+	}
+
+	#[test]
+	fn pseudo_components_get_columns_of_their_own() {
+		let results = vec![
+			(vec![(U, 1), (Origin, 0)], 1_000),
+			(vec![(U, 1), (Origin, 0), (ReadOnly, 1)], 700),
+			(vec![(U, 1), (Origin, 1)], 1_200),
+		];
+
+		assert_eq!(format_results(&results), "\
+			U,Origin,ReadOnly,time\n\
+			1,0,0,1000\n\
+			1,0,1,700\n\
+			1,1,0,1200\n\
+		");
+	}
+
+	#[test]
+	fn reference_factor_needs_fitted_weights() {
+		let cmd = |args: &[&str]| BenchmarkCmd::from_iter(
+			["benchmark", "-p", "balances", "-e", "transfer", "--reference-factor", "2"]
+				.iter()
+				.chain(args)
+				.cloned()
+		);
+
+		assert!(cmd(&[]).validate().is_err());
+		assert!(cmd(&["--weights"]).validate().is_ok());
+		assert!(cmd(&["--output", "weights"]).validate().is_ok());
+		assert_eq!(cmd(&["--output", "weights"]).reference_factor, Some(2.0));
+	}
+
+	#[test]
+	fn weight_units_are_separated_by_commas() {
+		let units = |args: &[&str]| BenchmarkCmd::from_iter(
+			["benchmark", "-p", "balances", "-e", "transfer"].iter().chain(args).cloned()
+		).weight_units;
+
+		assert_eq!(units(&[]), vec![WeightUnit::Nanoseconds]);
+		assert_eq!(
+			units(&["--weight-unit", "nanoseconds,picoseconds"]),
+			vec![WeightUnit::Nanoseconds, WeightUnit::Picoseconds],
+		);
+		assert!(BenchmarkCmd::from_iter_safe(
+			vec!["benchmark", "-p", "balances", "-e", "transfer", "--weight-unit", "femtoseconds"]
+		).is_err());
+	}
+}
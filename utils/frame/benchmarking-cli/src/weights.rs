@@ -0,0 +1,456 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weight formulas fitted to the results of a benchmark.
+//!
+//! A formula is a base weight plus a weight per unit of every component, one unit of weight
+//! being a nanosecond on the machine the benchmark ran on, unless scaled to a reference
+//! machine. Formulas can be converted to other units of weight, see [`WeightUnit`].
+
+use std::{fmt::Write, fs, io, path::{Path, PathBuf}};
+use frame_benchmarking::{BenchmarkParameter, BenchmarkResults, PalletResults};
+use crate::analysis::{baseline_results, linear_regression, real_components, sweep_rest_values};
+
+/// The largest weight the `Weight` type of the runtime holds, coefficients above it are clamped.
+pub const MAX_WEIGHT: u64 = u32::max_value() as u64;
+
+/// The weight of a dispatch as a function of the components of its benchmark.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightFormula {
+	/// The weight of the dispatch with all components at zero.
+	pub base: u64,
+	/// The weight added per unit of each component the cost scales with.
+	pub slopes: Vec<(BenchmarkParameter, u64)>,
+	/// The factor the measured times were scaled by, if any.
+	pub reference_factor: Option<f64>,
+	/// The unit of the weights.
+	pub unit: WeightUnit,
+}
+
+/// The time one unit of weight stands for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightUnit {
+	/// A nanosecond, the legacy unit the benchmarks measure in.
+	Nanoseconds,
+	/// A picosecond, i.e. a thousandth of the legacy unit.
+	Picoseconds,
+}
+
+impl WeightUnit {
+	/// The number of units in a nanosecond.
+	pub fn per_nanosecond(self) -> u64 {
+		match self {
+			WeightUnit::Nanoseconds => 1,
+			WeightUnit::Picoseconds => 1_000,
+		}
+	}
+
+	fn name(self) -> &'static str {
+		match self {
+			WeightUnit::Nanoseconds => "nanosecond",
+			WeightUnit::Picoseconds => "picosecond",
+		}
+	}
+}
+
+/// Fit a weight formula to the `results` of a benchmark.
+///
+/// Only the [`baseline_results`] are fitted, without their pseudo-components. Every component
+/// is fitted by the line through its own sweep. The intercept of a sweep still holds the cost of
+/// the values the other components were fixed at, so the base is the lowest intercept once that
+/// cost is taken off. Components without enough samples to fit, or whose cost decreases, add no
+/// weight.
+///
+/// The fitted coefficients are multiplied by `reference_factor`, e.g. the time a reference
+/// machine takes per nanosecond taken by the benchmarking machine. Without it the coefficients
+/// are left as measured. Either way they are in nanoseconds, see [`WeightFormula::in_unit`].
+pub fn fit_weights(results: &[BenchmarkResults], reference_factor: Option<f64>) -> WeightFormula {
+	let factor = reference_factor.unwrap_or(1.0);
+	let results = baseline_results(results);
+	let regressions: Vec<_> = real_components(&results).into_iter()
+		.filter_map(|param| linear_regression(&results, param))
+		.collect();
+
+	// The weight per unit of every component, as the formula adds it.
+	let slope = |param| regressions.iter()
+		.find(|r| r.param == param)
+		.map_or(0.0, |r| r.slope.max(0.0));
+	let base = regressions.iter()
+		.map(|r| sweep_rest_values(&results, r.param).into_iter()
+			.fold(r.intercept, |intercept, (param, value)| intercept - slope(param) * value as f64))
+		.fold(None, |min: Option<f64>, i| Some(min.map_or(i, |m| m.min(i))))
+		.unwrap_or_else(|| results.iter().map(|(_, t)| *t).min().unwrap_or(0) as f64);
+
+	WeightFormula {
+		base: scale(base, factor),
+		slopes: regressions.iter()
+			.map(|r| (r.param, scale(r.slope, factor)))
+			.filter(|(_, slope)| *slope > 0)
+			.collect(),
+		reference_factor,
+		unit: WeightUnit::Nanoseconds,
+	}
+}
+
+/// Returns `value * factor`, rounded and clamped to the range of a weight.
+fn scale(value: f64, factor: f64) -> u64 {
+	(value * factor).round().max(0.0).min(u64::max_value() as f64) as u64
+}
+
+impl WeightFormula {
+	/// Returns the formula with its coefficients converted to `unit`.
+	///
+	/// The coefficients are converted exactly, so formulas in different units derived from the
+	/// same fit differ by the ratio of their units only, up to saturation.
+	pub fn in_unit(&self, unit: WeightUnit) -> WeightFormula {
+		let convert = |weight: u64| {
+			if unit.per_nanosecond() >= self.unit.per_nanosecond() {
+				weight.saturating_mul(unit.per_nanosecond() / self.unit.per_nanosecond())
+			} else {
+				weight / (self.unit.per_nanosecond() / unit.per_nanosecond())
+			}
+		};
+		WeightFormula {
+			base: convert(self.base),
+			slopes: self.slopes.iter().map(|(param, slope)| (*param, convert(*slope))).collect(),
+			reference_factor: self.reference_factor,
+			unit,
+		}
+	}
+
+	/// Returns whether a coefficient of the formula exceeds [`MAX_WEIGHT`].
+	pub fn overflows(&self) -> bool {
+		self.base > MAX_WEIGHT || self.slopes.iter().any(|(_, slope)| *slope > MAX_WEIGHT)
+	}
+
+	/// Render the formula as a Rust function named `name` returning the weight.
+	///
+	/// The unit of the weight and the reference factor the coefficients were scaled by are
+	/// recorded in comments. Coefficients above [`MAX_WEIGHT`] are clamped to it, which is
+	/// recorded as well.
+	pub fn to_rust(&self, name: &str) -> String {
+		let mut out = String::new();
+		writeln!(out, "// One unit of weight is a {}.", self.unit.name())
+			.expect("writing to a string can't fail; qed");
+		if let Some(factor) = self.reference_factor {
+			writeln!(out, "// Scaled to the reference machine by a factor of {}.", factor)
+				.expect("writing to a string can't fail; qed");
+		}
+		if self.overflows() {
+			writeln!(out, "// Clamped to the largest weight of {}.", MAX_WEIGHT)
+				.expect("writing to a string can't fail; qed");
+		}
+
+		let args: Vec<String> = self.slopes.iter()
+			.map(|(param, _)| format!("{}: u32", param_name(*param)))
+			.collect();
+		writeln!(out, "pub fn {}({}) -> Weight {{", name, args.join(", "))
+			.and_then(|_| writeln!(out, "\t({} as Weight)", self.base.min(MAX_WEIGHT)))
+			.expect("writing to a string can't fail; qed");
+		for (param, slope) in &self.slopes {
+			writeln!(
+				out,
+				"\t\t.saturating_add(({} as Weight).saturating_mul({} as Weight))",
+				(*slope).min(MAX_WEIGHT),
+				param_name(*param),
+			).expect("writing to a string can't fail; qed");
+		}
+		out.push_str("}\n");
+		out
+	}
+}
+
+/// Fit a weight formula to the `results` of a benchmark once, and render it as a weight
+/// function named `name` in every unit of `units`, see [`fit_weights`].
+///
+/// With more than one unit, every function is named after its unit as well, e.g.
+/// `transfer_picoseconds`.
+pub fn weight_functions(
+	results: &[BenchmarkResults],
+	reference_factor: Option<f64>,
+	units: &[WeightUnit],
+	name: &str,
+) -> String {
+	unit_formulas(results, reference_factor, units, name)
+		.map(|(name, formula)| formula.to_rust(&name))
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+/// Returns a warning for every weight function of [`weight_functions`] whose coefficients are
+/// clamped to [`MAX_WEIGHT`].
+pub fn weight_overflow_warnings(
+	results: &[BenchmarkResults],
+	reference_factor: Option<f64>,
+	units: &[WeightUnit],
+	name: &str,
+) -> Vec<String> {
+	unit_formulas(results, reference_factor, units, name)
+		.filter(|(_, formula)| formula.overflows())
+		.map(|(name, formula)| format!(
+			"The weight function {} exceeds the largest weight of {} {}s and is clamped to it, \
+			consider a coarser unit of weight.",
+			name,
+			MAX_WEIGHT,
+			formula.unit.name(),
+		))
+		.collect()
+}
+
+/// Returns the formula fitted to `results` in every unit of `units`, along with the name of its
+/// weight function, see [`weight_functions`].
+fn unit_formulas<'a>(
+	results: &[BenchmarkResults],
+	reference_factor: Option<f64>,
+	units: &'a [WeightUnit],
+	name: &'a str,
+) -> impl Iterator<Item = (String, WeightFormula)> + 'a {
+	let formula = fit_weights(results, reference_factor);
+	units.iter().map(move |unit| {
+		let name = match units.len() {
+			1 => name.to_string(),
+			_ => format!("{}_{}s", name, unit.name()),
+		};
+		(name, formula.in_unit(*unit))
+	})
+}
+
+/// Render the weight file of every pallet in `pallets`, along with its file name.
+///
+/// A file holds the formula fitted to every benchmark of its pallet as functions named after
+/// the benchmark, in `units`, see [`weight_functions`]. It is named after the pallet in snake
+/// case, e.g. `balances.rs`.
+pub fn weight_files(
+	pallets: &[PalletResults],
+	reference_factor: Option<f64>,
+	units: &[WeightUnit],
+) -> Vec<(String, String)> {
+	pallets.iter().map(|pallet| {
+		let name = file_stem(&pallet.pallet);
+		let mut out = format!(
+			"//! Weights of the `{}` pallet, generated by the benchmark command.\n\n\
+			use frame_support::weights::Weight;\n",
+			name,
+		);
+		for (benchmark, results) in &pallet.benchmarks {
+			out.push('\n');
+			let name = file_stem(benchmark);
+			out.push_str(&weight_functions(results, reference_factor, units, &name));
+		}
+		(format!("{}.rs", name), out)
+	}).collect()
+}
+
+/// Write the weight file of every pallet in `pallets` to the directory `dir`, see
+/// [`weight_files`], returning their paths.
+///
+/// Every file is written to a temporary file first and then moved over any existing one, so
+/// readers see either the old or the new weights in full.
+pub fn write_weight_files(
+	dir: &Path,
+	pallets: &[PalletResults],
+	reference_factor: Option<f64>,
+	units: &[WeightUnit],
+) -> io::Result<Vec<PathBuf>> {
+	fs::create_dir_all(dir)?;
+	weight_files(pallets, reference_factor, units).into_iter().map(|(name, contents)| {
+		let path = dir.join(&name);
+		let temp = dir.join(format!(".{}.tmp", name));
+		fs::write(&temp, contents)?;
+		fs::rename(&temp, &path)?;
+		Ok(path)
+	}).collect()
+}
+
+/// Returns `name` in lower case, with everything but letters and digits replaced by `_`.
+fn file_stem(name: &[u8]) -> String {
+	String::from_utf8_lossy(name).chars()
+		.map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+		.collect()
+}
+
+/// The name of the argument of a weight function taking the value of `param`.
+fn param_name(param: BenchmarkParameter) -> String {
+	format!("{:?}", param).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_benchmarking::BenchmarkParameter::*;
+
+	fn results() -> Vec<BenchmarkResults> {
+		let a = (0..10).map(|a| (vec![(A, a * 10), (B, 5)], 1_000 + 25 * (a * 10) as u128 + 250));
+		let b = (0..10).map(|b| (vec![(A, 50), (B, b)], 1_000 + 25 * 50 + 50 * b as u128));
+		a.chain(b).collect()
+	}
+
+	#[test]
+	fn weights_are_fitted_as_measured_without_a_factor() {
+		let formula = fit_weights(&results(), None);
+		assert_eq!(formula, WeightFormula {
+			base: 1_000,
+			slopes: vec![(A, 25), (B, 50)],
+			reference_factor: None,
+			unit: WeightUnit::Nanoseconds,
+		});
+		assert!(!formula.to_rust("transfer").contains("reference"));
+	}
+
+	#[test]
+	fn weights_scale_linearly_with_the_reference_factor() {
+		let measured = fit_weights(&results(), None);
+		for factor in vec![2.0, 3.0, 10.0] {
+			let scaled = fit_weights(&results(), Some(factor));
+			assert_eq!(scaled.base, (measured.base as f64 * factor).round() as u64);
+			assert_eq!(scaled.reference_factor, Some(factor));
+			for (measured, scaled) in measured.slopes.iter().zip(&scaled.slopes) {
+				assert_eq!(measured.0, scaled.0);
+				assert_eq!(scaled.1, (measured.1 as f64 * factor).round() as u64);
+			}
+		}
+
+		let rendered = fit_weights(&results(), Some(2.0)).to_rust("transfer");
+		assert_eq!(rendered, "\
+			// One unit of weight is a nanosecond.\n\
+			// Scaled to the reference machine by a factor of 2.\n\
+			pub fn transfer(a: u32, b: u32) -> Weight {\n\
+			\t(2000 as Weight)\n\
+			\t\t.saturating_add((50 as Weight).saturating_mul(a as Weight))\n\
+			\t\t.saturating_add((100 as Weight).saturating_mul(b as Weight))\n\
+			}\n\
+		");
+	}
+
+	#[test]
+	fn weight_files_are_written_per_pallet() {
+		let dir = tempfile::tempdir().unwrap();
+		let pallets = vec![
+			PalletResults {
+				pallet: b"balances".to_vec(),
+				benchmarks: vec![
+					(b"set_balance".to_vec(), vec![(vec![(A, 0)], 300), (vec![(A, 10)], 300)]),
+					(b"transfer".to_vec(), results()),
+				],
+			},
+			PalletResults {
+				pallet: b"Identity".to_vec(),
+				benchmarks: vec![(b"set-identity".to_vec(), vec![(vec![], 700)])],
+			},
+		];
+
+		// Writing again replaces the earlier files.
+		fs::write(dir.path().join("balances.rs"), "stale").unwrap();
+		let paths = write_weight_files(dir.path(), &pallets, None, &[WeightUnit::Nanoseconds])
+			.unwrap();
+		assert_eq!(paths, vec![dir.path().join("balances.rs"), dir.path().join("identity.rs")]);
+
+		let mut names: Vec<_> = fs::read_dir(dir.path()).unwrap()
+			.map(|entry| entry.unwrap().file_name().into_string().unwrap())
+			.collect();
+		names.sort();
+		assert_eq!(names, vec!["balances.rs", "identity.rs"]);
+
+		assert_eq!(fs::read_to_string(&paths[0]).unwrap(), format!(
+			"//! Weights of the `balances` pallet, generated by the benchmark command.\n\n\
+			use frame_support::weights::Weight;\n\n\
+			// One unit of weight is a nanosecond.\n\
+			pub fn set_balance() -> Weight {{\n\
+			\t(300 as Weight)\n\
+			}}\n\n{}",
+			fit_weights(&results(), None).to_rust("transfer"),
+		));
+		assert_eq!(fs::read_to_string(&paths[1]).unwrap(), "\
+			//! Weights of the `identity` pallet, generated by the benchmark command.\n\n\
+			use frame_support::weights::Weight;\n\n\
+			// One unit of weight is a nanosecond.\n\
+			pub fn set_identity() -> Weight {\n\
+			\t(700 as Weight)\n\
+			}\n\
+		");
+	}
+
+	#[test]
+	fn units_differ_by_their_conversion_factor() {
+		let nanoseconds = fit_weights(&results(), Some(2.0));
+		let picoseconds = nanoseconds.in_unit(WeightUnit::Picoseconds);
+		let factor = WeightUnit::Picoseconds.per_nanosecond();
+		assert_eq!(factor, 1_000);
+
+		assert_eq!(picoseconds.unit, WeightUnit::Picoseconds);
+		assert_eq!(picoseconds.reference_factor, Some(2.0));
+		assert_eq!(picoseconds.base, nanoseconds.base * factor);
+		assert_eq!(
+			picoseconds.slopes,
+			nanoseconds.slopes.iter().map(|(p, slope)| (*p, slope * factor)).collect::<Vec<_>>(),
+		);
+		assert_eq!(picoseconds.in_unit(WeightUnit::Nanoseconds), nanoseconds);
+
+		let rendered = picoseconds.to_rust("transfer");
+		assert!(rendered.starts_with("// One unit of weight is a picosecond.\n"));
+		assert!(rendered.contains("\t(2000000 as Weight)\n"));
+	}
+
+	#[test]
+	fn weights_above_the_range_of_weight_are_clamped_with_a_warning() {
+		// 5ms per unit of `A` is 5_000_000_000 picoseconds, above `u32::max_value()`.
+		let results: Vec<_> = (0..10)
+			.map(|a| (vec![(A, a)], 1_000 + 5_000_000 * a as u128))
+			.collect();
+		let units = [WeightUnit::Nanoseconds, WeightUnit::Picoseconds];
+
+		let nanoseconds = fit_weights(&results, None);
+		let picoseconds = nanoseconds.in_unit(WeightUnit::Picoseconds);
+		assert!(!nanoseconds.overflows());
+		assert!(picoseconds.overflows());
+		assert_eq!(picoseconds.slopes, vec![(A, 5_000_000_000)]);
+
+		assert!(!nanoseconds.to_rust("transfer").contains("Clamped"));
+		assert_eq!(picoseconds.to_rust("transfer"), "\
+			// One unit of weight is a picosecond.\n\
+			// Clamped to the largest weight of 4294967295.\n\
+			pub fn transfer(a: u32) -> Weight {\n\
+			\t(1000000 as Weight)\n\
+			\t\t.saturating_add((4294967295 as Weight).saturating_mul(a as Weight))\n\
+			}\n\
+		");
+
+		assert_eq!(weight_overflow_warnings(&results, None, &units, "transfer"), vec![
+			"The weight function transfer_picoseconds exceeds the largest weight of 4294967295 \
+			picoseconds and is clamped to it, consider a coarser unit of weight.".to_string(),
+		]);
+		assert!(weight_overflow_warnings(&results, None, &units[..1], "transfer").is_empty());
+	}
+
+	#[test]
+	fn every_unit_is_emitted_from_one_fit() {
+		let formula = fit_weights(&results(), Some(2.0));
+		let units = [WeightUnit::Nanoseconds, WeightUnit::Picoseconds];
+
+		assert_eq!(
+			weight_functions(&results(), Some(2.0), &units, "transfer"),
+			format!(
+				"{}\n{}",
+				formula.to_rust("transfer_nanoseconds"),
+				formula.in_unit(WeightUnit::Picoseconds).to_rust("transfer_picoseconds"),
+			),
+		);
+		assert_eq!(
+			weight_functions(&results(), Some(2.0), &units[1..], "transfer"),
+			formula.in_unit(WeightUnit::Picoseconds).to_rust("transfer"),
+		);
+	}
+}